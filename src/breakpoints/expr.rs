@@ -0,0 +1,723 @@
+//! The breakpoint condition language.
+//!
+//! ```text
+//! any(cell == 'X')        a cell holds the character X
+//! sum > 1000              the numbers of the state add up to more than 1000
+//! changed(3, 4)           cell (3, 4) differs from the previous state
+//! tick % 100 == 0         every hundredth tick
+//! ```
+//!
+//! Numbers, `'text'` or `"text"`, `true` and `false`, combined with `+ - * / %`, comparisons and
+//! `and`/`or`/`not` (or `&&`/`||`/`!`). Variables are `tick`, `index`, `width`, `height`, and
+//! `sum`, `min`, `max` and `mean` over every number of the state. `cell(x, y)` reads a cell,
+//! `changed()` and `changed(x, y)` compare with the previous state, `abs(n)` is the absolute
+//! value. `any(..)`, `all(..)` and `count(..)` test a condition on every cell, which can use
+//! `cell`, `x` and `y`.
+
+use std::{cell::OnceCell, fmt, rc::Rc};
+
+use crate::story::{CellValue, FrameState};
+
+const VARIABLES: [&str; 11] = [
+    "tick", "index", "width", "height", "sum", "min", "max", "mean", "cell", "x", "y",
+];
+
+/// Names and accepted argument counts.
+const FUNCTIONS: [(&str, &[usize]); 6] = [
+    ("cell", &[2]),
+    ("changed", &[0, 2]),
+    ("any", &[1]),
+    ("all", &[1]),
+    ("count", &[1]),
+    ("abs", &[1]),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Ident(String),
+    Op(&'static str),
+}
+
+const OPERATORS: [&str; 18] = [
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "+", "-", "*", "/", "%", "!", "(", ")", ",", "=",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text
+                .parse()
+                .map_err(|_| format!("'{text}' is not a number"))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == '\'' || c == '"' {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&q| q == c)
+                .ok_or("unterminated text")?;
+            tokens.push(Token::Text(chars[i + 1..i + 1 + end].iter().collect()));
+            i += end + 2;
+        } else {
+            let rest: String = chars[i..].iter().take(2).collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(|| format!("unexpected '{c}'"))?;
+            // A lone `=` is almost certainly meant as a comparison.
+            tokens.push(Token::Op(if *op == "=" { "==" } else { op }));
+            i += op.chars().count();
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Var(String),
+    Call(String, Vec<Expr>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    /// Consumes the next token when it is the operator or keyword `word`.
+    fn eat(&mut self, word: &str) -> bool {
+        let matched = match self.peek() {
+            Some(Token::Op(op)) => *op == word,
+            Some(Token::Ident(ident)) => ident == word,
+            _ => false,
+        };
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn expect(&mut self, word: &str) -> Result<(), String> {
+        if self.eat(word) {
+            Ok(())
+        } else {
+            Err(format!("expected '{word}'"))
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut left = self.and()?;
+        while self.eat("or") || self.eat("||") {
+            left = Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.not()?;
+        while self.eat("and") || self.eat("&&") {
+            left = Expr::Binary(BinaryOp::And, Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.eat("not") || self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.additive()?;
+        let ops = [
+            ("==", BinaryOp::Eq),
+            ("!=", BinaryOp::Ne),
+            ("<=", BinaryOp::Le),
+            (">=", BinaryOp::Ge),
+            ("<", BinaryOp::Lt),
+            (">", BinaryOp::Gt),
+        ];
+        for (word, op) in ops {
+            if self.eat(word) {
+                return Ok(Expr::Binary(op, Box::new(left), Box::new(self.additive()?)));
+            }
+        }
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = if self.eat("+") {
+                BinaryOp::Add
+            } else if self.eat("-") {
+                BinaryOp::Sub
+            } else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.multiplicative()?));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        loop {
+            let op = if self.eat("*") {
+                BinaryOp::Mul
+            } else if self.eat("/") {
+                BinaryOp::Div
+            } else if self.eat("%") {
+                BinaryOp::Rem
+            } else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let token = self.peek().cloned().ok_or("unexpected end")?;
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Text(text) => Ok(Expr::Text(text)),
+            Token::Op("(") => {
+                let inner = self.or()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Token::Op(op) => Err(format!("unexpected '{op}'")),
+            Token::Ident(name) if name == "true" => Ok(Expr::Bool(true)),
+            Token::Ident(name) if name == "false" => Ok(Expr::Bool(false)),
+            Token::Ident(name) if self.eat("(") => {
+                let mut args = Vec::new();
+                if !self.eat(")") {
+                    loop {
+                        args.push(self.or()?);
+                        if self.eat(")") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                let (_, arities) = FUNCTIONS
+                    .iter()
+                    .find(|(f, _)| *f == name)
+                    .ok_or_else(|| format!("unknown function '{name}'"))?;
+                if !arities.contains(&args.len()) {
+                    return Err(format!("{name}() takes {arities:?} arguments"));
+                }
+                Ok(Expr::Call(name, args))
+            }
+            Token::Ident(name) if VARIABLES.contains(&name.as_str()) => Ok(Expr::Var(name)),
+            Token::Ident(name) => Err(format!("unknown variable '{name}'")),
+        }
+    }
+}
+
+impl Expr {
+    pub fn parse(source: &str) -> Result<Expr, String> {
+        let tokens = tokenize(source)?;
+        if tokens.is_empty() {
+            return Err("empty condition".to_string());
+        }
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected {token:?} after the condition")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Empty,
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{n}"),
+            Value::Text(text) => write!(f, "'{text}'"),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Empty => f.write_str("an empty cell"),
+        }
+    }
+}
+
+impl From<CellValue> for Value {
+    fn from(cell: CellValue) -> Self {
+        match cell {
+            CellValue::Empty => Value::Empty,
+            CellValue::Char(c) => Value::Text(c.to_string()),
+            CellValue::Number(v) => Value::Number(f64::from(v)),
+        }
+    }
+}
+
+impl Value {
+    /// Text reads as a number when it is one, so digit cells compare with numbers.
+    fn number(&self) -> Result<f64, String> {
+        match self {
+            Value::Number(n) => Ok(*n),
+            Value::Text(text) => text
+                .trim()
+                .parse()
+                .map_err(|_| format!("{self} is not a number")),
+            _ => Err(format!("{self} is not a number")),
+        }
+    }
+
+    fn condition(&self) -> Result<bool, String> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            _ => Err(format!("{self} is not a condition")),
+        }
+    }
+
+    pub fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Text(a), Value::Text(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Empty, Value::Empty) => true,
+            (Value::Empty, _) | (_, Value::Empty) => false,
+            (a, b) => matches!((a.number(), b.number()), (Ok(a), Ok(b)) if a == b),
+        }
+    }
+}
+
+/// `sum`, `min`, `max` and `mean` over the numbers of a state.
+#[derive(Debug, Clone, Copy)]
+struct Stats {
+    sum: f64,
+    min: f64,
+    max: f64,
+    count: usize,
+}
+
+impl Stats {
+    /// `None` when the state holds no number.
+    fn of(state: &FrameState) -> Option<Stats> {
+        let numbers: Box<dyn Iterator<Item = f64> + '_> = match state {
+            FrameState::Volume(volume) => Box::new(
+                volume
+                    .values
+                    .iter()
+                    .filter(|v| v.is_finite())
+                    .map(|&v| f64::from(v)),
+            ),
+            FrameState::Graph(graph) => {
+                Box::new(graph.nodes.iter().filter_map(|n| n.weight).map(f64::from))
+            }
+            state => {
+                let (width, height) = state.dimensions();
+                Box::new(
+                    (0..height)
+                        .flat_map(move |y| (0..width).map(move |x| (x, y)))
+                        .filter_map(move |(x, y)| state.cell(x, y).as_number())
+                        .map(f64::from),
+                )
+            }
+        };
+        numbers.fold(None, |stats, n| {
+            Some(match stats {
+                None => Stats {
+                    sum: n,
+                    min: n,
+                    max: n,
+                    count: 1,
+                },
+                Some(s) => Stats {
+                    sum: s.sum + n,
+                    min: s.min.min(n),
+                    max: s.max.max(n),
+                    count: s.count + 1,
+                },
+            })
+        })
+    }
+}
+
+/// What a condition is evaluated against.
+pub struct Scope<'a> {
+    pub state: &'a FrameState,
+    pub previous: Option<&'a FrameState>,
+    pub tick: u64,
+    pub index: usize,
+    /// The cell visited by `any`, `all` or `count`.
+    pub cell: Option<(usize, usize)>,
+    /// Computed on the first use and shared with the scopes of the visited cells.
+    stats: Rc<OnceCell<Option<Stats>>>,
+}
+
+impl<'a> Scope<'a> {
+    pub fn new(
+        state: &'a FrameState,
+        previous: Option<&'a FrameState>,
+        tick: u64,
+        index: usize,
+    ) -> Self {
+        Self {
+            state,
+            previous,
+            tick,
+            index,
+            cell: None,
+            stats: Rc::default(),
+        }
+    }
+}
+
+impl Scope<'_> {
+    fn stats(&self) -> Option<Stats> {
+        *self.stats.get_or_init(|| Stats::of(self.state))
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = self.state.dimensions();
+        (0..height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn at(&self, cell: (usize, usize)) -> Scope<'_> {
+        Scope {
+            cell: Some(cell),
+            stats: self.stats.clone(),
+            ..*self
+        }
+    }
+}
+
+fn position(x: &Value, y: &Value) -> Result<(usize, usize), String> {
+    let coordinate = |v: &Value| {
+        let n = v.number()?;
+        if n >= 0.0 && n.fract() == 0.0 {
+            Ok(n as usize)
+        } else {
+            Err(format!("{v} is not a cell coordinate"))
+        }
+    };
+    Ok((coordinate(x)?, coordinate(y)?))
+}
+
+impl Expr {
+    /// Whether the expression reads `cell`, `x` or `y` outside of `any`, `all` and `count`,
+    /// i.e. whether it is a condition on a single cell.
+    pub fn uses_cell(&self) -> bool {
+        match self {
+            Expr::Var(name) => matches!(name.as_str(), "cell" | "x" | "y"),
+            Expr::Call(name, _) if matches!(name.as_str(), "any" | "all" | "count") => false,
+            Expr::Call(_, args) => args.iter().any(Expr::uses_cell),
+            Expr::Not(inner) | Expr::Neg(inner) => inner.uses_cell(),
+            Expr::Binary(_, a, b) => a.uses_cell() || b.uses_cell(),
+            Expr::Number(_) | Expr::Text(_) | Expr::Bool(_) => false,
+        }
+    }
+
+    pub fn eval(&self, scope: &Scope) -> Result<Value, String> {
+        match self {
+            Expr::Number(n) => Ok(Value::Number(*n)),
+            Expr::Text(text) => Ok(Value::Text(text.clone())),
+            Expr::Bool(b) => Ok(Value::Bool(*b)),
+            Expr::Var(name) => eval_var(name, scope),
+            Expr::Call(name, args) => eval_call(name, args, scope),
+            Expr::Not(inner) => Ok(Value::Bool(!inner.eval(scope)?.condition()?)),
+            Expr::Neg(inner) => Ok(Value::Number(-inner.eval(scope)?.number()?)),
+            Expr::Binary(BinaryOp::And, a, b) => Ok(Value::Bool(
+                a.eval(scope)?.condition()? && b.eval(scope)?.condition()?,
+            )),
+            Expr::Binary(BinaryOp::Or, a, b) => Ok(Value::Bool(
+                a.eval(scope)?.condition()? || b.eval(scope)?.condition()?,
+            )),
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (a.eval(scope)?, b.eval(scope)?);
+                match op {
+                    BinaryOp::Eq => Ok(Value::Bool(a.equals(&b))),
+                    BinaryOp::Ne => Ok(Value::Bool(!a.equals(&b))),
+                    op => {
+                        let (a, b) = (a.number()?, b.number()?);
+                        Ok(match op {
+                            BinaryOp::Lt => Value::Bool(a < b),
+                            BinaryOp::Le => Value::Bool(a <= b),
+                            BinaryOp::Gt => Value::Bool(a > b),
+                            BinaryOp::Ge => Value::Bool(a >= b),
+                            BinaryOp::Add => Value::Number(a + b),
+                            BinaryOp::Sub => Value::Number(a - b),
+                            BinaryOp::Mul => Value::Number(a * b),
+                            BinaryOp::Div => Value::Number(a / b),
+                            BinaryOp::Rem => Value::Number(a % b),
+                            _ => unreachable!("handled above"),
+                        })
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn eval_var(name: &str, scope: &Scope) -> Result<Value, String> {
+    let (width, height) = scope.state.dimensions();
+    let number = |n: f64| -> Result<Value, String> { Ok(Value::Number(n)) };
+    let aggregate = |reduce: fn(Stats) -> f64| {
+        Ok(scope
+            .stats()
+            .map_or(Value::Empty, |stats| Value::Number(reduce(stats))))
+    };
+    let visited = || {
+        scope
+            .cell
+            .ok_or_else(|| format!("'{name}' only exists inside any(), all() and count()"))
+    };
+    match name {
+        "tick" => number(scope.tick as f64),
+        "index" => number(scope.index as f64),
+        "width" => number(width as f64),
+        "height" => number(height as f64),
+        "sum" => number(scope.stats().map_or(0.0, |stats| stats.sum)),
+        "min" => aggregate(|stats| stats.min),
+        "max" => aggregate(|stats| stats.max),
+        "mean" => aggregate(|stats| stats.sum / stats.count as f64),
+        "cell" => visited().map(|(x, y)| scope.state.cell(x, y).into()),
+        "x" => visited().map(|(x, _)| Value::Number(x as f64)),
+        "y" => visited().map(|(_, y)| Value::Number(y as f64)),
+        name => Err(format!("unknown variable '{name}'")),
+    }
+}
+
+fn eval_call(name: &str, args: &[Expr], scope: &Scope) -> Result<Value, String> {
+    let values = || {
+        args.iter()
+            .map(|a| a.eval(scope))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    match (name, args) {
+        ("cell", _) => {
+            let values = values()?;
+            let (x, y) = position(&values[0], &values[1])?;
+            Ok(scope.state.cell(x, y).into())
+        }
+        ("changed", []) => Ok(Value::Bool(
+            scope.previous.is_some_and(|p| p != scope.state),
+        )),
+        ("changed", _) => {
+            let values = values()?;
+            let (x, y) = position(&values[0], &values[1])?;
+            Ok(Value::Bool(
+                scope
+                    .previous
+                    .is_some_and(|p| p.cell(x, y) != scope.state.cell(x, y)),
+            ))
+        }
+        ("abs", _) => Ok(Value::Number(values()?[0].number()?.abs())),
+        ("any", [test]) => {
+            for cell in scope.cells() {
+                if test.eval(&scope.at(cell))?.condition()? {
+                    return Ok(Value::Bool(true));
+                }
+            }
+            Ok(Value::Bool(false))
+        }
+        ("all", [test]) => {
+            for cell in scope.cells() {
+                if !test.eval(&scope.at(cell))?.condition()? {
+                    return Ok(Value::Bool(false));
+                }
+            }
+            Ok(Value::Bool(true))
+        }
+        ("count", [test]) => {
+            let mut count = 0;
+            for cell in scope.cells() {
+                if test.eval(&scope.at(cell))?.condition()? {
+                    count += 1;
+                }
+            }
+            Ok(Value::Number(count as f64))
+        }
+        (name, _) => Err(format!("unknown function '{name}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story::{ScalarGrid, TextGrid};
+
+    fn eval(source: &str, state: &FrameState, previous: Option<&FrameState>, tick: u64) -> Value {
+        let expr = Expr::parse(source).unwrap_or_else(|e| panic!("{source}: {e}"));
+        expr.eval(&Scope::new(state, previous, tick, 0))
+            .unwrap_or_else(|e| panic!("{source}: {e}"))
+    }
+
+    fn text(lines: &[&str]) -> FrameState {
+        FrameState::Text(TextGrid::from_lines(lines.iter().copied()))
+    }
+
+    fn numbers(rows: Vec<Vec<f32>>) -> FrameState {
+        FrameState::Scalar(ScalarGrid::from_rows(rows))
+    }
+
+    #[test]
+    fn any_cell() {
+        let state = text(&["..", ".X"]);
+        assert_eq!(eval("any(cell == 'X')", &state, None, 0), Value::Bool(true));
+        assert_eq!(
+            eval("any(cell == 'Y')", &state, None, 0),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            eval("count(cell == '.')", &state, None, 0),
+            Value::Number(3.0)
+        );
+    }
+
+    #[test]
+    fn sum_of_numbers() {
+        let state = numbers(vec![vec![600.0, 500.0], vec![f32::NAN, -50.0]]);
+        assert_eq!(eval("sum > 1000", &state, None, 0), Value::Bool(true));
+        assert_eq!(eval("sum", &state, None, 0), Value::Number(1050.0));
+        assert_eq!(eval("min", &state, None, 0), Value::Number(-50.0));
+        assert_eq!(eval("max", &state, None, 0), Value::Number(600.0));
+        assert_eq!(eval("mean", &state, None, 0), Value::Number(350.0));
+        assert_eq!(eval("any(cell > mean)", &state, None, 0), Value::Bool(true));
+        assert_eq!(eval("min", &text(&["ab"]), None, 0), Value::Empty);
+    }
+
+    #[test]
+    fn changed_cell() {
+        let previous = numbers(vec![vec![0.0; 5]; 5]);
+        let mut rows = vec![vec![0.0; 5]; 5];
+        rows[4][3] = 1.0;
+        let state = numbers(rows);
+        let changed = |source| eval(source, &state, Some(&previous), 0);
+        assert_eq!(changed("changed(3, 4)"), Value::Bool(true));
+        assert_eq!(changed("changed(4, 3)"), Value::Bool(false));
+        assert_eq!(changed("changed()"), Value::Bool(true));
+        assert_eq!(eval("changed(3, 4)", &state, None, 0), Value::Bool(false));
+    }
+
+    #[test]
+    fn every_hundredth_tick() {
+        let state = text(&["."]);
+        assert_eq!(
+            eval("tick % 100 == 0", &state, None, 300),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            eval("tick % 100 == 0", &state, None, 301),
+            Value::Bool(false)
+        );
+    }
+
+    #[test]
+    fn precedence() {
+        let state = text(&["."]);
+        assert_eq!(eval("1 + 2 * 3", &state, None, 0), Value::Number(7.0));
+        assert_eq!(eval("(1 + 2) * 3", &state, None, 0), Value::Number(9.0));
+        assert_eq!(eval("-2 * 3 + 1", &state, None, 0), Value::Number(-5.0));
+        assert_eq!(
+            eval("true or false and false", &state, None, 0),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            eval("not 1 > 2 and 2 > 1", &state, None, 0),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            Expr::parse("1 + 2 < 4 || 0").unwrap(),
+            Expr::Binary(
+                BinaryOp::Or,
+                Box::new(Expr::Binary(
+                    BinaryOp::Lt,
+                    Box::new(Expr::Binary(
+                        BinaryOp::Add,
+                        Box::new(Expr::Number(1.0)),
+                        Box::new(Expr::Number(2.0)),
+                    )),
+                    Box::new(Expr::Number(4.0)),
+                )),
+                Box::new(Expr::Number(0.0)),
+            )
+        );
+    }
+
+    #[test]
+    fn single_equals_compares() {
+        assert_eq!(Expr::parse("tick = 3"), Expr::parse("tick == 3"));
+        assert_eq!(eval("tick = 3", &text(&["."]), None, 3), Value::Bool(true));
+    }
+
+    #[test]
+    fn unterminated_text() {
+        assert_eq!(
+            Expr::parse("any(cell == 'X)"),
+            Err("unterminated text".to_string())
+        );
+        assert!(Expr::parse("\"abc").is_err());
+    }
+
+    #[test]
+    fn wrong_argument_count() {
+        assert_eq!(
+            Expr::parse("changed(1)"),
+            Err("changed() takes [0, 2] arguments".to_string())
+        );
+        assert!(Expr::parse("cell(1)").is_err());
+        assert!(Expr::parse("any()").is_err());
+        assert!(Expr::parse("abs(1, 2)").is_err());
+    }
+}
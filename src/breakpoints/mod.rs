@@ -0,0 +1,113 @@
+//! Conditions that pause playback when they become true, see [`expr`] for the language.
+
+pub mod expr;
+
+use bevy::prelude::*;
+
+use crate::story::Story;
+use expr::{Expr, Scope};
+
+#[derive(Debug, Clone)]
+pub struct Breakpoint {
+    source: String,
+    condition: Result<Expr, String>,
+    pub enabled: bool,
+    /// Times the condition became true during playback.
+    pub hits: u32,
+    /// Why the last evaluation failed, cleared once it succeeds again.
+    pub error: Option<String>,
+    /// Frame last evaluated and the outcome, to only fire when the condition becomes true.
+    last: Option<(usize, bool)>,
+}
+
+impl Breakpoint {
+    pub fn new(source: impl Into<String>) -> Self {
+        let source = source.into();
+        Self {
+            condition: Expr::parse(&source),
+            source,
+            enabled: true,
+            hits: 0,
+            error: None,
+            last: None,
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The parse error of the condition, if any.
+    pub fn parse_error(&self) -> Option<&str> {
+        self.condition.as_ref().err().map(String::as_str)
+    }
+
+    pub fn set_source(&mut self, source: String) {
+        *self = Self {
+            enabled: self.enabled,
+            hits: self.hits,
+            ..Self::new(source)
+        };
+    }
+
+    fn armed(&self) -> bool {
+        self.enabled && self.condition.is_ok()
+    }
+
+    /// Evaluation errors count as false, and are kept for the panel.
+    fn holds(&mut self, story: &Story, index: usize, previous: Option<usize>) -> bool {
+        let Ok(condition) = &self.condition else {
+            return false;
+        };
+        let (Some(state), Some(tick)) = (story.state(index), story.tick(index)) else {
+            return false;
+        };
+        let previous = previous.and_then(|i| story.state(i));
+        let scope = Scope::new(&state, previous.as_deref(), tick, index);
+        let holds = condition.eval(&scope).and_then(|value| match value {
+            expr::Value::Bool(b) => Ok(b),
+            value => Err(format!("the condition gives {value}, not true or false")),
+        });
+        self.error = holds.as_ref().err().cloned();
+        let holds = holds.unwrap_or(false);
+        self.last = Some((index, holds));
+        holds
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct Breakpoints {
+    pub list: Vec<Breakpoint>,
+    /// Breakpoint that paused playback last, and the frame it paused on.
+    pub last_hit: Option<(usize, usize)>,
+}
+
+impl Breakpoints {
+    pub fn is_armed(&self) -> bool {
+        self.list.iter().any(Breakpoint::armed)
+    }
+
+    /// Evaluates the armed breakpoints on frame `index`, reached from `previous`, and returns
+    /// the first that became true. A breakpoint last evaluated elsewhere, e.g. before a seek,
+    /// is first evaluated on `previous` so that a condition already true does not fire.
+    pub fn check(&mut self, story: &Story, index: usize, previous: usize) -> Option<usize> {
+        let mut hit = None;
+        for (i, breakpoint) in self.list.iter_mut().enumerate() {
+            if !breakpoint.armed() {
+                continue;
+            }
+            let was = match breakpoint.last {
+                Some((at, holds)) if at == previous => holds,
+                _ => breakpoint.holds(story, previous, previous.checked_sub(1)),
+            };
+            if breakpoint.holds(story, index, Some(previous)) && !was {
+                breakpoint.hits += 1;
+                hit = hit.or(Some(i));
+            }
+        }
+        if let Some(i) = hit {
+            self.last_hit = Some((i, index));
+        }
+        hit
+    }
+}
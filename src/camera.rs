@@ -0,0 +1,625 @@
+//! The 3D camera: its modes and their controls, preset views, saved views, and the eased moves
+//! between them.
+
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI, TAU};
+
+use bevy::{
+    camera::{ScalingMode, primitives::Aabb},
+    input::mouse::{MouseMotion, MouseWheel},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use bevy_egui::EguiContexts;
+
+use crate::{
+    config::{AnimationConfig, InterpolationKind},
+    viewports::ViewportId,
+    visualization::{FrameBounds, TaggedEntity},
+};
+
+/// How long a move to another view takes.
+const TRANSITION_SECONDS: f32 = 0.6;
+
+/// Just short of straight up or down, where `look_at` loses track of the up axis.
+const MAX_PITCH: f32 = 1.5;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct OrbitCamera {
+    pub radius: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub target: Vec3,
+}
+
+impl OrbitCamera {
+    /// Looks at the centre of a box from `yaw` and `pitch`, far enough back to see all of it.
+    pub fn framing(center: Vec3, half_extents: Vec3, yaw: f32, pitch: f32) -> Self {
+        Self {
+            radius: (half_extents.length() * 2.2).max(0.1),
+            yaw,
+            pitch,
+            target: center,
+        }
+    }
+
+    /// Part way to `to`, turning the short way around.
+    pub fn lerp(&self, to: &OrbitCamera, t: f32) -> Self {
+        let mut turn = (to.yaw - self.yaw).rem_euclid(TAU);
+        if turn > PI {
+            turn -= TAU;
+        }
+        Self {
+            radius: self.radius + (to.radius - self.radius) * t,
+            yaw: self.yaw + turn * t,
+            pitch: self.pitch + (to.pitch - self.pitch) * t,
+            target: self.target.lerp(to.target, t),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraPreset {
+    Top,
+    Front,
+    Side,
+    Isometric,
+}
+
+impl CameraPreset {
+    pub const ALL: [CameraPreset; 4] = [
+        CameraPreset::Top,
+        CameraPreset::Front,
+        CameraPreset::Side,
+        CameraPreset::Isometric,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CameraPreset::Top => "Top",
+            CameraPreset::Front => "Front",
+            CameraPreset::Side => "Side",
+            CameraPreset::Isometric => "Iso",
+        }
+    }
+
+    /// Yaw and pitch of the preset.
+    fn angles(&self) -> (f32, f32) {
+        match self {
+            CameraPreset::Top => (0.0, -MAX_PITCH),
+            CameraPreset::Front => (0.0, 0.0),
+            CameraPreset::Side => (FRAC_PI_2, 0.0),
+            // The classic isometric elevation, atan(1 / sqrt(2)).
+            CameraPreset::Isometric => (FRAC_PI_4, -0.6155),
+        }
+    }
+
+    /// The preset seen around the target of `from`, at the same distance.
+    pub fn view(&self, from: &OrbitCamera) -> OrbitCamera {
+        let (yaw, pitch) = self.angles();
+        OrbitCamera {
+            yaw,
+            pitch,
+            ..*from
+        }
+    }
+}
+
+/// Where to move the primary camera, see [`move_camera_system`].
+#[derive(Message, Debug, Clone, Copy)]
+pub enum MoveCamera {
+    Preset(CameraPreset),
+    /// Fits everything the renderer spawned, keeping the current angle.
+    FrameAll,
+    View(OrbitCamera),
+}
+
+/// An eased move between two views, dropped once done or as soon as the user grabs the camera.
+#[derive(Component, Debug)]
+pub struct CameraTransition {
+    from: OrbitCamera,
+    to: OrbitCamera,
+    started: f32,
+}
+
+/// How the primary camera is driven. Every mode reads and writes the same [`OrbitCamera`], so
+/// switching keeps the view where it was.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Turns around a target point.
+    #[default]
+    Orbit,
+    /// Moves with WASD, Q and E, looks around with the mouse.
+    Fly,
+    /// Looks straight down through an orthographic projection.
+    TopDown,
+    /// Orbits an object picked with a click, and moves along with it.
+    Follow,
+}
+
+impl CameraMode {
+    pub const ALL: [CameraMode; 4] = [
+        CameraMode::Orbit,
+        CameraMode::Fly,
+        CameraMode::TopDown,
+        CameraMode::Follow,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CameraMode::Orbit => "Orbit",
+            CameraMode::Fly => "Fly",
+            CameraMode::TopDown => "2D",
+            CameraMode::Follow => "Follow",
+        }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self {
+            CameraMode::Orbit => "drag to orbit, middle drag to pan, scroll to zoom",
+            CameraMode::Fly => "WASD to move, Q/E down and up, Shift faster, drag to look",
+            CameraMode::TopDown => "drag to pan, scroll to zoom at the pointer",
+            CameraMode::Follow => "click an object to follow it, drag to orbit, scroll to zoom",
+        }
+    }
+}
+
+/// Sent to switch the primary camera to another mode.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct SetCameraMode(pub CameraMode);
+
+/// The object a camera in [`CameraMode::Follow`] keeps as its target.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CameraFollow(pub Entity);
+
+/// Zoom range and movement speeds in proportion to the scene, so that a 500×500 grid can be
+/// seen whole and a 3×3 one up close.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct CameraLimits {
+    pub min_radius: f32,
+    pub max_radius: f32,
+    /// World units per second of the fly camera.
+    pub fly_speed: f32,
+}
+
+impl Default for CameraLimits {
+    fn default() -> Self {
+        Self::fit(Vec3::splat(4.0))
+    }
+}
+
+impl CameraLimits {
+    /// Limits for a scene whose bounding box has these half extents.
+    pub fn fit(half_extents: Vec3) -> Self {
+        let size = half_extents.length().max(0.5);
+        Self {
+            min_radius: size * 0.05,
+            max_radius: size * 10.0,
+            fly_speed: size,
+        }
+    }
+}
+
+/// Mouse and keyboard controls of every mode. Input goes to the camera whose viewport is under
+/// the pointer, and a drag stays with the camera it started on. Nothing reaches the cameras while
+/// egui uses the pointer, e.g. to scroll a panel or drag a slider, nor the keyboard while it has
+/// focus.
+#[allow(clippy::too_many_arguments)]
+pub fn camera_controls(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    limits: Res<CameraLimits>,
+    mut motion: MessageReader<MouseMotion>,
+    mut scroll: MessageReader<MouseWheel>,
+    mut query: Query<(
+        Entity,
+        &Camera,
+        &GlobalTransform,
+        &CameraMode,
+        &mut OrbitCamera,
+    )>,
+    meshes: Query<(Entity, &GlobalTransform, &Aabb), With<TaggedEntity>>,
+    mut grabbed: Local<Option<(Entity, Vec2)>>,
+) -> Result {
+    // Always drained, so that motion over egui is not replayed once the pointer leaves it.
+    let delta: Vec2 = motion.read().map(|ev| ev.delta).sum();
+    let wheel: f32 = scroll.read().map(|ev| ev.y).sum();
+
+    let ctx = contexts.ctx_mut()?;
+    let egui_pointer = ctx.wants_pointer_input() || ctx.is_pointer_over_area();
+    let egui_keyboard = ctx.wants_keyboard_input();
+    let cursor = window.cursor_position();
+    let hovered = cursor
+        .filter(|_| !egui_pointer)
+        .and_then(|cursor| viewport_under(&query, cursor));
+    let buttons = [MouseButton::Left, MouseButton::Middle];
+    let mut clicked = None;
+    if mouse.any_just_pressed(buttons) {
+        *grabbed = hovered.zip(cursor);
+    } else if !mouse.any_pressed(buttons) {
+        // A release close to where the press happened is a click rather than a drag.
+        if let Some((entity, pressed)) = grabbed.take()
+            && mouse.just_released(MouseButton::Left)
+            && let Some(cursor) = cursor
+            && cursor.distance(pressed) < 4.
+        {
+            clicked = Some((entity, cursor));
+        }
+    }
+
+    for (entity, camera, camera_transform, mode, mut orbit) in &mut query {
+        let dragged = grabbed.is_some_and(|(grab, _)| grab == entity);
+        let zoomed = hovered == Some(entity) && wheel != 0.;
+        let left = dragged && mouse.pressed(MouseButton::Left);
+        let middle = dragged && mouse.pressed(MouseButton::Middle);
+        let flying = *mode == CameraMode::Fly && !egui_keyboard && fly_keys_pressed(&keys);
+        if dragged || zoomed || flying {
+            commands.entity(entity).remove::<CameraTransition>();
+        }
+        match mode {
+            CameraMode::Orbit | CameraMode::Follow => {
+                if left {
+                    let (delta_yaw, delta_pitch) = orbit_angles(delta);
+                    orbit.yaw += delta_yaw;
+                    orbit.pitch = (orbit.pitch + delta_pitch).clamp(-MAX_PITCH, MAX_PITCH);
+                }
+                if middle && *mode == CameraMode::Orbit {
+                    let rotation = camera_transform.rotation();
+                    orbit.target += compute_pan_vector(delta, rotation, orbit.radius);
+                }
+                if zoomed {
+                    orbit.radius = adjust_zoom(orbit.radius, wheel, &limits);
+                }
+            }
+            CameraMode::Fly => {
+                if left {
+                    // Turn around the eye rather than around the target.
+                    let eye = orbit_eye(&orbit);
+                    let (delta_yaw, delta_pitch) = orbit_angles(delta);
+                    orbit.yaw += delta_yaw;
+                    orbit.pitch = (orbit.pitch + delta_pitch).clamp(-MAX_PITCH, MAX_PITCH);
+                    orbit.target = eye - orbit_rotation(&orbit) * Vec3::Z * orbit.radius;
+                }
+                if flying {
+                    let rotation = orbit_rotation(&orbit);
+                    let boost = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+                        4.0
+                    } else {
+                        1.0
+                    };
+                    let axis = |neg: KeyCode, pos: KeyCode| {
+                        keys.pressed(pos) as i8 as f32 - keys.pressed(neg) as i8 as f32
+                    };
+                    let step = rotation * Vec3::NEG_Z * axis(KeyCode::KeyS, KeyCode::KeyW)
+                        + rotation * Vec3::X * axis(KeyCode::KeyA, KeyCode::KeyD)
+                        + Vec3::Y * axis(KeyCode::KeyQ, KeyCode::KeyE);
+                    orbit.target +=
+                        step.normalize_or_zero() * limits.fly_speed * boost * time.delta_secs();
+                }
+            }
+            CameraMode::TopDown => {
+                if left || middle {
+                    let scale = top_down_height(orbit.radius) / viewport_height(camera);
+                    orbit.target -= Vec3::new(delta.x, 0.0, delta.y) * scale;
+                }
+                if zoomed {
+                    let radius = adjust_zoom(orbit.radius, wheel, &limits);
+                    // Keep the point under the pointer in place.
+                    if let Some(cursor) = cursor
+                        && let Ok(ray) = camera.viewport_to_world(camera_transform, cursor)
+                        && let Some(distance) =
+                            ray.intersect_plane(orbit.target, InfinitePlane3d::new(Vec3::Y))
+                    {
+                        let under = ray.get_point(distance);
+                        orbit.target = under + (orbit.target - under) * (radius / orbit.radius);
+                    }
+                    orbit.radius = radius;
+                }
+            }
+        }
+        if *mode == CameraMode::Follow
+            && let Some((_, cursor)) = clicked.filter(|(clicked, _)| *clicked == entity)
+            && let Ok(ray) = camera.viewport_to_world(camera_transform, cursor)
+        {
+            match pick(&meshes, ray) {
+                Some(picked) => commands.entity(entity).insert(CameraFollow(picked)),
+                None => commands.entity(entity).remove::<CameraFollow>(),
+            };
+        }
+    }
+    Ok(())
+}
+
+fn fly_keys_pressed(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([
+        KeyCode::KeyW,
+        KeyCode::KeyA,
+        KeyCode::KeyS,
+        KeyCode::KeyD,
+        KeyCode::KeyQ,
+        KeyCode::KeyE,
+    ])
+}
+
+/// The active camera drawn on top at `cursor`, in logical window coordinates.
+fn viewport_under(
+    query: &Query<(
+        Entity,
+        &Camera,
+        &GlobalTransform,
+        &CameraMode,
+        &mut OrbitCamera,
+    )>,
+    cursor: Vec2,
+) -> Option<Entity> {
+    query
+        .iter()
+        .filter(|(_, camera, ..)| {
+            camera.is_active
+                && camera
+                    .logical_viewport_rect()
+                    .is_some_and(|rect| rect.contains(cursor))
+        })
+        .max_by_key(|(_, camera, ..)| camera.order)
+        .map(|(entity, ..)| entity)
+}
+
+/// The nearest renderer mesh hit by `ray`, by its bounding box.
+fn pick(
+    meshes: &Query<(Entity, &GlobalTransform, &Aabb), With<TaggedEntity>>,
+    ray: Ray3d,
+) -> Option<Entity> {
+    meshes
+        .iter()
+        .filter_map(|(entity, transform, aabb)| {
+            let (min, max) = world_aabb(transform, aabb);
+            let direction = Vec3::from(ray.direction);
+            let a = (min - ray.origin) / direction;
+            let b = (max - ray.origin) / direction;
+            let near = a.min(b).max_element();
+            let far = a.max(b).min_element();
+            (near <= far && far >= 0.0).then_some((entity, near.max(0.0)))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity)
+}
+
+/// Applies a mode switch asked for by [`SetCameraMode`]. Leaving the top-down view keeps looking
+/// down at the same spot, the other modes already share their view.
+pub fn camera_mode_system(
+    mut commands: Commands,
+    mut events: MessageReader<SetCameraMode>,
+    mut cameras: Query<(Entity, &ViewportId, &mut CameraMode, &mut OrbitCamera)>,
+) {
+    let Some(SetCameraMode(to)) = events.read().last().copied() else {
+        return;
+    };
+    for (entity, id, mut mode, mut orbit) in &mut cameras {
+        if *id != ViewportId::Primary || *mode == to {
+            continue;
+        }
+        if *mode == CameraMode::TopDown {
+            orbit.yaw = 0.0;
+            orbit.pitch = -MAX_PITCH;
+        }
+        if *mode == CameraMode::Follow {
+            commands.entity(entity).remove::<CameraFollow>();
+        }
+        commands.entity(entity).remove::<CameraTransition>();
+        *mode = to;
+    }
+}
+
+/// Keeps following cameras aimed at their object, easing so that it does not jitter with the
+/// tweens of the renderer.
+pub fn camera_follow_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<AnimationConfig>,
+    mut cameras: Query<(Entity, &CameraFollow, &mut OrbitCamera)>,
+    targets: Query<&GlobalTransform>,
+) {
+    for (entity, follow, mut orbit) in &mut cameras {
+        let Ok(target) = targets.get(follow.0) else {
+            commands.entity(entity).remove::<CameraFollow>();
+            continue;
+        };
+        let goal = target.translation();
+        orbit.target = if config.reduce_motion {
+            goal
+        } else {
+            orbit
+                .target
+                .lerp(goal, 1.0 - (-8.0 * time.delta_secs()).exp())
+        };
+    }
+}
+
+/// Turns each view into a transform, and swaps the projection for the top-down view.
+pub fn apply_camera_system(
+    limits: Res<CameraLimits>,
+    mut cameras: Query<(&CameraMode, &OrbitCamera, &mut Transform, &mut Projection)>,
+) {
+    for (mode, orbit, mut transform, mut projection) in &mut cameras {
+        if *mode == CameraMode::TopDown {
+            // Far above the scene, orthographic projections do not care about distance.
+            let height = limits.max_radius;
+            *transform = Transform::from_translation(orbit.target + Vec3::Y * height)
+                .looking_at(orbit.target, Vec3::NEG_Z);
+            let viewport_height = top_down_height(orbit.radius);
+            let up_to_date = matches!(
+                &*projection,
+                Projection::Orthographic(OrthographicProjection {
+                    scaling_mode: ScalingMode::FixedVertical { viewport_height: current },
+                    far,
+                    ..
+                }) if *current == viewport_height && *far == height * 2.0
+            );
+            if !up_to_date {
+                *projection = Projection::Orthographic(OrthographicProjection {
+                    scaling_mode: ScalingMode::FixedVertical { viewport_height },
+                    far: height * 2.0,
+                    ..OrthographicProjection::default_3d()
+                });
+            }
+        } else {
+            transform.translation = orbit_eye(orbit);
+            transform.look_at(orbit.target, Vec3::Y);
+            if !matches!(&*projection, Projection::Perspective(_)) {
+                *projection = Projection::Perspective(default());
+            }
+        }
+    }
+}
+
+/// Narrows the zoom range and speeds to the scene whenever the renderer spawns new meshes.
+pub fn camera_limits_system(
+    mut limits: ResMut<CameraLimits>,
+    meshes: Query<(&GlobalTransform, &Aabb), With<TaggedEntity>>,
+    added: Query<(), (With<TaggedEntity>, Added<Aabb>)>,
+) {
+    if added.is_empty() {
+        return;
+    }
+    let fitted = scene_bounds(&meshes)
+        .map_or_else(CameraLimits::default, |(_, half)| CameraLimits::fit(half));
+    if *limits != fitted {
+        *limits = fitted;
+    }
+}
+
+/// Points the orbit camera at the requested bounds, far enough back to see all of them.
+pub fn frame_bounds_system(
+    mut events: MessageReader<FrameBounds>,
+    mut limits: ResMut<CameraLimits>,
+    mut query: Query<&mut OrbitCamera>,
+) {
+    for bounds in events.read() {
+        *limits = CameraLimits::fit(bounds.half_extents);
+        for mut orbit in &mut query {
+            *orbit = OrbitCamera::framing(bounds.center, bounds.half_extents, 0.6, -0.6);
+        }
+    }
+}
+
+/// Starts an eased move of the primary camera, or jumps straight there with reduced motion.
+pub fn move_camera_system(
+    mut commands: Commands,
+    mut events: MessageReader<MoveCamera>,
+    time: Res<Time>,
+    config: Res<AnimationConfig>,
+    meshes: Query<(&GlobalTransform, &Aabb), With<TaggedEntity>>,
+    mut cameras: Query<(Entity, &ViewportId, &mut OrbitCamera)>,
+) {
+    let Some(event) = events.read().last().copied() else {
+        return;
+    };
+    let Some((entity, _, mut orbit)) = cameras
+        .iter_mut()
+        .find(|(_, id, _)| **id == ViewportId::Primary)
+    else {
+        return;
+    };
+    let to = match event {
+        MoveCamera::Preset(preset) => preset.view(&orbit),
+        MoveCamera::FrameAll => match scene_bounds(&meshes) {
+            Some((center, half_extents)) => {
+                OrbitCamera::framing(center, half_extents, orbit.yaw, orbit.pitch)
+            }
+            None => return,
+        },
+        MoveCamera::View(view) => view,
+    };
+    if config.reduce_motion {
+        *orbit = to;
+        commands.entity(entity).remove::<CameraTransition>();
+    } else {
+        commands.entity(entity).insert(CameraTransition {
+            from: *orbit,
+            to,
+            started: time.elapsed_secs(),
+        });
+    }
+}
+
+/// Centre and half extents of the box around every mesh of the current renderer.
+fn scene_bounds(
+    meshes: &Query<(&GlobalTransform, &Aabb), With<TaggedEntity>>,
+) -> Option<(Vec3, Vec3)> {
+    let (min, max) = meshes
+        .iter()
+        .map(|(transform, aabb)| world_aabb(transform, aabb))
+        .reduce(|(lo, hi), (min, max)| (lo.min(min), hi.max(max)))?;
+    Some(((min + max) / 2.0, (max - min) / 2.0))
+}
+
+/// Eases cameras along their transition, `apply_camera_system` turns the view into a
+/// transform.
+pub fn camera_transition_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &CameraTransition, &mut OrbitCamera)>,
+) {
+    for (entity, transition, mut orbit) in &mut query {
+        let t = (time.elapsed_secs() - transition.started) / TRANSITION_SECONDS;
+        if t >= 1.0 {
+            *orbit = transition.to;
+            commands.entity(entity).remove::<CameraTransition>();
+        } else {
+            let eased = InterpolationKind::EaseInOutCubic.apply(t);
+            *orbit = transition.from.lerp(&transition.to, eased);
+        }
+    }
+}
+
+fn orbit_angles(delta: Vec2) -> (f32, f32) {
+    let sensitivity = 0.005;
+    (-delta.x * sensitivity, -delta.y * sensitivity)
+}
+
+/// Zooms by a tenth of the distance per wheel step, so that zooming feels the same at any scale.
+fn adjust_zoom(radius: f32, wheel: f32, limits: &CameraLimits) -> f32 {
+    (radius * (1.0 - wheel * 0.1).max(0.1)).clamp(limits.min_radius, limits.max_radius)
+}
+
+fn compute_pan_vector(delta: Vec2, rotation: Quat, radius: f32) -> Vec3 {
+    let sensitivity = 0.005 * radius;
+    let right = rotation * Vec3::X;
+    let up = rotation * Vec3::Y;
+    (-right * delta.x * sensitivity) + (up * delta.y * sensitivity)
+}
+
+fn orbit_rotation(orbit: &OrbitCamera) -> Quat {
+    Quat::from_rotation_y(orbit.yaw) * Quat::from_rotation_x(orbit.pitch)
+}
+
+fn orbit_eye(orbit: &OrbitCamera) -> Vec3 {
+    orbit.target + orbit_rotation(orbit) * Vec3::new(0.0, 0.0, orbit.radius)
+}
+
+/// World height seen by the top-down view, matching what the perspective view sees at the
+/// target so that switching between them keeps the scale.
+fn top_down_height(radius: f32) -> f32 {
+    2.0 * radius * (PerspectiveProjection::default().fov / 2.0).tan()
+}
+
+fn viewport_height(camera: &Camera) -> f32 {
+    camera
+        .logical_viewport_size()
+        .map_or(1.0, |size| size.y.max(1.0))
+}
+
+/// World space box around a mesh.
+fn world_aabb(transform: &GlobalTransform, aabb: &Aabb) -> (Vec3, Vec3) {
+    let center = transform.transform_point(aabb.center.into());
+    let axes = transform.affine().matrix3;
+    let half = Vec3::from(
+        axes.x_axis.abs() * aabb.half_extents.x
+            + axes.y_axis.abs() * aabb.half_extents.y
+            + axes.z_axis.abs() * aabb.half_extents.z,
+    );
+    (center - half, center + half)
+}
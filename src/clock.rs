@@ -0,0 +1,103 @@
+//! Fixed-rate clock the story advances on, independent of the frame rate.
+
+use bevy::prelude::*;
+
+use crate::{
+    breakpoints::Breakpoints,
+    config::{AnimationConfig, LagPolicy, MAX_CATCH_UP},
+    session::{Session, play_frames},
+    story::{LoopMode, Playhead, Story},
+};
+
+/// Accumulates real time and hands out whole ticks at the pace set by [`AnimationConfig`].
+#[derive(Resource, Debug, Default)]
+pub struct StoryClock {
+    accumulated: f32,
+    due: u32,
+    /// Ticks dropped by the lag policy since startup.
+    pub dropped: u64,
+}
+
+impl StoryClock {
+    /// Ticks to run this frame.
+    pub fn due(&self) -> u32 {
+        self.due
+    }
+
+    /// How far the clock is into the next tick, in `[0, 1)`.
+    pub fn fraction(&self, config: &AnimationConfig) -> f32 {
+        (self.accumulated / config.tick_seconds()).clamp(0.0, 1.0)
+    }
+
+    fn advance(&mut self, delta: f32, config: &AnimationConfig) {
+        let period = config.tick_seconds();
+        self.accumulated += delta;
+        let elapsed = (self.accumulated / period).floor();
+        self.accumulated -= elapsed * period;
+        let elapsed = elapsed as u64;
+        let limit = match config.lag_policy {
+            LagPolicy::CatchUp => MAX_CATCH_UP,
+            LagPolicy::Skip => 1,
+        };
+        self.due = elapsed.min(limit as u64) as u32;
+        self.dropped += elapsed - self.due as u64;
+    }
+}
+
+/// Runs before anything reads the clock, so every system in a frame sees the same ticks.
+pub fn story_clock_system(
+    time: Res<Time>,
+    config: Res<AnimationConfig>,
+    mut clock: ResMut<StoryClock>,
+) {
+    clock.advance(time.delta_secs(), &config);
+}
+
+/// Moves the playhead one frame per story clock tick through the in/out range, wrapping or
+/// turning around as the loop mode says. Pauses at the end of a single pass, or on the first
+/// frame where a breakpoint becomes true.
+pub fn story_tick_system(
+    clock: Res<StoryClock>,
+    story: Res<Story>,
+    session: Option<Res<Session>>,
+    mut playhead: ResMut<Playhead>,
+    mut breakpoints: ResMut<Breakpoints>,
+) {
+    if clock.due() == 0 || playhead.paused {
+        return;
+    }
+    let frames = play_frames(&story, session.as_deref());
+    let mode = session.as_ref().map(|s| s.loop_mode).unwrap_or_default();
+    let mut index = playhead.index;
+    let mut reverse = playhead.reverse;
+    let mut stop = false;
+    // Every frame passed over is checked, even when several ticks fell due at once.
+    for _ in 0..clock.due() {
+        let Some(next) = mode.next(index, &mut reverse, &frames) else {
+            stop = true;
+            break;
+        };
+        stop = breakpoints.is_armed() && breakpoints.check(&story, next, index).is_some();
+        index = next;
+        if stop {
+            break;
+        }
+    }
+    let end = if reverse {
+        *frames.start()
+    } else {
+        *frames.end()
+    };
+    if mode == LoopMode::Once && index == end {
+        stop = true;
+    }
+    if index != playhead.index {
+        playhead.index = index;
+    }
+    if reverse != playhead.reverse {
+        playhead.reverse = reverse;
+    }
+    if stop {
+        playhead.paused = true;
+    }
+}
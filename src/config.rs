@@ -1,6 +1,16 @@
+use std::{f32::consts::TAU, ops::RangeInclusive};
+
 use bevy::prelude::*;
 
-#[derive(Resource)]
+use crate::history::{DEFAULT_CACHE_BUDGET, DEFAULT_KEYFRAME_INTERVAL};
+
+/// Bounds of [`AnimationConfig::animation_speed`].
+pub const SPEED_RANGE: RangeInclusive<f32> = 0.1..=100.0;
+
+/// Most ticks [`LagPolicy::CatchUp`] runs in one frame, so a long stall cannot freeze the app.
+pub const MAX_CATCH_UP: u32 = 256;
+
+#[derive(Resource, Clone, Debug, PartialEq)]
 pub struct AnimationConfig {
     /// Seconds per tick for your simulation (e.g. 0.5 = 2 ticks per second)
     pub tick_rate: f32,
@@ -8,12 +18,135 @@ pub struct AnimationConfig {
     pub animation_speed: f32,
     /// The interpolation type
     pub interpolation: InterpolationKind,
+    /// What to do with ticks that fell due during a slow frame
+    pub lag_policy: LagPolicy,
+    /// Snap to each state instead of easing towards it
+    pub reduce_motion: bool,
+}
+
+impl AnimationConfig {
+    /// Real seconds between two ticks once the speed multiplier is applied.
+    pub fn tick_seconds(&self) -> f32 {
+        let speed = self
+            .animation_speed
+            .clamp(*SPEED_RANGE.start(), *SPEED_RANGE.end());
+        (self.tick_rate / speed).max(f32::EPSILON)
+    }
+}
+
+/// What the story clock does when more than one tick fell due since the last frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LagPolicy {
+    /// Run every missed tick, up to [`MAX_CATCH_UP`] per frame.
+    #[default]
+    CatchUp,
+    /// Run a single tick and drop the others.
+    Skip,
 }
 
-#[derive(Clone, Copy, Debug)]
+impl LagPolicy {
+    pub const ALL: [LagPolicy; 2] = [LagPolicy::CatchUp, LagPolicy::Skip];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LagPolicy::CatchUp => "Catch up",
+            LagPolicy::Skip => "Skip",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum InterpolationKind {
     Linear,
-    Exponential { exponent: f32 },
+    Exponential {
+        exponent: f32,
+    },
+    EaseInOutCubic,
+    /// Overshoots and settles, less damping wobbles longer.
+    Spring {
+        damping: f32,
+    },
+    /// Holds, then jumps in `steps` equal increments.
+    Step {
+        steps: u32,
+    },
+    /// CSS-style curve through (0, 0), `p1`, `p2` and (1, 1).
+    CubicBezier {
+        p1: Vec2,
+        p2: Vec2,
+    },
+}
+
+impl InterpolationKind {
+    /// One of each kind with sensible parameters, for pickers.
+    pub const PRESETS: [InterpolationKind; 6] = [
+        InterpolationKind::Linear,
+        InterpolationKind::Exponential { exponent: 2.0 },
+        InterpolationKind::EaseInOutCubic,
+        InterpolationKind::Spring { damping: 0.5 },
+        InterpolationKind::Step { steps: 1 },
+        InterpolationKind::CubicBezier {
+            p1: Vec2::new(0.25, 0.1),
+            p2: Vec2::new(0.25, 1.0),
+        },
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InterpolationKind::Linear => "Linear",
+            InterpolationKind::Exponential { .. } => "Exponential",
+            InterpolationKind::EaseInOutCubic => "Ease in/out cubic",
+            InterpolationKind::Spring { .. } => "Spring",
+            InterpolationKind::Step { .. } => "Step",
+            InterpolationKind::CubicBezier { .. } => "Cubic bezier",
+        }
+    }
+
+    /// Maps linear progress `t` in `[0, 1]` onto the eased progress. `Exponential` eases in and
+    /// out with the given power. `Spring` and `CubicBezier` may leave `[0, 1]` on the way, but
+    /// every kind starts at 0 and ends at 1.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match *self {
+            InterpolationKind::Linear => t,
+            InterpolationKind::Exponential { exponent } if t < 0.5 => {
+                0.5 * (2.0 * t).powf(exponent)
+            }
+            InterpolationKind::Exponential { exponent } => {
+                1.0 - 0.5 * (2.0 * (1.0 - t)).powf(exponent)
+            }
+            InterpolationKind::EaseInOutCubic if t < 0.5 => 4.0 * t * t * t,
+            InterpolationKind::EaseInOutCubic => 1.0 - (2.0 - 2.0 * t).powi(3) / 2.0,
+            // The (1 - t) envelope pins the end at exactly 1 whatever the damping.
+            InterpolationKind::Spring { damping } => {
+                1.0 - (1.0 - t) * (-damping * 8.0 * t).exp() * (TAU * 2.0 * t).cos()
+            }
+            InterpolationKind::Step { steps } => {
+                let steps = steps.max(1) as f32;
+                (t * steps).floor() / steps
+            }
+            InterpolationKind::CubicBezier { p1, p2 } => cubic_bezier(p1, p2, t),
+        }
+    }
+}
+
+/// Solves the curve's x for `x` by bisection, which is enough while both control points keep x
+/// in `[0, 1]`, then reads y at that point.
+fn cubic_bezier(p1: Vec2, p2: Vec2, x: f32) -> f32 {
+    let at = |a: f32, b: f32, s: f32| {
+        let r = 1.0 - s;
+        3.0 * a * s * r * r + 3.0 * b * s * s * r + s * s * s
+    };
+    let (mut lo, mut hi) = (0.0, 1.0);
+    for _ in 0..24 {
+        let mid = (lo + hi) / 2.0;
+        if at(p1.x, p2.x, mid) < x {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    at(p1.y, p2.y, (lo + hi) / 2.0)
 }
 
 impl Default for AnimationConfig {
@@ -22,6 +155,29 @@ impl Default for AnimationConfig {
             tick_rate: 0.5,
             animation_speed: 1.0,
             interpolation: InterpolationKind::Exponential { exponent: 2.0 },
+            lag_policy: LagPolicy::default(),
+            reduce_motion: false,
+        }
+    }
+}
+
+/// Keyframe cache of the story history, see [`crate::history::StateHistory`].
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct HistoryConfig {
+    /// Frames between two cached keyframes
+    pub keyframe_interval: usize,
+    /// Bytes of keyframes kept before the least recently used are evicted
+    pub cache_budget: usize,
+    /// Show seek latency and cache use over the scene
+    pub overlay: bool,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            keyframe_interval: DEFAULT_KEYFRAME_INTERVAL,
+            cache_budget: DEFAULT_CACHE_BUDGET,
+            overlay: false,
         }
     }
 }
@@ -0,0 +1,148 @@
+//! Failures shown to the user on the error screen, instead of being logged and forgotten.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+
+use crate::{loaders::LoadError, story::Story, visualization::VisualizerState};
+
+/// Where in the pipeline a failure happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorStage {
+    Detect,
+    Parse,
+    Spawn,
+    Render,
+}
+
+impl ErrorStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ErrorStage::Detect => "Detecting the file type",
+            ErrorStage::Parse => "Parsing",
+            ErrorStage::Spawn => "Spawning the renderer",
+            ErrorStage::Render => "Rendering",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorContext {
+    Line(usize),
+    /// A program's exit code and the end of its stderr.
+    ExitCode(i32, String),
+    Detail(String),
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorContext::Line(line) => write!(f, "line {line}"),
+            ErrorContext::ExitCode(code, stderr) if stderr.is_empty() => {
+                write!(f, "exit code {code}")
+            }
+            ErrorContext::ExitCode(code, stderr) => write!(f, "exit code {code}\n{stderr}"),
+            ErrorContext::Detail(detail) => f.write_str(detail),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VisualizationError {
+    pub source: Option<PathBuf>,
+    pub stage: ErrorStage,
+    pub message: String,
+    pub context: Option<ErrorContext>,
+}
+
+impl VisualizationError {
+    pub fn new(stage: ErrorStage, message: impl Into<String>) -> Self {
+        Self {
+            source: None,
+            stage,
+            message: message.into(),
+            context: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: &Path) -> Self {
+        self.source = Some(source.to_path_buf());
+        self
+    }
+
+    pub fn with_context(mut self, context: ErrorContext) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Keeps the path, line or exit code a loader attached to its error as structured fields.
+    pub fn from_load(stage: ErrorStage, err: &LoadError) -> Self {
+        match err {
+            LoadError::Io(path, io) => Self::new(stage, io.to_string()).with_source(path),
+            LoadError::Empty(path) => Self::new(stage, "no frames found").with_source(path),
+            LoadError::Line(path, line, why) => Self::new(stage, why.clone())
+                .with_source(path)
+                .with_context(ErrorContext::Line(*line)),
+            LoadError::Exited(path, Some(code), stderr) => Self::new(stage, "the program failed")
+                .with_source(path)
+                .with_context(ErrorContext::ExitCode(*code, stderr.clone())),
+            LoadError::Exited(path, None, stderr) => {
+                let error =
+                    Self::new(stage, "the program was stopped by a signal").with_source(path);
+                if stderr.is_empty() {
+                    error
+                } else {
+                    error.with_context(ErrorContext::Detail(stderr.clone()))
+                }
+            }
+            err => Self::new(stage, err.to_string()),
+        }
+    }
+
+    /// Plain text summary, for the clipboard and the log.
+    pub fn details(&self) -> String {
+        let mut details = format!("Stage: {}\n", self.stage.label());
+        if let Some(source) = &self.source {
+            details += &format!("File: {}\n", source.display());
+        }
+        details += &format!("Error: {}\n", self.message);
+        if let Some(context) = &self.context {
+            details += &format!("Context: {context}\n");
+        }
+        details
+    }
+}
+
+/// A renderer was handed a frame it cannot draw.
+pub fn unexpected_frame(story: &Story, index: usize, expected: &str) -> VisualizationError {
+    let tick = story.tick(index).unwrap_or_default();
+    VisualizationError::new(ErrorStage::Render, format!("the frame holds no {expected}"))
+        .with_source(&story.source)
+        .with_context(ErrorContext::Detail(format!("frame {index}, tick {tick}")))
+}
+
+/// Sent by any system that hit a failure it cannot recover from.
+#[derive(Message)]
+pub struct ReportError(pub VisualizationError);
+
+pub fn report_error(commands: &mut Commands, error: VisualizationError) {
+    commands.write_message(ReportError(error));
+}
+
+/// Moves to the error screen with the last reported error.
+pub fn report_error_system(
+    mut events: MessageReader<ReportError>,
+    mut next_state: ResMut<NextState<VisualizerState>>,
+) {
+    if let Some(ReportError(error)) = events.read().last() {
+        error!("{}", error.details().trim_end().replace('\n', ", "));
+        next_state.set(VisualizerState::Error(error.clone()));
+    }
+}
+
+pub fn in_error_state(state: Res<State<VisualizerState>>) -> bool {
+    matches!(state.get(), VisualizerState::Error(_))
+}
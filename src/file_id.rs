@@ -1,8 +1,14 @@
-use std::path::Path;
+use std::{fs::File, io::Read, path::Path};
 
 use bevy::ecs::resource::Resource;
 
-use crate::FileTypeSelection;
+use crate::{
+    FileTypeSelection,
+    loaders::{
+        numpy::{NPY_MAGIC, NPZ_MAGIC},
+        volume::RAW_VOLUME_MAGIC,
+    },
+};
 
 #[derive(Default, Debug, Resource, Clone)]
 pub enum FileTypeSuggestion {
@@ -10,12 +16,33 @@ pub enum FileTypeSuggestion {
     Executable,
     Text,
     Readable,
+    Table,
+    Volume,
+    NumPy,
+    Graph,
+    Events,
     #[default]
     Unknown,
 }
 
+/// Recognises formats that carry a magic number, whatever their extension. Raw volumes are only
+/// recognised this way, `.raw` alone says nothing about the content.
+fn sniff(path_buf: &Path) -> Option<FileTypeSuggestion> {
+    let mut magic = [0u8; 6];
+    File::open(path_buf).ok()?.read_exact(&mut magic).ok()?;
+    if magic.starts_with(RAW_VOLUME_MAGIC) {
+        return Some(FileTypeSuggestion::Volume);
+    }
+    let is_npz =
+        magic.starts_with(NPZ_MAGIC) && path_buf.extension().is_some_and(|ext| ext == "npz");
+    (magic == *NPY_MAGIC || is_npz).then_some(FileTypeSuggestion::NumPy)
+}
+
 pub fn suggestion(path_buf: &Path) -> FileTypeSuggestion {
     if path_buf.is_file() {
+        if let Some(suggestion) = sniff(path_buf) {
+            return suggestion;
+        }
         let ext = match path_buf.extension() {
             Some(ext) => ext.to_str().unwrap_or("Unknown extension"),
             None => return FileTypeSuggestion::Unknown,
@@ -24,6 +51,9 @@ pub fn suggestion(path_buf: &Path) -> FileTypeSuggestion {
             "exe" | "bat" | "cmd" | "com" | "sh" | "ps1" | "vbs" | "wsf" | "js" | "jse" | "vbe"
             | "wsh" | "hta" | "cpl" | "scr" | "pif" | "lnk" => FileTypeSuggestion::Executable,
             "txt" => FileTypeSuggestion::Text,
+            "csv" | "tsv" => FileTypeSuggestion::Table,
+            "dot" | "gv" => FileTypeSuggestion::Graph,
+            "story" | "events" => FileTypeSuggestion::Events,
             ".md" | "json" | "toml" | "yaml" | "json5" | "jsonc" | "yml" | "yamlc" | "ymlc" => {
                 FileTypeSuggestion::Readable
             }
@@ -0,0 +1,492 @@
+//! Story states kept as one base state plus a delta per tick. Any state is rebuilt by replaying
+//! deltas from the closest keyframe, and keyframes are cached under a memory budget.
+
+use std::{
+    collections::HashMap,
+    mem::size_of,
+    ops::RangeInclusive,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use bevy::{prelude::*, tasks::AsyncComputeTaskPool};
+
+use crate::{
+    config::HistoryConfig,
+    story::{ArrayState, FrameState, GraphEdge, GraphNode, Story},
+};
+
+/// Changed and appended entries of a vector, with its new length.
+#[derive(Debug, Clone)]
+struct Patch<T> {
+    len: usize,
+    changes: Vec<(usize, T)>,
+}
+
+impl<T: Clone> Patch<T> {
+    fn between(prev: &[T], next: &[T], same: impl Fn(&T, &T) -> bool) -> Self {
+        Self {
+            len: next.len(),
+            changes: next
+                .iter()
+                .enumerate()
+                .filter(|(i, v)| !prev.get(*i).is_some_and(|p| same(p, *v)))
+                .map(|(i, v)| (i, v.clone()))
+                .collect(),
+        }
+    }
+
+    /// Appended entries come last and in order, so pushing them restores the new length.
+    fn apply(&self, values: &mut Vec<T>) {
+        values.truncate(self.len);
+        for (i, v) in &self.changes {
+            match values.get_mut(*i) {
+                Some(slot) => *slot = v.clone(),
+                None => values.push(v.clone()),
+            }
+        }
+    }
+
+    fn bytes(&self, entry: impl Fn(&T) -> usize) -> usize {
+        self.changes
+            .iter()
+            .map(|(_, v)| size_of::<usize>() + entry(v))
+            .sum()
+    }
+}
+
+/// Bit equality, so that `NaN` cells count as unchanged.
+fn same_f32(a: &f32, b: &f32) -> bool {
+    a.to_bits() == b.to_bits()
+}
+
+/// What turns one state into the next.
+#[derive(Debug, Clone)]
+enum Delta {
+    Text(Patch<Vec<char>>),
+    Values(Patch<f32>),
+    Graph {
+        nodes: Patch<GraphNode>,
+        edges: Patch<GraphEdge>,
+    },
+    /// Operation lists are per tick anyway, so they are stored whole beside the value patch.
+    Array {
+        values: Patch<f32>,
+        rest: Box<ArrayState>,
+    },
+    /// The state changed kind or shape.
+    Full(Box<FrameState>),
+}
+
+impl Delta {
+    fn between(prev: &FrameState, next: &FrameState) -> Self {
+        match (prev, next) {
+            (FrameState::Text(a), FrameState::Text(b)) => {
+                Delta::Text(Patch::between(&a.rows, &b.rows, PartialEq::eq))
+            }
+            (FrameState::Scalar(a), FrameState::Scalar(b))
+                if (a.width, a.height) == (b.width, b.height) =>
+            {
+                Delta::Values(Patch::between(&a.values, &b.values, same_f32))
+            }
+            (FrameState::Volume(a), FrameState::Volume(b))
+                if (a.width, a.height, a.depth) == (b.width, b.height, b.depth) =>
+            {
+                Delta::Values(Patch::between(&a.values, &b.values, same_f32))
+            }
+            (FrameState::Graph(a), FrameState::Graph(b)) => Delta::Graph {
+                nodes: Patch::between(&a.nodes, &b.nodes, PartialEq::eq),
+                edges: Patch::between(&a.edges, &b.edges, PartialEq::eq),
+            },
+            (FrameState::Array(a), FrameState::Array(b)) => Delta::Array {
+                values: Patch::between(&a.values, &b.values, same_f32),
+                rest: Box::new(ArrayState {
+                    values: Vec::new(),
+                    compared: b.compared.clone(),
+                    swapped: b.swapped.clone(),
+                    written: b.written.clone(),
+                    counts: b.counts,
+                    totals: b.totals,
+                }),
+            },
+            (_, next) => Delta::Full(Box::new(next.clone())),
+        }
+    }
+
+    /// Only ever applied to the state it was computed from.
+    fn apply(&self, state: &mut FrameState) {
+        match (self, state) {
+            (Delta::Full(next), state) => *state = (**next).clone(),
+            (Delta::Text(rows), FrameState::Text(grid)) => rows.apply(&mut grid.rows),
+            (Delta::Values(values), FrameState::Scalar(grid)) => values.apply(&mut grid.values),
+            (Delta::Values(values), FrameState::Volume(volume)) => values.apply(&mut volume.values),
+            (Delta::Graph { nodes, edges }, FrameState::Graph(graph)) => {
+                nodes.apply(&mut graph.nodes);
+                edges.apply(&mut graph.edges);
+            }
+            (Delta::Array { values, rest }, FrameState::Array(array)) => {
+                let kept = std::mem::take(&mut array.values);
+                *array = ArrayState {
+                    values: kept,
+                    ..(**rest).clone()
+                };
+                values.apply(&mut array.values);
+            }
+            _ => unreachable!("delta applied to a state of another kind"),
+        }
+    }
+
+    fn bytes(&self) -> usize {
+        size_of::<Delta>()
+            + match self {
+                Delta::Text(rows) => rows.bytes(|row| row.len() * size_of::<char>()),
+                Delta::Values(values) => values.bytes(|_| size_of::<f32>()),
+                Delta::Graph { nodes, edges } => {
+                    nodes.bytes(|n| size_of::<GraphNode>() + n.id.len())
+                        + edges.bytes(|_| size_of::<GraphEdge>())
+                }
+                Delta::Array { values, rest } => {
+                    values.bytes(|_| size_of::<f32>()) + array_bytes(rest)
+                }
+                Delta::Full(state) => state_bytes(state),
+            }
+    }
+}
+
+fn array_bytes(array: &ArrayState) -> usize {
+    size_of::<ArrayState>()
+        + array.values.len() * size_of::<f32>()
+        + array.compared.len() * size_of::<usize>()
+        + array.swapped.len() * size_of::<(usize, usize)>()
+        + array.written.len() * size_of::<usize>()
+}
+
+/// Rough heap and inline size of a state, for the cache budget.
+pub fn state_bytes(state: &FrameState) -> usize {
+    size_of::<FrameState>()
+        + match state {
+            FrameState::Text(grid) => grid
+                .rows
+                .iter()
+                .map(|row| size_of::<Vec<char>>() + row.len() * size_of::<char>())
+                .sum(),
+            FrameState::Scalar(grid) => grid.values.len() * size_of::<f32>(),
+            FrameState::Volume(volume) => volume.values.len() * size_of::<f32>(),
+            FrameState::Graph(graph) => {
+                graph
+                    .nodes
+                    .iter()
+                    .map(|n| size_of::<GraphNode>() + n.id.len())
+                    .sum::<usize>()
+                    + graph.edges.len() * size_of::<GraphEdge>()
+            }
+            FrameState::Array(array) => array_bytes(array),
+        }
+}
+
+/// Counters shown by the history overlay.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HistoryStats {
+    pub keyframes: usize,
+    pub keyframe_bytes: usize,
+    pub delta_bytes: usize,
+    pub budget: usize,
+    /// Time taken by the last lookup that had to replay deltas.
+    pub last_seek: Duration,
+    pub last_replayed: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+#[derive(Debug)]
+struct Keyframe {
+    state: Arc<FrameState>,
+    bytes: usize,
+    last_used: u64,
+}
+
+#[derive(Debug)]
+struct KeyframeCache {
+    interval: usize,
+    budget: usize,
+    keyframes: HashMap<usize, Keyframe>,
+    bytes: usize,
+    uses: u64,
+    /// The two states handed out last, newest first, so stepping forward replays a single delta
+    /// and renderers comparing with the previous frame do not replay at all.
+    recent: [Option<(usize, Arc<FrameState>)>; 2],
+    last_seek: Duration,
+    last_replayed: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl KeyframeCache {
+    fn insert(&mut self, index: usize, state: Arc<FrameState>) {
+        let bytes = state_bytes(&state);
+        self.uses += 1;
+        if let Some(old) = self.keyframes.insert(
+            index,
+            Keyframe {
+                state,
+                bytes,
+                last_used: self.uses,
+            },
+        ) {
+            self.bytes -= old.bytes;
+        }
+        self.bytes += bytes;
+        self.evict();
+    }
+
+    /// Drops the least recently used keyframes until the cache fits its budget.
+    fn evict(&mut self) {
+        while self.bytes > self.budget {
+            let Some(index) = self
+                .keyframes
+                .iter()
+                .min_by_key(|(_, k)| k.last_used)
+                .map(|(i, _)| *i)
+            else {
+                break;
+            };
+            if let Some(evicted) = self.keyframes.remove(&index) {
+                self.bytes -= evicted.bytes;
+            }
+        }
+    }
+
+    fn remember(&mut self, index: usize, state: &Arc<FrameState>) {
+        if self.recent[0].as_ref().is_some_and(|(i, _)| *i == index) {
+            return;
+        }
+        self.recent[1] = self.recent[0].take();
+        self.recent[0] = Some((index, state.clone()));
+    }
+
+    /// Closest cached state at or before `index`, preferring recent ones.
+    fn start_for(&mut self, index: usize) -> Option<(usize, Arc<FrameState>)> {
+        let keyframe = (1..=index / self.interval)
+            .rev()
+            .map(|k| k * self.interval)
+            .find(|k| self.keyframes.contains_key(k));
+        let recent = self
+            .recent
+            .iter()
+            .flatten()
+            .filter(|(i, _)| *i <= index)
+            .max_by_key(|(i, _)| *i);
+        if let Some((i, state)) = recent
+            && keyframe.is_none_or(|k| *i >= k)
+        {
+            return Some((*i, state.clone()));
+        }
+        let k = keyframe?;
+        self.uses += 1;
+        let keyframe = self.keyframes.get_mut(&k)?;
+        keyframe.last_used = self.uses;
+        Some((k, keyframe.state.clone()))
+    }
+}
+
+/// Every state of a story, stored as deltas from the first one.
+#[derive(Debug)]
+pub struct StateHistory {
+    base: Option<Arc<FrameState>>,
+    /// `deltas[i]` turns state `i` into state `i + 1`.
+    deltas: Vec<Delta>,
+    delta_bytes: usize,
+    cache: Mutex<KeyframeCache>,
+}
+
+pub const DEFAULT_KEYFRAME_INTERVAL: usize = 64;
+pub const DEFAULT_CACHE_BUDGET: usize = 256 * 1024 * 1024;
+
+impl StateHistory {
+    pub fn new(states: impl IntoIterator<Item = FrameState>) -> Self {
+        let mut states = states.into_iter();
+        let base = states.next();
+        let mut deltas = Vec::new();
+        if let Some(base) = &base {
+            let mut prev = base.clone();
+            for next in states {
+                deltas.push(Delta::between(&prev, &next));
+                prev = next;
+            }
+        }
+        let delta_bytes = deltas.iter().map(Delta::bytes).sum();
+        let history = Self {
+            base: base.map(Arc::new),
+            deltas,
+            delta_bytes,
+            cache: Mutex::new(KeyframeCache {
+                interval: DEFAULT_KEYFRAME_INTERVAL,
+                budget: DEFAULT_CACHE_BUDGET,
+                keyframes: HashMap::new(),
+                bytes: 0,
+                uses: 0,
+                recent: [None, None],
+                last_seek: Duration::ZERO,
+                last_replayed: 0,
+                hits: 0,
+                misses: 0,
+            }),
+        };
+        history.fill_keyframes();
+        history
+    }
+
+    pub fn len(&self) -> usize {
+        self.base.as_ref().map_or(0, |_| self.deltas.len() + 1)
+    }
+
+    pub fn first(&self) -> Option<&FrameState> {
+        self.base.as_deref()
+    }
+
+    /// State `index`, replayed from the closest cached keyframe. Keyframes crossed on the way
+    /// are cached for the next lookup, which also rebuilds the ones evicted earlier.
+    pub fn get(&self, index: usize) -> Option<Arc<FrameState>> {
+        let base = self.base.as_ref()?;
+        if index >= self.len() {
+            return None;
+        }
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        if index == 0 {
+            return Some(base.clone());
+        }
+        let started = Instant::now();
+        let (from, start) = cache.start_for(index).unwrap_or_else(|| (0, base.clone()));
+        if from == index {
+            cache.hits += 1;
+            cache.remember(index, &start);
+            return Some(start);
+        }
+        let mut state = (*start).clone();
+        for i in from..index {
+            self.deltas[i].apply(&mut state);
+            if (i + 1) % cache.interval == 0 && i + 1 < index {
+                cache.insert(i + 1, Arc::new(state.clone()));
+            }
+        }
+        let state = Arc::new(state);
+        if index % cache.interval == 0 {
+            cache.insert(index, state.clone());
+        }
+        cache.remember(index, &state);
+        cache.misses += 1;
+        cache.last_replayed = index - from;
+        cache.last_seek = started.elapsed();
+        Some(state)
+    }
+
+    /// Walks every state in order without touching the cache, stopping as soon as `f` returns
+    /// `false`. Returns whether every state was visited.
+    pub fn all(&self, mut f: impl FnMut(&FrameState) -> bool) -> bool {
+        let Some(base) = &self.base else {
+            return true;
+        };
+        let mut state = (**base).clone();
+        if !f(&state) {
+            return false;
+        }
+        for delta in &self.deltas {
+            delta.apply(&mut state);
+            if !f(&state) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Smallest and largest numeric value over the states in `frames`, replaying the history up
+    /// to the last of them.
+    pub fn value_range_in(&self, frames: RangeInclusive<usize>) -> Option<(f32, f32)> {
+        let mut range: Option<(f32, f32)> = None;
+        let mut index = 0;
+        self.all(|state| {
+            if frames.contains(&index)
+                && let Some((l, h)) = state.value_range()
+            {
+                range = Some(range.map_or((l, h), |(lo, hi)| (lo.min(l), hi.max(h))));
+            }
+            index += 1;
+            index <= *frames.end()
+        });
+        range
+    }
+
+    /// Caches a keyframe at every multiple of the interval, from the start of the story until
+    /// the budget is full, so that the first seeks do not replay from the base state. Gives up
+    /// when the interval changes meanwhile.
+    pub fn fill_keyframes(&self) {
+        let Some(base) = &self.base else {
+            return;
+        };
+        let interval = self
+            .cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .interval;
+        let mut state = (**base).clone();
+        for (i, delta) in self.deltas.iter().enumerate() {
+            delta.apply(&mut state);
+            let index = i + 1;
+            if index % interval != 0 {
+                continue;
+            }
+            let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+            if cache.interval != interval || cache.bytes + state_bytes(&state) > cache.budget {
+                return;
+            }
+            if !cache.keyframes.contains_key(&index) {
+                cache.insert(index, Arc::new(state.clone()));
+            }
+        }
+    }
+
+    /// Changing the interval invalidates the cached keyframes, a smaller budget evicts some.
+    /// Returns whether there is room for more keyframes, see [`Self::fill_keyframes`].
+    pub fn configure(&self, interval: usize, budget: usize) -> bool {
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        let interval = interval.max(1);
+        let refill = cache.interval != interval || budget > cache.budget;
+        if cache.interval != interval {
+            cache.interval = interval;
+            cache.keyframes.clear();
+            cache.bytes = 0;
+        }
+        cache.budget = budget;
+        cache.evict();
+        refill
+    }
+
+    pub fn stats(&self) -> HistoryStats {
+        let cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        HistoryStats {
+            keyframes: cache.keyframes.len(),
+            keyframe_bytes: cache.bytes,
+            delta_bytes: self.delta_bytes,
+            budget: cache.budget,
+            last_seek: cache.last_seek,
+            last_replayed: cache.last_replayed,
+            hits: cache.hits,
+            misses: cache.misses,
+        }
+    }
+}
+
+/// Pushes [`HistoryConfig`] to the cache of the story, when either of them changes, and refills
+/// the keyframes on the async pool.
+pub fn configure_history_system(config: Res<HistoryConfig>, story: Res<Story>) {
+    if (config.is_changed() || story.is_added())
+        && story
+            .history()
+            .configure(config.keyframe_interval, config.cache_budget)
+    {
+        let (history, _) = story.shared();
+        AsyncComputeTaskPool::get()
+            .spawn(async move { history.fill_keyframes() })
+            .detach();
+    }
+}
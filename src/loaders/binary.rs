@@ -0,0 +1,392 @@
+//! Raw bytes of files nothing else recognises, memory-mapped so multi-gigabyte files open
+//! instantly and only the pages being looked at are read.
+
+use std::{fs::File, path::Path, sync::Arc};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, futures::check_ready},
+};
+use memmap2::Mmap;
+
+use crate::{
+    FileTypeSelection,
+    loaders::{LoadError, numpy::NPY_MAGIC},
+    loading::LoadProgress,
+    story::{Frame, FrameState, ScalarGrid, Story},
+    visualization::{DroppedFile, VisualizationKind},
+};
+
+pub const BYTES_PER_ROW: u64 = 16;
+/// Searches stop after this many matches.
+pub const MAX_MATCHES: usize = 1000;
+/// Bytes searched between two cancellation checks.
+const SEARCH_CHUNK: usize = 16 * 1024 * 1024;
+/// Largest byte range that can be turned into a grid.
+pub const MAX_GRID_BYTES: u64 = 16 * 1024 * 1024;
+
+/// A cheap handle on the mapped bytes, for work done off the main thread.
+#[derive(Clone, Default)]
+pub struct SharedBytes(Option<Arc<Mmap>>);
+
+impl SharedBytes {
+    pub fn bytes(&self) -> &[u8] {
+        self.0.as_deref().map(|map| &map[..]).unwrap_or_default()
+    }
+}
+
+#[derive(Resource)]
+pub struct BinaryFile {
+    map: SharedBytes,
+    pub error: Option<String>,
+    /// Fields of a recognised file header, in file order.
+    pub fields: Vec<HeaderField>,
+}
+
+impl BinaryFile {
+    pub fn open(path: &Path) -> Self {
+        let map = File::open(path).and_then(|file| {
+            if file.metadata()?.len() == 0 {
+                return Ok(None);
+            }
+            // SAFETY: the map is only read. Another process truncating the file while it is
+            // open would fault; that risk is accepted for a viewer.
+            unsafe { Mmap::map(&file) }.map(Some)
+        });
+        match map {
+            Ok(map) => {
+                let fields = map.as_deref().map(header_fields).unwrap_or_default();
+                Self {
+                    map: SharedBytes(map.map(Arc::new)),
+                    error: None,
+                    fields,
+                }
+            }
+            Err(err) => Self {
+                map: SharedBytes::default(),
+                error: Some(LoadError::Io(path.to_path_buf(), err).to_string()),
+                fields: Vec::new(),
+            },
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        self.map.bytes()
+    }
+
+    pub fn share(&self) -> SharedBytes {
+        self.map.clone()
+    }
+
+    pub fn len(&self) -> u64 {
+        self.bytes().len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes().is_empty()
+    }
+
+    pub fn rows(&self) -> u64 {
+        self.len().div_ceil(BYTES_PER_ROW)
+    }
+}
+
+/// A byte pattern search running on the async compute pool, see [`PatternSearch::spawn`].
+#[derive(Resource)]
+pub struct PatternSearch {
+    task: Task<Option<(Vec<u64>, bool)>>,
+    pub progress: Arc<LoadProgress>,
+    pattern_len: u64,
+}
+
+impl PatternSearch {
+    /// Finds the offsets of `pattern`, at most [`MAX_MATCHES`] of them, a chunk at a time.
+    pub fn spawn(bytes: SharedBytes, pattern: Vec<u8>) -> Self {
+        let progress = Arc::new(LoadProgress::default());
+        let task_progress = progress.clone();
+        let pattern_len = pattern.len() as u64;
+        let task = AsyncComputeTaskPool::get()
+            .spawn(async move { find_all(bytes.bytes(), &pattern, &task_progress) });
+        Self {
+            task,
+            progress,
+            pattern_len,
+        }
+    }
+}
+
+/// Offsets of `pattern` and whether there were more than [`MAX_MATCHES`], or `None` once
+/// cancelled. Chunks overlap by the pattern length so no match is split between two.
+fn find_all(bytes: &[u8], pattern: &[u8], progress: &LoadProgress) -> Option<(Vec<u64>, bool)> {
+    let mut offsets = Vec::new();
+    if pattern.is_empty() {
+        return Some((offsets, false));
+    }
+    let finder = memchr::memmem::Finder::new(pattern);
+    progress.set_total(bytes.len() as u64);
+    for start in (0..bytes.len()).step_by(SEARCH_CHUNK) {
+        if progress.is_cancelled() {
+            return None;
+        }
+        let end = (start + SEARCH_CHUNK + pattern.len() - 1).min(bytes.len());
+        for found in finder.find_iter(&bytes[start..end]) {
+            if offsets.len() == MAX_MATCHES {
+                return Some((offsets, true));
+            }
+            offsets.push((start + found) as u64);
+        }
+        progress.advance((end.min(start + SEARCH_CHUNK) - start) as u64);
+    }
+    Some((offsets, false))
+}
+
+/// Hands the offsets found to the hex view, and shows the first one.
+pub fn poll_pattern_search(
+    mut commands: Commands,
+    mut search: ResMut<PatternSearch>,
+    selection: Option<ResMut<FileTypeSelection>>,
+) {
+    let Some(found) = check_ready(&mut search.task) else {
+        return;
+    };
+    commands.remove_resource::<PatternSearch>();
+    let (Some((offsets, truncated)), Some(mut selection)) = (found, selection) else {
+        return;
+    };
+    if let FileTypeSelection::Unknown(_, cfg) = &mut *selection {
+        cfg.matches = offsets;
+        cfg.matches_truncated = truncated;
+        cfg.match_len = search.pattern_len;
+        cfg.current_match = 0;
+        if let Some(&offset) = cfg.matches.first() {
+            cfg.show_offset(offset);
+        }
+    }
+}
+
+pub fn map_binary_system(
+    mut commands: Commands,
+    dropped: Res<DroppedFile>,
+    selection: Res<FileTypeSelection>,
+) {
+    if !matches!(*selection, FileTypeSelection::Unknown(..)) {
+        return;
+    }
+    commands.insert_resource(BinaryFile::open(&dropped.0));
+}
+
+/// A named span of a known header, e.g. the width field of a PNG.
+#[derive(Debug, Clone)]
+pub struct HeaderField {
+    pub start: u64,
+    pub len: u64,
+    pub name: &'static str,
+    pub value: String,
+}
+
+/// Recognises ELF, PNG and npy headers from their magic numbers.
+pub fn header_fields(bytes: &[u8]) -> Vec<HeaderField> {
+    let mut fields = Vec::new();
+    let mut field = |start: u64, len: u64, name: &'static str, value: String| {
+        if start + len <= bytes.len() as u64 {
+            fields.push(HeaderField {
+                start,
+                len,
+                name,
+                value,
+            });
+        }
+    };
+    let byte = |i: usize| bytes.get(i).copied().unwrap_or(0);
+    let u16_at = |i: usize, big: bool| {
+        let b = [byte(i), byte(i + 1)];
+        if big {
+            u16::from_be_bytes(b)
+        } else {
+            u16::from_le_bytes(b)
+        }
+    };
+    let u32_at = |i: usize, big: bool| {
+        let b = [byte(i), byte(i + 1), byte(i + 2), byte(i + 3)];
+        if big {
+            u32::from_be_bytes(b)
+        } else {
+            u32::from_le_bytes(b)
+        }
+    };
+    let u64_at = |i: usize, big: bool| {
+        let b = std::array::from_fn(|k| byte(i + k));
+        if big {
+            u64::from_be_bytes(b)
+        } else {
+            u64::from_le_bytes(b)
+        }
+    };
+
+    if bytes.starts_with(b"\x7fELF") {
+        let wide = byte(4) == 2;
+        let big = byte(5) == 2;
+        field(0, 4, "ELF magic", "\\x7fELF".to_string());
+        field(
+            4,
+            1,
+            "Class",
+            if wide { "64-bit" } else { "32-bit" }.to_string(),
+        );
+        field(
+            5,
+            1,
+            "Endianness",
+            if big { "big" } else { "little" }.to_string(),
+        );
+        field(6, 1, "Version", byte(6).to_string());
+        field(7, 1, "OS ABI", format!("{:#04x}", byte(7)));
+        let kind = match u16_at(16, big) {
+            1 => "relocatable".to_string(),
+            2 => "executable".to_string(),
+            3 => "shared object".to_string(),
+            4 => "core dump".to_string(),
+            other => format!("{other:#06x}"),
+        };
+        field(16, 2, "Type", kind);
+        field(18, 2, "Machine", format!("{:#06x}", u16_at(18, big)));
+        if wide {
+            field(24, 8, "Entry point", format!("{:#x}", u64_at(24, big)));
+        } else {
+            field(24, 4, "Entry point", format!("{:#x}", u32_at(24, big)));
+        }
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        field(0, 8, "PNG signature", "\\x89PNG\\r\\n\\x1a\\n".to_string());
+        field(8, 4, "Chunk length", u32_at(8, true).to_string());
+        field(
+            12,
+            4,
+            "Chunk type",
+            String::from_utf8_lossy(bytes.get(12..16).unwrap_or_default()).into_owned(),
+        );
+        field(16, 4, "Width", u32_at(16, true).to_string());
+        field(20, 4, "Height", u32_at(20, true).to_string());
+        field(24, 1, "Bit depth", byte(24).to_string());
+        field(25, 1, "Colour type", byte(25).to_string());
+    } else if bytes.starts_with(NPY_MAGIC) {
+        field(0, 6, "npy magic", "\\x93NUMPY".to_string());
+        field(6, 2, "Version", format!("{}.{}", byte(6), byte(7)));
+        let header_len = u16_at(8, false) as u64;
+        field(8, 2, "Header length", header_len.to_string());
+        let end = (10 + header_len as usize).min(bytes.len());
+        field(
+            10,
+            header_len,
+            "Header",
+            String::from_utf8_lossy(&bytes[10.min(end)..end])
+                .trim()
+                .to_string(),
+        );
+    }
+    fields
+}
+
+/// Parses an offset typed by the user, in hex with a `0x` prefix or in decimal.
+pub fn parse_offset(text: &str) -> Option<u64> {
+    let text = text.trim().replace('_', "");
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// Parses a search pattern: a quoted string matches its bytes, anything else is read as hex
+/// bytes such as `7f 45 4c 46`.
+pub fn parse_pattern(text: &str) -> Result<Vec<u8>, String> {
+    let text = text.trim();
+    if let Some(quoted) = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+        return Ok(quoted.as_bytes().to_vec());
+    }
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if !digits.is_ascii() || digits.len() % 2 != 0 {
+        return Err("hex patterns need two digits per byte".to_string());
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| format!("'{}' is not a hex byte", &digits[i..i + 2]))
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HexConfiguration {
+    /// First row shown, in rows of [`BYTES_PER_ROW`].
+    pub row: u64,
+    pub goto: String,
+    pub pattern: String,
+    pub pattern_error: Option<String>,
+    pub matches: Vec<u64>,
+    /// The search stopped at [`MAX_MATCHES`] with more left.
+    pub matches_truncated: bool,
+    /// Length in bytes of the pattern `matches` were found for.
+    pub match_len: u64,
+    pub current_match: usize,
+    pub grid_start: u64,
+    pub grid_len: u64,
+    pub grid_width: usize,
+    pub kind: Option<VisualizationKind>,
+}
+
+impl Default for HexConfiguration {
+    fn default() -> Self {
+        Self {
+            row: 0,
+            goto: String::new(),
+            pattern: String::new(),
+            pattern_error: None,
+            matches: Vec::new(),
+            matches_truncated: false,
+            match_len: 0,
+            current_match: 0,
+            grid_start: 0,
+            grid_len: 4096,
+            grid_width: 64,
+            kind: None,
+        }
+    }
+}
+
+impl HexConfiguration {
+    /// Scrolls so that `offset` is near the top of the view.
+    pub fn show_offset(&mut self, offset: u64) {
+        self.row = (offset / BYTES_PER_ROW).saturating_sub(2);
+    }
+}
+
+/// A single frame holding the byte range as a grid of values from 0 to 255.
+pub fn byte_grid_story(
+    path: &Path,
+    bytes: &[u8],
+    cfg: &HexConfiguration,
+    progress: &LoadProgress,
+) -> Result<Story, LoadError> {
+    let start = (cfg.grid_start as usize).min(bytes.len());
+    let len = cfg.grid_len.min(MAX_GRID_BYTES) as usize;
+    let range = &bytes[start..(start + len).min(bytes.len())];
+    if range.is_empty() {
+        return Err(LoadError::Empty(path.to_path_buf()));
+    }
+    progress.set_total(range.len() as u64);
+    let rows = range
+        .chunks(cfg.grid_width.max(1))
+        .map(|row| {
+            progress.check()?;
+            progress.advance(row.len() as u64);
+            Ok(row.iter().map(|&b| f32::from(b)).collect())
+        })
+        .collect::<Result<_, LoadError>>()?;
+    Ok(Story::new(
+        path.to_path_buf(),
+        vec![Frame {
+            tick: 0,
+            state: FrameState::Scalar(ScalarGrid::from_rows(rows)),
+        }],
+    ))
+}
@@ -0,0 +1,436 @@
+//! Graphs, from Graphviz DOT files or edge lists.
+//!
+//! DOT files are read as a single frame; `state` and `weight` attributes on nodes and edges are
+//! kept, everything else is ignored. Edge lists are delimited tables of
+//! `source, target[, weight[, tick[, state]]]`, or any order when a header names the columns.
+//! Rows with a tick update the edge from that tick on.
+
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    loaders::{
+        LoadError,
+        table::{detect_delimiter, detect_header, split_record},
+    },
+    loading::{LoadProgress, read_text},
+    story::{Frame, FrameState, GraphEdge, GraphNode, GraphState, Story},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GraphFormat {
+    #[default]
+    Dot,
+    EdgeList,
+}
+
+impl GraphFormat {
+    pub const ALL: [GraphFormat; 2] = [GraphFormat::Dot, GraphFormat::EdgeList];
+
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv" | "tsv" | "txt") => GraphFormat::EdgeList,
+            _ => GraphFormat::Dot,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GraphFormat::Dot => "Graphviz DOT",
+            GraphFormat::EdgeList => "Edge list (csv, tsv)",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphConfiguration {
+    pub format: GraphFormat,
+}
+
+impl GraphConfiguration {
+    /// Reads the format off the extension of `path`.
+    pub fn for_path(path: &Path) -> Self {
+        Self {
+            format: GraphFormat::from_path(path),
+        }
+    }
+}
+
+pub fn load_graph_story(
+    path: &Path,
+    cfg: &GraphConfiguration,
+    progress: &LoadProgress,
+) -> Result<Story, LoadError> {
+    progress.set_stage("Reading the file");
+    let content = read_text(path, progress)?;
+    progress.set_stage("Parsing graph");
+    let frames = match cfg.format {
+        GraphFormat::Dot => parse_dot(path, &content, progress)?,
+        GraphFormat::EdgeList => parse_edge_list(&content, progress)?,
+    };
+    if frames.is_empty() {
+        return Err(LoadError::Empty(path.to_path_buf()));
+    }
+    Ok(Story::new(path.to_path_buf(), frames))
+}
+
+/// Accumulates node and edge updates, snapshotting the whole graph at every tick. Updates
+/// persist until overwritten, so loaders only need to describe what changed.
+#[derive(Default)]
+pub struct GraphBuilder {
+    graph: GraphState,
+    nodes: HashMap<String, usize>,
+    edges: HashMap<(usize, usize), usize>,
+    tick: Option<u64>,
+    frames: Vec<Frame>,
+}
+
+impl GraphBuilder {
+    fn node_index(&mut self, id: &str) -> usize {
+        if let Some(&index) = self.nodes.get(id) {
+            return index;
+        }
+        let index = self.graph.nodes.len();
+        self.graph.nodes.push(GraphNode {
+            id: id.to_string(),
+            state: None,
+            weight: None,
+        });
+        self.nodes.insert(id.to_string(), index);
+        index
+    }
+
+    /// Adds the node if needed. `None` leaves the current state or weight untouched.
+    pub fn node(&mut self, id: &str, state: Option<String>, weight: Option<f32>) {
+        let index = self.node_index(id);
+        let node = &mut self.graph.nodes[index];
+        node.state = state.or(node.state.take());
+        node.weight = weight.or(node.weight);
+    }
+
+    /// Adds the edge if needed; an update to `b a` applies to an existing `a b` edge.
+    pub fn edge(&mut self, from: &str, to: &str, state: Option<String>, weight: Option<f32>) {
+        let (from, to) = (self.node_index(from), self.node_index(to));
+        let existing = self
+            .edges
+            .get(&(from, to))
+            .or_else(|| self.edges.get(&(to, from)))
+            .copied();
+        let index = existing.unwrap_or_else(|| {
+            self.graph.edges.push(GraphEdge {
+                from,
+                to,
+                state: None,
+                weight: None,
+            });
+            self.edges.insert((from, to), self.graph.edges.len() - 1);
+            self.graph.edges.len() - 1
+        });
+        let edge = &mut self.graph.edges[index];
+        edge.state = state.or(edge.state.take());
+        edge.weight = weight.or(edge.weight);
+    }
+
+    /// Closes the current tick, if any, and starts `tick`.
+    pub fn tick(&mut self, tick: u64) {
+        if let Some(current) = self.tick.replace(tick)
+            && current != tick
+        {
+            self.snapshot(current);
+        }
+    }
+
+    fn snapshot(&mut self, tick: u64) {
+        self.frames.push(Frame {
+            tick,
+            state: FrameState::Graph(self.graph.clone()),
+        });
+    }
+
+    pub fn finish(mut self) -> Vec<Frame> {
+        if !self.graph.nodes.is_empty() {
+            let tick = self.tick.unwrap_or(0);
+            self.snapshot(tick);
+        }
+        self.frames
+    }
+}
+
+fn parse_dot(path: &Path, content: &str, progress: &LoadProgress) -> Result<Vec<Frame>, LoadError> {
+    let invalid = |why: String| LoadError::Invalid(format!("{}: {why}", path.display()));
+    let tokens = dot_tokens(content).map_err(invalid)?;
+    let start = tokens
+        .iter()
+        .position(|t| t == "{")
+        .ok_or_else(|| invalid("expected a '{' opening the graph".to_string()))?;
+    let mut builder = GraphBuilder::default();
+
+    // Statements end at ';' or braces, or implicitly where a node id follows a complete one.
+    let mut statement: Vec<&str> = Vec::new();
+    let mut in_attributes = false;
+    for token in &tokens[start + 1..] {
+        let token = token.as_str();
+        match token {
+            ";" | "{" | "}" if !in_attributes => {
+                progress.check()?;
+                dot_statement(&mut builder, &statement);
+                statement.clear();
+                continue;
+            }
+            "[" => in_attributes = true,
+            "]" => in_attributes = false,
+            "->" | "--" | "=" => {}
+            _ if !in_attributes
+                && statement
+                    .last()
+                    .is_some_and(|last| !matches!(*last, "->" | "--" | "=" | "[" | "subgraph")) =>
+            {
+                dot_statement(&mut builder, &statement);
+                statement.clear();
+            }
+            _ => {}
+        }
+        statement.push(token);
+    }
+    dot_statement(&mut builder, &statement);
+    Ok(builder.finish())
+}
+
+fn dot_statement(builder: &mut GraphBuilder, statement: &[&str]) {
+    let attributes_at = statement.iter().position(|t| *t == "[");
+    let (ids, attributes) = statement.split_at(attributes_at.unwrap_or(statement.len()));
+    let Some(first) = ids.first() else {
+        return;
+    };
+    if matches!(*first, "graph" | "node" | "edge" | "subgraph") || ids.get(1) == Some(&"=") {
+        return;
+    }
+    let mut state = None;
+    let mut weight = None;
+    for pair in attributes.windows(3) {
+        match pair {
+            ["state", "=", value] => state = Some(value.to_string()),
+            ["weight", "=", value] => weight = value.parse().ok(),
+            _ => {}
+        }
+    }
+    let nodes: Vec<&str> = ids
+        .iter()
+        .copied()
+        .filter(|t| *t != "->" && *t != "--")
+        .collect();
+    if nodes.len() == 1 {
+        builder.node(nodes[0], state, weight);
+    }
+    for pair in nodes.windows(2) {
+        builder.edge(pair[0], pair[1], state.clone(), weight);
+    }
+}
+
+/// Splits DOT source into identifiers, quoted strings (unquoted) and punctuation, dropping
+/// comments.
+fn dot_tokens(content: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() || c == ',' => {}
+            '#' => while chars.next_if(|c| *c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|c| *c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '"' => {
+                let mut token = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => token.extend(chars.next()),
+                        Some('"') => break,
+                        Some(c) => token.push(c),
+                        None => return Err("unterminated string".to_string()),
+                    }
+                }
+                tokens.push(token);
+            }
+            '-' if matches!(chars.peek(), Some('>' | '-')) => {
+                tokens.push(format!("-{}", chars.next().unwrap_or('-')));
+            }
+            '{' | '}' | '[' | ']' | ';' | '=' => tokens.push(c.to_string()),
+            c => {
+                let mut token = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || "_.".contains(*c)) {
+                    token.push(c);
+                }
+                tokens.push(token);
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_edge_list(content: &str, progress: &LoadProgress) -> Result<Vec<Frame>, LoadError> {
+    let delimiter = detect_delimiter(content);
+    let mut rows: Vec<Vec<String>> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| split_record(line, delimiter))
+        .collect();
+
+    // Positional order, unless a header says otherwise.
+    let mut columns = [Some(0), Some(1), Some(2), Some(3), Some(4)];
+    if let Some(first) = rows.first()
+        && detect_header(first, rows.get(1).map(Vec::as_slice))
+    {
+        let find = |names: &[&str]| {
+            first
+                .iter()
+                .position(|h| names.contains(&h.trim().to_lowercase().as_str()))
+        };
+        columns = [
+            find(&["source", "from", "src", "u"]).or(Some(0)),
+            find(&["target", "to", "dst", "v"]).or(Some(1)),
+            find(&["weight", "cost", "distance", "w"]),
+            find(&["tick", "time", "step", "t"]),
+            find(&["state", "status", "label"]),
+        ];
+        rows.remove(0);
+    }
+    let [source, target, weight, tick, state] = columns;
+
+    let mut builder = GraphBuilder::default();
+    // Untimed rows describe the initial graph.
+    let tick_of = |row: &Vec<String>| -> Option<u64> {
+        row.get(tick?)?.parse::<f64>().ok().map(|t| t as u64)
+    };
+    rows.sort_by_key(|row| tick_of(row).unwrap_or(0));
+    for row in &rows {
+        progress.check()?;
+        let (Some(from), Some(to)) = (
+            source.and_then(|i| row.get(i)),
+            target.and_then(|i| row.get(i)),
+        ) else {
+            continue;
+        };
+        builder.tick(tick_of(row).unwrap_or(0));
+        let weight = weight.and_then(|i| row.get(i)?.parse().ok());
+        let state = state
+            .and_then(|i| row.get(i))
+            .filter(|s| !s.is_empty())
+            .cloned();
+        builder.edge(from, to, state, weight);
+    }
+    Ok(builder.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graphs(frames: Vec<Frame>) -> Vec<(u64, GraphState)> {
+        frames
+            .into_iter()
+            .map(|frame| match frame.state {
+                FrameState::Graph(graph) => (frame.tick, graph),
+                state => panic!("expected a graph, got {state:?}"),
+            })
+            .collect()
+    }
+
+    fn dot(content: &str) -> GraphState {
+        let frames = parse_dot(Path::new("g.dot"), content, &LoadProgress::default()).unwrap();
+        let mut graphs = graphs(frames);
+        assert_eq!(graphs.len(), 1);
+        graphs.remove(0).1
+    }
+
+    fn edge_list(content: &str) -> Vec<(u64, GraphState)> {
+        graphs(parse_edge_list(content, &LoadProgress::default()).unwrap())
+    }
+
+    fn ids(graph: &GraphState) -> Vec<&str> {
+        graph.nodes.iter().map(|n| n.id.as_str()).collect()
+    }
+
+    #[test]
+    fn tokens_unquote_strings_and_drop_comments() {
+        let tokens =
+            dot_tokens("a -> \"b \\\"c\\\"\" // gone\n/* gone */ # gone\n[w=1.5]").unwrap();
+        assert_eq!(
+            tokens,
+            ["a", "->", "b \"c\"", "[", "w", "=", "1.5", "]"].map(String::from)
+        );
+        assert_eq!(
+            dot_tokens("a -> \"b"),
+            Err("unterminated string".to_string())
+        );
+    }
+
+    #[test]
+    fn statements_end_without_semicolons() {
+        let graph = dot("digraph { a -> b\n c [state=visited]\n d e }");
+        assert_eq!(ids(&graph), ["a", "b", "c", "d", "e"]);
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.nodes[2].state.as_deref(), Some("visited"));
+    }
+
+    #[test]
+    fn quoted_ids_and_attributes() {
+        let graph = dot(r#"graph { "left node" -- "right" [state="tree edge", weight=2.5]; }"#);
+        assert_eq!(ids(&graph), ["left node", "right"]);
+        assert_eq!(graph.edges[0].state.as_deref(), Some("tree edge"));
+        assert_eq!(graph.edges[0].weight, Some(2.5));
+    }
+
+    #[test]
+    fn defaults_and_graph_attributes_are_skipped() {
+        let graph = dot("digraph { rankdir = LR; node [state=x]; edge [weight=1]; a -> b; }");
+        assert_eq!(ids(&graph), ["a", "b"]);
+        assert_eq!(graph.nodes[0].state, None);
+        assert_eq!(graph.edges[0].weight, None);
+    }
+
+    #[test]
+    fn reversed_edges_update_the_existing_one() {
+        let mut builder = GraphBuilder::default();
+        builder.edge("a", "b", Some("tree".to_string()), Some(1.0));
+        builder.edge("b", "a", None, Some(3.0));
+        let graphs = graphs(builder.finish());
+        let edges = &graphs[0].1.edges;
+        assert_eq!(edges.len(), 1);
+        assert_eq!((edges[0].from, edges[0].to), (0, 1));
+        assert_eq!(edges[0].state.as_deref(), Some("tree"));
+        assert_eq!(edges[0].weight, Some(3.0));
+    }
+
+    #[test]
+    fn headers_name_the_columns() {
+        let graphs = edge_list("tick,to,from,cost\n0,b,a,2\n");
+        let edge = &graphs[0].1.edges[0];
+        assert_eq!(ids(&graphs[0].1), ["a", "b"]);
+        assert_eq!(edge.weight, Some(2.0));
+    }
+
+    #[test]
+    fn headerless_rows_are_positional() {
+        let graphs = edge_list("a b 1.5\nb c\n");
+        assert_eq!(graphs.len(), 1);
+        assert_eq!(ids(&graphs[0].1), ["a", "b", "c"]);
+        assert_eq!(graphs[0].1.edges[0].weight, Some(1.5));
+    }
+
+    #[test]
+    fn rows_are_replayed_in_tick_order() {
+        let graphs = edge_list("a,b,1,2,done\na,b,1,0,new\nb,c,1,1,new\n");
+        let ticks: Vec<u64> = graphs.iter().map(|(tick, _)| *tick).collect();
+        assert_eq!(ticks, [0, 1, 2]);
+        assert_eq!(graphs[0].1.edges.len(), 1);
+        assert_eq!(graphs[1].1.edges.len(), 2);
+        assert_eq!(graphs[2].1.edges[0].state.as_deref(), Some("done"));
+    }
+}
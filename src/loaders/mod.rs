@@ -0,0 +1,50 @@
+use std::{fmt, io, path::PathBuf};
+
+pub mod binary;
+pub mod graph;
+pub mod numpy;
+pub mod process;
+pub mod protocol;
+pub mod structured;
+pub mod table;
+pub mod text;
+pub mod volume;
+
+/// Anything that can go wrong while turning a file into a [`crate::story::Story`].
+#[derive(Debug)]
+pub enum LoadError {
+    Io(PathBuf, io::Error),
+    InvalidPattern(String),
+    Empty(PathBuf),
+    Unsupported(String),
+    Invalid(String),
+    /// A malformed line, numbered from 1.
+    Line(PathBuf, usize, String),
+    /// A program failed, with its exit code unless a signal ended it, and the end of its stderr.
+    Exited(PathBuf, Option<i32>, String),
+    Cancelled,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(path, err) => write!(f, "could not read {}: {err}", path.display()),
+            LoadError::InvalidPattern(msg) => write!(f, "invalid separator pattern: {msg}"),
+            LoadError::Empty(path) => write!(f, "no frames found in {}", path.display()),
+            LoadError::Unsupported(what) => write!(f, "cannot visualize {what}"),
+            LoadError::Invalid(why) => write!(f, "malformed file {why}"),
+            LoadError::Line(path, line, why) => {
+                write!(f, "malformed file {}:{line}: {why}", path.display())
+            }
+            LoadError::Exited(path, Some(code), _) => {
+                write!(f, "{} exited with code {code}", path.display())
+            }
+            LoadError::Exited(path, None, _) => {
+                write!(f, "{} was stopped by a signal", path.display())
+            }
+            LoadError::Cancelled => write!(f, "loading was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
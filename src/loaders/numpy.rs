@@ -0,0 +1,460 @@
+//! NumPy `.npy` arrays and `.npz` archives of them.
+//!
+//! An `.npy` file starts with the magic `\x93NUMPY`, a version, the header length (`u16` in
+//! version 1, `u32` after) and a python dict literal such as
+//! `{'descr': '<f8', 'fortran_order': False, 'shape': (64, 64), }`, followed by the raw data.
+//! An `.npz` is a zip archive holding one `.npy` per named array.
+
+use std::{
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    FileTypeSelection,
+    loaders::LoadError,
+    loading::{LoadProgress, Loaded, LoadingTask, read_file},
+    story::{Frame, FrameState, ScalarGrid, ScalarVolume, Story},
+    visualization::{DroppedFile, VisualizationKind},
+};
+
+pub const NPY_MAGIC: &[u8; 6] = b"\x93NUMPY";
+pub const NPZ_MAGIC: &[u8; 4] = b"PK\x03\x04";
+
+/// Element type of an array, from the `descr` field of the header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NpyDtype {
+    Bool,
+    Int(usize),
+    UInt(usize),
+    Float(usize),
+}
+
+impl NpyDtype {
+    /// Parses descriptors such as `<f8`, `|u1` or `>i4`, returning the type and whether it is
+    /// big-endian.
+    fn parse(descr: &str) -> Result<(Self, bool), String> {
+        let mut chars = descr.chars();
+        let big_endian = match chars.next() {
+            Some('>') => true,
+            Some('<' | '|' | '=') => false,
+            _ => return Err(format!("unknown byte order in dtype '{descr}'")),
+        };
+        let kind = chars.next();
+        let size: usize = chars
+            .as_str()
+            .parse()
+            .map_err(|_| format!("unknown dtype '{descr}'"))?;
+        let dtype = match (kind, size) {
+            (Some('b'), 1) => NpyDtype::Bool,
+            (Some('i'), 1 | 2 | 4 | 8) => NpyDtype::Int(size),
+            (Some('u'), 1 | 2 | 4 | 8) => NpyDtype::UInt(size),
+            (Some('f'), 4 | 8) => NpyDtype::Float(size),
+            _ => return Err(format!("unsupported dtype '{descr}'")),
+        };
+        Ok((dtype, big_endian))
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            NpyDtype::Bool => 1,
+            NpyDtype::Int(size) | NpyDtype::UInt(size) | NpyDtype::Float(size) => *size,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            NpyDtype::Bool => "bool".to_string(),
+            NpyDtype::Int(size) => format!("int{}", size * 8),
+            NpyDtype::UInt(size) => format!("uint{}", size * 8),
+            NpyDtype::Float(size) => format!("float{}", size * 8),
+        }
+    }
+
+    fn read(&self, bytes: &[u8], big_endian: bool) -> f32 {
+        let mut buf = [0u8; 8];
+        buf[..bytes.len()].copy_from_slice(bytes);
+        if big_endian {
+            buf[..bytes.len()].reverse();
+        }
+        match self {
+            NpyDtype::Bool => f32::from(u8::from(buf[0] != 0)),
+            NpyDtype::Int(1) => buf[0] as i8 as f32,
+            NpyDtype::Int(2) => i16::from_le_bytes([buf[0], buf[1]]) as f32,
+            NpyDtype::Int(4) => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f32,
+            NpyDtype::Int(_) => i64::from_le_bytes(buf) as f32,
+            NpyDtype::UInt(1) => buf[0] as f32,
+            NpyDtype::UInt(2) => u16::from_le_bytes([buf[0], buf[1]]) as f32,
+            NpyDtype::UInt(4) => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f32,
+            NpyDtype::UInt(_) => u64::from_le_bytes(buf) as f32,
+            NpyDtype::Float(4) => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]),
+            NpyDtype::Float(_) => f64::from_le_bytes(buf) as f32,
+        }
+    }
+}
+
+/// One array, its values converted to `f32` and stored in C order whatever the file's order.
+#[derive(Debug, Clone)]
+pub struct NpyArray {
+    /// Name inside an `.npz`, or the file stem for a lone `.npy`.
+    pub name: String,
+    pub dtype: NpyDtype,
+    pub shape: Vec<usize>,
+    pub fortran_order: bool,
+    pub values: Vec<f32>,
+}
+
+impl NpyArray {
+    pub fn shape_label(&self) -> String {
+        let dims: Vec<String> = self.shape.iter().map(usize::to_string).collect();
+        format!("({})", dims.join(", "))
+    }
+
+    /// Value at a C-order multi-index.
+    fn at(&self, index: &[usize]) -> f32 {
+        let flat = index
+            .iter()
+            .zip(&self.shape)
+            .fold(0, |flat, (i, dim)| flat * dim + i);
+        self.values[flat]
+    }
+}
+
+/// Parses one `.npy` file held in memory.
+pub fn parse_npy(name: String, bytes: &[u8]) -> Result<NpyArray, String> {
+    if bytes.len() < 10 || &bytes[..6] != NPY_MAGIC {
+        return Err("missing NUMPY header".to_string());
+    }
+    let (header_len, header_start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => (
+            u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+            12,
+        ),
+        version => return Err(format!("unsupported npy version {version}")),
+    };
+    let data_start = header_start + header_len;
+    let header = bytes
+        .get(header_start..data_start)
+        .and_then(|h| std::str::from_utf8(h).ok())
+        .ok_or("truncated header")?;
+
+    let descr = header_field(header, "descr")
+        .and_then(|rest| rest.strip_prefix('\''))
+        .and_then(|rest| rest.split('\'').next())
+        .ok_or("header has no 'descr'")?;
+    let (dtype, big_endian) = NpyDtype::parse(descr)?;
+    let fortran_order =
+        header_field(header, "fortran_order").is_some_and(|v| v.starts_with("True"));
+    let shape: Vec<usize> = header_field(header, "shape")
+        .and_then(|rest| rest.strip_prefix('('))
+        .and_then(|rest| rest.split(')').next())
+        .ok_or("header has no 'shape'")?
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| {
+            dim.parse()
+                .map_err(|_| format!("invalid dimension '{dim}'"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let overflow = || "shape is too large".to_string();
+    let count = shape
+        .iter()
+        .try_fold(1usize, |count, &dim| count.checked_mul(dim))
+        .ok_or_else(overflow)?;
+    let byte_len = count.checked_mul(dtype.size()).ok_or_else(overflow)?;
+    let data = &bytes[data_start..];
+    if data.len() < byte_len {
+        return Err("data is shorter than the header claims".to_string());
+    }
+    let mut values: Vec<f32> = data
+        .chunks_exact(dtype.size())
+        .take(count)
+        .map(|chunk| dtype.read(chunk, big_endian))
+        .collect();
+    if fortran_order {
+        values = fortran_to_c(&values, &shape);
+    }
+    Ok(NpyArray {
+        name,
+        dtype,
+        shape,
+        fortran_order,
+        values,
+    })
+}
+
+/// The text following `'key':` in a header dict.
+fn header_field<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let pattern = format!("'{key}':");
+    let start = header.find(&pattern)? + pattern.len();
+    Some(header[start..].trim_start())
+}
+
+fn fortran_to_c(values: &[f32], shape: &[usize]) -> Vec<f32> {
+    (0..values.len())
+        .map(|c| {
+            // Split the C-order index into per-axis indices, last axis first, and recombine them
+            // with column-major strides.
+            let mut rest = c;
+            let mut index = vec![0; shape.len()];
+            for (axis, dim) in shape.iter().enumerate().rev() {
+                index[axis] = rest % dim;
+                rest /= dim;
+            }
+            let f = index
+                .iter()
+                .zip(shape)
+                .rev()
+                .fold(0, |flat, (i, dim)| flat * dim + i);
+            values[f]
+        })
+        .collect()
+}
+
+/// Reads every array of an `.npy` or `.npz` file, telling them apart by their magic bytes.
+pub fn read_numpy(path: &Path, progress: &LoadProgress) -> Result<Vec<NpyArray>, LoadError> {
+    let io_err = |e| LoadError::Io(path.to_path_buf(), e);
+    let invalid = |why: String| LoadError::Invalid(format!("{}: {why}", path.display()));
+    progress.set_stage("Reading arrays");
+    let bytes = read_file(path, progress)?;
+
+    if bytes.starts_with(NPY_MAGIC) {
+        let name = path
+            .file_stem()
+            .map_or("array".to_string(), |s| s.to_string_lossy().into_owned());
+        return parse_npy(name, &bytes)
+            .map(|array| vec![array])
+            .map_err(invalid);
+    }
+    if !bytes.starts_with(NPZ_MAGIC) {
+        return Err(invalid("neither an npy nor an npz file".to_string()));
+    }
+
+    progress.set_stage("Unpacking arrays");
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(&bytes)).map_err(|e| invalid(e.to_string()))?;
+    let mut arrays = Vec::new();
+    for i in 0..archive.len() {
+        progress.check()?;
+        let mut entry = archive.by_index(i).map_err(|e| invalid(e.to_string()))?;
+        let Some(name) = entry.name().strip_suffix(".npy").map(str::to_string) else {
+            continue;
+        };
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).map_err(io_err)?;
+        arrays
+            .push(parse_npy(name.clone(), &data).map_err(|why| invalid(format!("{name}: {why}")))?);
+    }
+    if arrays.is_empty() {
+        return Err(LoadError::Empty(path.to_path_buf()));
+    }
+    Ok(arrays)
+}
+
+/// Every array found in the dropped file, for the array picker.
+#[derive(Resource, Debug)]
+pub struct NumpyFile {
+    pub arrays: Result<Vec<NpyArray>, String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NumpyConfiguration {
+    /// Index of the selected array in [`NumpyFile::arrays`].
+    pub array: usize,
+    /// For 3D arrays, the axis frames are taken along. `None` shows the array as a volume.
+    pub time_axis: Option<usize>,
+    pub kind: Option<VisualizationKind>,
+}
+
+pub fn parse_numpy_system(
+    mut commands: Commands,
+    dropped: Res<DroppedFile>,
+    selection: Res<FileTypeSelection>,
+) {
+    if !matches!(*selection, FileTypeSelection::NumPy(..)) {
+        return;
+    }
+    let path = dropped.0.clone();
+    commands.insert_resource(LoadingTask::read(
+        move |progress| match read_numpy(&path, progress) {
+            Err(LoadError::Cancelled) => Err(LoadError::Cancelled),
+            arrays => Ok(Loaded::Numpy(NumpyFile {
+                arrays: arrays.map_err(|e| e.to_string()),
+            })),
+        },
+        |commands| {
+            commands.insert_resource(NumpyFile {
+                arrays: Err(LoadError::Cancelled.to_string()),
+            })
+        },
+    ));
+}
+
+/// Turns an array into frames: 1D and 2D arrays are a single grid, 3D arrays are either one
+/// volume or one grid per index along `cfg.time_axis`.
+pub fn numpy_story(
+    source: &Path,
+    array: &NpyArray,
+    cfg: &NumpyConfiguration,
+    progress: &LoadProgress,
+) -> Result<Story, LoadError> {
+    let frames = match (array.shape.as_slice(), cfg.time_axis) {
+        (&[width], _) => vec![FrameState::Scalar(ScalarGrid {
+            width,
+            height: 1,
+            values: array.values.clone(),
+        })],
+        (&[height, width], _) => vec![FrameState::Scalar(ScalarGrid {
+            width,
+            height,
+            values: array.values.clone(),
+        })],
+        (&[depth, height, width], None) => vec![FrameState::Volume(ScalarVolume {
+            width,
+            height,
+            depth,
+            values: array.values.clone(),
+        })],
+        (shape @ &[_, _, _], Some(time)) => {
+            let time = time.min(2);
+            // The two remaining axes, in order, become rows and columns.
+            let [rows, cols] = match time {
+                0 => [1, 2],
+                1 => [0, 2],
+                _ => [0, 1],
+            };
+            let (height, width) = (shape[rows], shape[cols]);
+            (0..shape[time])
+                .map(|t| {
+                    progress.check()?;
+                    let mut values = Vec::with_capacity(width * height);
+                    let mut index = [0; 3];
+                    index[time] = t;
+                    for y in 0..height {
+                        for x in 0..width {
+                            index[rows] = y;
+                            index[cols] = x;
+                            values.push(array.at(&index));
+                        }
+                    }
+                    Ok(FrameState::Scalar(ScalarGrid {
+                        width,
+                        height,
+                        values,
+                    }))
+                })
+                .collect::<Result<_, LoadError>>()?
+        }
+        (shape, _) => {
+            return Err(LoadError::Unsupported(format!(
+                "{}-dimensional array '{}'",
+                shape.len(),
+                array.name
+            )));
+        }
+    };
+    if frames.is_empty() {
+        return Err(LoadError::Empty(source.to_path_buf()));
+    }
+    Ok(Story::new(
+        source.to_path_buf(),
+        frames
+            .into_iter()
+            .enumerate()
+            .map(|(tick, state)| Frame {
+                tick: tick as u64,
+                state,
+            })
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A version 1 `.npy` file.
+    fn npy(header: &str, data: &[u8]) -> Vec<u8> {
+        let mut bytes = NPY_MAGIC.to_vec();
+        bytes.extend([1, 0]);
+        bytes.extend((header.len() as u16).to_le_bytes());
+        bytes.extend(header.as_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    fn parse(header: &str, data: &[u8]) -> Result<NpyArray, String> {
+        parse_npy("a".to_string(), &npy(header, data))
+    }
+
+    fn error(header: &str, data: &[u8]) -> String {
+        parse(header, data).unwrap_err()
+    }
+
+    #[test]
+    fn reads_a_fortran_ordered_matrix() {
+        let array = parse(
+            "{'descr': '|u1', 'fortran_order': True, 'shape': (2, 3), }",
+            &[1, 2, 3, 4, 5, 6],
+        )
+        .unwrap();
+        assert_eq!(array.shape, [2, 3]);
+        assert_eq!(array.values, [1.0, 3.0, 5.0, 2.0, 4.0, 6.0]);
+    }
+
+    #[test]
+    fn rejects_a_missing_magic() {
+        assert_eq!(
+            parse_npy("a".to_string(), b"NUMPY\x01\0\0\0\0").unwrap_err(),
+            "missing NUMPY header"
+        );
+    }
+
+    #[test]
+    fn rejects_overflowing_shapes() {
+        let too_large = "shape is too large";
+        // The element count overflows.
+        assert_eq!(
+            error(
+                "{'descr': '<f4', 'fortran_order': False, 'shape': (4294967296, 4294967296, 2), }",
+                &[],
+            ),
+            too_large
+        );
+        // The element count fits, its byte count does not.
+        assert_eq!(
+            error(
+                "{'descr': '<f8', 'fortran_order': False, 'shape': (4611686018427387904,), }",
+                &[],
+            ),
+            too_large
+        );
+    }
+
+    #[test]
+    fn rejects_missing_data() {
+        assert_eq!(
+            error(
+                "{'descr': '<f4', 'fortran_order': False, 'shape': (4,), }",
+                &[0; 8],
+            ),
+            "data is shorter than the header claims"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_dimensions() {
+        assert_eq!(
+            error(
+                "{'descr': '<f4', 'fortran_order': False, 'shape': (-1,), }",
+                &[],
+            ),
+            "invalid dimension '-1'"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Programs as story sources: whatever an executable prints on stdout is its story.
+//!
+//! Output made of protocol events (see [`crate::loaders::protocol`]) is read as a trace, anything
+//! else as text frames split like a text file with the default separator.
+
+use std::{
+    io::{self, Read},
+    path::Path,
+    process::{Child, ChildStderr, ChildStdout, Command, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    ExecutableConfiguration,
+    loaders::{
+        LoadError,
+        protocol::{looks_like_protocol, parse_protocol},
+        text::{TextConfiguration, split_frames},
+    },
+    loading::LoadProgress,
+    story::Story,
+};
+
+/// How long to wait for output before checking for cancellation again.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const READ_CHUNK: usize = 64 * 1024;
+/// Bytes of stderr kept for the error shown when the program fails.
+const STDERR_TAIL: usize = 4 * 1024;
+
+/// The command running the program at `path`, through its interpreter when one is set.
+pub fn program_command(path: &Path, cfg: &ExecutableConfiguration) -> Command {
+    match &cfg.interpreter {
+        Some(interpreter) => {
+            let mut command = Command::new(&interpreter.0);
+            command.arg(path);
+            command
+        }
+        None => Command::new(path),
+    }
+}
+
+/// Runs the program at `path` and collects its output. Cancelling kills the program, even while
+/// it prints nothing.
+pub fn run_executable_story(
+    path: &Path,
+    cfg: &ExecutableConfiguration,
+    progress: &LoadProgress,
+) -> Result<Story, LoadError> {
+    let mut command = program_command(path, cfg);
+    progress.set_stage("Running the program");
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+    let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        stop(&mut child);
+        return Err(LoadError::Invalid(format!("{}: no output", path.display())));
+    };
+    let chunks = read_chunks(stdout);
+    let stderr = thread::spawn(move || read_tail(stderr));
+
+    let mut output = Vec::new();
+    loop {
+        match chunks.recv_timeout(POLL_INTERVAL) {
+            Ok(Ok(chunk)) => {
+                progress.advance(chunk.len() as u64);
+                output.extend_from_slice(&chunk);
+            }
+            Ok(Err(e)) => {
+                stop(&mut child);
+                return Err(LoadError::Io(path.to_path_buf(), e));
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if progress.is_cancelled() {
+            stop(&mut child);
+            return Err(LoadError::Cancelled);
+        }
+    }
+    // The program may keep running after closing its output.
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => {}
+            Err(e) => {
+                stop(&mut child);
+                return Err(LoadError::Io(path.to_path_buf(), e));
+            }
+        }
+        if progress.is_cancelled() {
+            stop(&mut child);
+            return Err(LoadError::Cancelled);
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+    if !status.success() {
+        let stderr = stderr.join().unwrap_or_default();
+        let stderr = String::from_utf8_lossy(&stderr).trim().to_string();
+        return Err(LoadError::Exited(path.to_path_buf(), status.code(), stderr));
+    }
+
+    let output = String::from_utf8(output)
+        .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned());
+    if looks_like_protocol(&output) {
+        return parse_protocol(path, &output, progress);
+    }
+    progress.set_stage("Splitting frames");
+    let frames = split_frames(&output, &TextConfiguration::default().compile()?, progress)?;
+    if frames.is_empty() {
+        return Err(LoadError::Empty(path.to_path_buf()));
+    }
+    Ok(Story::new(path.to_path_buf(), frames))
+}
+
+/// Kills the program and reaps it, so it does not linger as a zombie.
+fn stop(child: &mut Child) {
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Reads `stdout` on its own thread, so the caller can give up while the program is silent. The
+/// channel closes at the end of the output.
+fn read_chunks(mut stdout: ChildStdout) -> Receiver<io::Result<Vec<u8>>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = vec![0; READ_CHUNK];
+        loop {
+            let chunk = match stdout.read(&mut buffer) {
+                Ok(0) => return,
+                Ok(read) => Ok(buffer[..read].to_vec()),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(e),
+            };
+            let failed = chunk.is_err();
+            if sender.send(chunk).is_err() || failed {
+                return;
+            }
+        }
+    });
+    receiver
+}
+
+/// The last [`STDERR_TAIL`] bytes written to `stderr`.
+fn read_tail(mut stderr: ChildStderr) -> Vec<u8> {
+    let mut tail = Vec::new();
+    let mut buffer = vec![0; READ_CHUNK];
+    loop {
+        match stderr.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => {
+                tail.extend_from_slice(&buffer[..read]);
+                let excess = tail.len().saturating_sub(STDERR_TAIL);
+                tail.drain(..excess);
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => break,
+        }
+    }
+    tail
+}
@@ -0,0 +1,339 @@
+//! The story protocol: a line-based event log that a program can print while it runs.
+//!
+//! ```text
+//! # BFS from A
+//! tick 0
+//! node A frontier
+//! tick 1
+//! node A visited
+//! edge A B tree 2.5
+//! node B frontier
+//! ```
+//!
+//! `tick N` starts a new tick, events before the first `tick` line are folded into it.
+//! `node ID` and `edge FROM TO` take an optional state and an optional numeric weight, in any
+//! order, and their values persist until changed. Lines starting with `#` are comments.
+//! `marker TEXT` labels the current tick, e.g. `marker phase 2 begins`.
+//!
+//! Array traces use `array V...` for a full snapshot, then `compare I J`, `swap I J` and
+//! `set I V`. When a trace has no `tick` line at all, every array event is its own tick.
+//! A story holds either graph or array events, not both.
+
+use std::path::Path;
+
+use crate::{
+    loaders::{LoadError, graph::GraphBuilder},
+    loading::{LoadProgress, read_text},
+    story::{ArrayState, Frame, FrameState, Marker, OperationCounts, Story},
+};
+
+/// Words that start a protocol event, used to tell a trace from plain text frames.
+const EVENTS: [&str; 8] = [
+    "tick", "marker", "node", "edge", "array", "compare", "swap", "set",
+];
+
+pub fn load_protocol_story(path: &Path, progress: &LoadProgress) -> Result<Story, LoadError> {
+    progress.set_stage("Reading the file");
+    let content = read_text(path, progress)?;
+    parse_protocol(path, &content, progress)
+}
+
+/// Whether the first event of `content`, skipping blank lines and comments, is a protocol event.
+pub fn looks_like_protocol(content: &str) -> bool {
+    content
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .find(|word| !word.starts_with('#'))
+        .is_some_and(|word| EVENTS.contains(&word))
+}
+
+/// Parses a trace that was read from `path`, e.g. the output of a program.
+pub fn parse_protocol(
+    path: &Path,
+    content: &str,
+    progress: &LoadProgress,
+) -> Result<Story, LoadError> {
+    progress.set_stage("Replaying events");
+    let invalid = |number: usize, why: String| LoadError::Line(path.to_path_buf(), number + 1, why);
+    let mut builder = ProtocolBuilder {
+        auto_tick: !content
+            .lines()
+            .any(|line| line.split_whitespace().next() == Some("tick")),
+        ..Default::default()
+    };
+    for (number, line) in content.lines().enumerate() {
+        progress.check()?;
+        builder.event(line).map_err(|why| invalid(number, why))?;
+    }
+    let frames = match (builder.has_graph, builder.has_array) {
+        (true, true) => {
+            return Err(LoadError::Unsupported(
+                "a story mixing graph and array events".to_string(),
+            ));
+        }
+        (_, true) => builder.array.finish(),
+        _ => builder.graph.finish(),
+    };
+    if frames.is_empty() {
+        return Err(LoadError::Empty(path.to_path_buf()));
+    }
+    Ok(Story::new(path.to_path_buf(), frames).with_markers(builder.markers))
+}
+
+#[derive(Default)]
+struct ProtocolBuilder {
+    graph: GraphBuilder,
+    array: ArrayBuilder,
+    has_graph: bool,
+    has_array: bool,
+    auto_tick: bool,
+    tick: u64,
+    markers: Vec<Marker>,
+}
+
+impl ProtocolBuilder {
+    fn event(&mut self, line: &str) -> Result<(), String> {
+        let mut words = line.split_whitespace();
+        let Some(event) = words.next() else {
+            return Ok(());
+        };
+        match event {
+            comment if comment.starts_with('#') => {}
+            "tick" => {
+                let tick = words.next().ok_or("'tick' needs a number")?;
+                let tick = tick.parse().map_err(|_| format!("invalid tick '{tick}'"))?;
+                self.graph.tick(tick);
+                self.array.tick(tick);
+                self.tick = tick;
+            }
+            "marker" => {
+                let label = line.trim_start()["marker".len()..].trim();
+                if label.is_empty() {
+                    return Err("'marker' needs a label".to_string());
+                }
+                // Without tick lines, the marker belongs to the next array event.
+                let tick = if self.auto_tick {
+                    self.array.tick.map_or(0, |t| t + 1)
+                } else {
+                    self.tick
+                };
+                self.markers.push(Marker {
+                    tick,
+                    label: label.to_string(),
+                });
+            }
+            "node" => {
+                let id = words.next().ok_or("'node' needs an id")?;
+                let (state, weight) = state_and_weight(words);
+                self.graph.node(id, state, weight);
+                self.has_graph = true;
+            }
+            "edge" => {
+                let (Some(from), Some(to)) = (words.next(), words.next()) else {
+                    return Err("'edge' needs two node ids".to_string());
+                };
+                let (state, weight) = state_and_weight(words);
+                self.graph.edge(from, to, state, weight);
+                self.has_graph = true;
+            }
+            "array" | "compare" | "swap" | "set" => {
+                if self.auto_tick {
+                    let next = self.array.tick.map_or(0, |t| t + 1);
+                    self.array.tick(next);
+                }
+                let numbers = words
+                    .map(|w| {
+                        w.parse::<f32>()
+                            .map_err(|_| format!("'{w}' is not a number"))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.array.event(event, &numbers)?;
+                self.has_array = true;
+            }
+            event => return Err(format!("unknown event '{event}'")),
+        }
+        Ok(())
+    }
+}
+
+fn state_and_weight<'a>(words: impl Iterator<Item = &'a str>) -> (Option<String>, Option<f32>) {
+    let mut state = None;
+    let mut weight = None;
+    for word in words {
+        match word.parse::<f32>() {
+            Ok(value) => weight = Some(value),
+            Err(_) => state = Some(word.to_string()),
+        }
+    }
+    (state, weight)
+}
+
+/// Applies array operations, snapshotting the array and what touched it at every tick.
+#[derive(Default)]
+struct ArrayBuilder {
+    state: ArrayState,
+    tick: Option<u64>,
+    frames: Vec<Frame>,
+}
+
+impl ArrayBuilder {
+    fn tick(&mut self, tick: u64) {
+        if let Some(current) = self.tick.replace(tick)
+            && current != tick
+        {
+            self.snapshot(current);
+        }
+    }
+
+    fn snapshot(&mut self, tick: u64) {
+        self.frames.push(Frame {
+            tick,
+            state: FrameState::Array(self.state.clone()),
+        });
+        self.state.compared.clear();
+        self.state.swapped.clear();
+        self.state.written.clear();
+        self.state.counts = OperationCounts::default();
+    }
+
+    fn index(&self, value: f32) -> Result<usize, String> {
+        let len = self.state.values.len();
+        if value < 0.0 || value.fract() != 0.0 || value as usize >= len {
+            return Err(format!("index {value} is out of bounds for {len} elements"));
+        }
+        Ok(value as usize)
+    }
+
+    fn event(&mut self, event: &str, numbers: &[f32]) -> Result<(), String> {
+        match (event, numbers) {
+            ("array", values) => {
+                // Highlights point into the replaced values, they may not exist anymore.
+                let state = &mut self.state;
+                state.values = values.to_vec();
+                state.compared.clear();
+                state.swapped.clear();
+                state.written.clear();
+            }
+            ("compare", &[i, j]) => {
+                let (i, j) = (self.index(i)?, self.index(j)?);
+                let state = &mut self.state;
+                state.compared.extend([i, j]);
+                state.counts.compares += 1;
+                state.totals.compares += 1;
+            }
+            ("swap", &[i, j]) => {
+                let (i, j) = (self.index(i)?, self.index(j)?);
+                let state = &mut self.state;
+                state.values.swap(i, j);
+                state.swapped.push((i, j));
+                state.counts.swaps += 1;
+                state.totals.swaps += 1;
+            }
+            ("set", &[i, value]) => {
+                let i = self.index(i)?;
+                let state = &mut self.state;
+                state.values[i] = value;
+                state.written.push(i);
+                state.counts.writes += 1;
+                state.totals.writes += 1;
+            }
+            (event, _) => return Err(format!("'{event}' needs two numbers")),
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Vec<Frame> {
+        if let Some(tick) = self.tick {
+            self.snapshot(tick);
+        }
+        self.frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Result<Story, LoadError> {
+        parse_protocol(Path::new("trace"), content, &LoadProgress::default())
+    }
+
+    fn ticks(story: &Story) -> Vec<u64> {
+        (0..story.len()).filter_map(|i| story.tick(i)).collect()
+    }
+
+    fn array(story: &Story, index: usize) -> ArrayState {
+        match story.state(index).as_deref() {
+            Some(FrameState::Array(array)) => array.clone(),
+            state => panic!("expected an array, got {state:?}"),
+        }
+    }
+
+    fn markers(story: &Story) -> Vec<(u64, &str)> {
+        story
+            .markers
+            .iter()
+            .map(|m| (m.tick, m.label.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn array_events_tick_themselves_without_tick_lines() {
+        let story = parse("array 3 1 2\ncompare 0 1\nmarker  in order\nswap 0 1\n").unwrap();
+        assert_eq!(ticks(&story), [0, 1, 2]);
+        assert_eq!(markers(&story), [(2, "in order")]);
+        assert_eq!(array(&story, 1).compared, [0, 1]);
+        let swapped = array(&story, 2);
+        assert_eq!(swapped.values, [1.0, 3.0, 2.0]);
+        assert_eq!(swapped.swapped, [(0, 1)]);
+        assert!(swapped.compared.is_empty());
+        assert_eq!((swapped.totals.compares, swapped.totals.swaps), (1, 1));
+    }
+
+    #[test]
+    fn markers_label_the_current_tick() {
+        let story =
+            parse("marker start\ntick 0\narray 2 1\nmarker first\ntick 5\nswap 0 1\nmarker last\n")
+                .unwrap();
+        assert_eq!(ticks(&story), [0, 5]);
+        assert_eq!(markers(&story), [(0, "start"), (0, "first"), (5, "last")]);
+    }
+
+    #[test]
+    fn array_snapshots_drop_highlights() {
+        let story = parse("tick 0\narray 1 2 3\ncompare 0 2\nset 2 9\narray 4 5\n").unwrap();
+        let state = array(&story, 0);
+        assert_eq!(state.values, [4.0, 5.0]);
+        assert!(state.compared.is_empty() && state.written.is_empty());
+        assert_eq!(state.counts.compares, 1);
+    }
+
+    #[test]
+    fn graph_events_persist_across_ticks() {
+        let story =
+            parse("# BFS\ntick 0\nnode A frontier\ntick 1\nnode A visited\nedge A B 2.5 tree\n")
+                .unwrap();
+        assert_eq!(ticks(&story), [0, 1]);
+        let Some(FrameState::Graph(graph)) = story.state(1).as_deref().cloned() else {
+            panic!("expected a graph");
+        };
+        assert_eq!(graph.nodes[0].state.as_deref(), Some("visited"));
+        assert_eq!(graph.edges[0].state.as_deref(), Some("tree"));
+        assert_eq!(graph.edges[0].weight, Some(2.5));
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = parse("tick 0\narray 1 2\n\nswap 0 2\n").unwrap_err();
+        assert!(matches!(error, LoadError::Line(_, 4, why) if why.contains("out of bounds")));
+        let error = parse("node A\narray 1\n").unwrap_err();
+        assert!(matches!(error, LoadError::Unsupported(_)));
+        assert!(matches!(parse("# nothing\n"), Err(LoadError::Empty(_))));
+    }
+
+    #[test]
+    fn protocol_is_told_apart_from_text() {
+        assert!(looks_like_protocol("\n# comment\ntick 0\n"));
+        assert!(!looks_like_protocol("# comment\nhello world\n"));
+    }
+}
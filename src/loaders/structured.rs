@@ -0,0 +1,261 @@
+use std::{collections::HashSet, path::Path};
+
+use bevy::prelude::*;
+use serde_json::Value;
+
+use crate::{
+    FileTypeSelection,
+    loaders::LoadError,
+    loading::{LoadProgress, Loaded, LoadingTask, read_text},
+    story::{Frame, FrameState, ScalarGrid, ScalarVolume, Story, TextGrid},
+    visualization::{DroppedFile, VisualizationKind},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl DocumentFormat {
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml" | "yml" | "yamlc" | "ymlc") => DocumentFormat::Yaml,
+            Some("toml") => DocumentFormat::Toml,
+            _ => DocumentFormat::Json,
+        }
+    }
+}
+
+/// A parsed json, yaml or toml file, kept as a json tree whatever the source format.
+#[derive(Resource, Debug)]
+pub struct StructuredDocument {
+    pub format: DocumentFormat,
+    pub root: Result<Value, String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReadableConfiguration {
+    /// Case-insensitive filter on keys and scalar values.
+    pub search: String,
+    /// The lowercase search last applied, and the pointers of the nodes it keeps.
+    pub search_matches: Option<(String, HashSet<String>)>,
+    /// JSON pointer to the frames, where `*` matches every child (e.g. `/frames/*/grid`).
+    pub binding: String,
+    /// The binding last resolved, and how many nodes it matched.
+    pub binding_matches: Option<(String, usize)>,
+    pub kind: Option<VisualizationKind>,
+}
+
+/// Parse failures are kept in the document rather than returned, so they can be shown in place.
+/// Only cancelling is an error.
+pub fn parse_document(
+    path: &Path,
+    progress: &LoadProgress,
+) -> Result<StructuredDocument, LoadError> {
+    let format = DocumentFormat::from_path(path);
+    let content = match read_text(path, progress) {
+        Err(LoadError::Cancelled) => return Err(LoadError::Cancelled),
+        content => content,
+    };
+    progress.set_stage("Parsing the document");
+    let root = content
+        .map_err(|e| e.to_string())
+        .and_then(|content| match format {
+            DocumentFormat::Json => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            DocumentFormat::Yaml => serde_norway::from_str(&content).map_err(|e| e.to_string()),
+            DocumentFormat::Toml => toml::from_str(&content).map_err(|e| e.to_string()),
+        });
+    Ok(StructuredDocument { format, root })
+}
+
+/// Parses the dropped file once the user picks the readable flow.
+pub fn parse_document_system(
+    mut commands: Commands,
+    dropped: Res<DroppedFile>,
+    selection: Res<FileTypeSelection>,
+) {
+    if !matches!(*selection, FileTypeSelection::Readable(..)) {
+        return;
+    }
+    let path = dropped.0.clone();
+    let format = DocumentFormat::from_path(&path);
+    commands.insert_resource(LoadingTask::read(
+        move |progress| parse_document(&path, progress).map(Loaded::Document),
+        move |commands| {
+            commands.insert_resource(StructuredDocument {
+                format,
+                root: Err(LoadError::Cancelled.to_string()),
+            })
+        },
+    ));
+}
+
+/// Resolves a JSON pointer in which a `*` segment fans out over every child of an array or
+/// object. Returns the concrete pointer of each match, in document order.
+pub fn resolve_pointer<'a>(root: &'a Value, pattern: &str) -> Vec<(String, &'a Value)> {
+    let mut matches = vec![(String::new(), root)];
+    for segment in pattern.split('/').skip(1) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        matches = matches
+            .into_iter()
+            .flat_map(|(pointer, value)| children(value, &segment, pointer))
+            .collect();
+    }
+    matches
+}
+
+fn children<'a>(value: &'a Value, segment: &str, pointer: String) -> Vec<(String, &'a Value)> {
+    let join = |key: &str| format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"));
+    match (value, segment) {
+        (Value::Array(items), "*") => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (join(&i.to_string()), v))
+            .collect(),
+        (Value::Object(map), "*") => map.iter().map(|(k, v)| (join(k), v)).collect(),
+        (Value::Array(items), index) => index
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| items.get(i))
+            .map(|v| vec![(join(index), v)])
+            .unwrap_or_default(),
+        (Value::Object(map), key) => map
+            .get(key)
+            .map(|v| vec![(join(key), v)])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// Builds a story with one frame per value matched by `pattern`.
+pub fn bind_story(
+    source: &Path,
+    root: &Value,
+    pattern: &str,
+    progress: &LoadProgress,
+) -> Result<Story, LoadError> {
+    let frames = resolve_pointer(root, pattern)
+        .into_iter()
+        .enumerate()
+        .map(|(tick, (pointer, value))| {
+            progress.check()?;
+            frame_state(value)
+                .map(|state| Frame {
+                    tick: tick as u64,
+                    state,
+                })
+                .ok_or(LoadError::Unsupported(pointer))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if frames.is_empty() {
+        return Err(LoadError::Empty(source.to_path_buf()));
+    }
+    Ok(Story::new(source.to_path_buf(), frames))
+}
+
+/// Interprets a value as a board: a multi-line string, an array of strings, or a 2D array.
+/// 2D arrays of numbers become scalar grids, anything else is read cell by cell as text.
+/// 3D arrays of numbers and `{ "shape", "data" }` objects become volumes, see
+/// [`crate::loaders::volume`].
+pub fn frame_state(value: &Value) -> Option<FrameState> {
+    if let Some(volume) = volume_state(value) {
+        return Some(volume);
+    }
+    match value {
+        Value::String(s) => Some(FrameState::Text(TextGrid::from_lines(s.lines()))),
+        Value::Array(rows) if rows.iter().all(Value::is_string) => Some(FrameState::Text(
+            TextGrid::from_lines(rows.iter().filter_map(Value::as_str)),
+        )),
+        Value::Array(rows) if rows.iter().all(Value::is_array) => {
+            let rows: Vec<&Vec<Value>> = rows.iter().filter_map(Value::as_array).collect();
+            if rows.iter().flat_map(|r| r.iter()).all(Value::is_number) {
+                return Some(FrameState::Scalar(ScalarGrid::from_rows(
+                    rows.iter()
+                        .map(|r| {
+                            r.iter()
+                                .filter_map(|v| v.as_f64().map(|f| f as f32))
+                                .collect()
+                        })
+                        .collect(),
+                )));
+            }
+            Some(FrameState::Text(TextGrid {
+                rows: rows
+                    .iter()
+                    .map(|r| r.iter().map(cell_char).collect())
+                    .collect(),
+            }))
+        }
+        _ => None,
+    }
+}
+
+fn volume_state(value: &Value) -> Option<FrameState> {
+    let numbers = |v: &Value| -> Option<Vec<f32>> {
+        v.as_array()?
+            .iter()
+            .map(|n| n.as_f64().map(|f| f as f32))
+            .collect()
+    };
+    if let Value::Object(map) = value {
+        let shape: Vec<usize> = map
+            .get("shape")?
+            .as_array()?
+            .iter()
+            .map(|n| n.as_u64().map(|u| u as usize))
+            .collect::<Option<_>>()?;
+        let values = numbers(map.get("data")?)?;
+        return match shape[..] {
+            [height, width] if values.len() == width * height => {
+                Some(FrameState::Scalar(ScalarGrid {
+                    width,
+                    height,
+                    values,
+                }))
+            }
+            [depth, height, width] if values.len() == width * height * depth => {
+                Some(FrameState::Volume(ScalarVolume {
+                    width,
+                    height,
+                    depth,
+                    values,
+                }))
+            }
+            _ => None,
+        };
+    }
+
+    let slices: Vec<Vec<Vec<f32>>> = value
+        .as_array()?
+        .iter()
+        .map(|slice| slice.as_array()?.iter().map(numbers).collect())
+        .collect::<Option<_>>()?;
+    let depth = slices.len();
+    let height = slices.first()?.len();
+    let width = slices.first()?.first()?.len();
+    if slices
+        .iter()
+        .any(|s| s.len() != height || s.iter().any(|row| row.len() != width))
+    {
+        return None;
+    }
+    Some(FrameState::Volume(ScalarVolume {
+        width,
+        height,
+        depth,
+        values: slices.into_iter().flatten().flatten().collect(),
+    }))
+}
+
+fn cell_char(value: &Value) -> char {
+    match value {
+        Value::Null => ' ',
+        Value::Bool(true) => '#',
+        Value::Bool(false) => '.',
+        Value::String(s) => s.chars().next().unwrap_or(' '),
+        Value::Number(n) => n.to_string().chars().next().unwrap_or('?'),
+        Value::Array(_) | Value::Object(_) => '?',
+    }
+}
@@ -0,0 +1,470 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    FileTypeSelection,
+    error::{ErrorStage, VisualizationError, report_error},
+    loaders::LoadError,
+    loading::LoadProgress,
+    story::{Frame, FrameState, ScalarGrid, Story, TextGrid},
+    visualization::{DroppedFile, VisualizationKind},
+};
+
+/// How much of the file is read to detect the delimiter, header and columns.
+const PREVIEW_BYTES: u64 = 64 * 1024;
+const PREVIEW_ROWS: usize = 8;
+/// Largest grid a long-format table may describe, coordinates beyond it are a typo or garbage.
+const MAX_LONG_CELLS: usize = 16 * 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TableLayout {
+    /// One numeric matrix per frame, frames separated by blank lines.
+    #[default]
+    Matrix,
+    /// One row per tick, every unmapped column being a value.
+    Wide,
+    /// One row per cell and tick, e.g. `tick,x,y,value`.
+    Long,
+}
+
+impl TableLayout {
+    pub const ALL: [TableLayout; 3] = [TableLayout::Matrix, TableLayout::Wide, TableLayout::Long];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TableLayout::Matrix => "Matrix per frame",
+            TableLayout::Wide => "One row per tick",
+            TableLayout::Long => "One row per cell (long)",
+        }
+    }
+}
+
+/// Column index for each role, `None` when the role is not present in the file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ColumnMapping {
+    pub tick: Option<usize>,
+    pub x: Option<usize>,
+    pub y: Option<usize>,
+    pub value: Option<usize>,
+    pub category: Option<usize>,
+}
+
+impl ColumnMapping {
+    /// Guesses roles from common header names.
+    pub fn guess(headers: &[String]) -> Self {
+        let find = |names: &[&str]| {
+            headers
+                .iter()
+                .position(|h| names.contains(&h.trim().to_lowercase().as_str()))
+        };
+        Self {
+            tick: find(&["tick", "t", "time", "step", "frame", "iteration"]),
+            x: find(&["x", "col", "column", "i"]),
+            y: find(&["y", "row", "j"]),
+            value: find(&["value", "val", "v", "z"]),
+            category: find(&["category", "cat", "label", "state", "kind"]),
+        }
+    }
+
+    fn roles(&self) -> [Option<usize>; 5] {
+        [self.tick, self.x, self.y, self.value, self.category]
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TableConfiguration {
+    pub kind: Option<VisualizationKind>,
+    pub layout: TableLayout,
+    pub mapping: ColumnMapping,
+}
+
+/// What was detected from the start of the file, shown in the column mapping step.
+#[derive(Resource, Debug, Clone)]
+pub struct TablePreview {
+    pub delimiter: Option<char>,
+    pub has_header: bool,
+    /// Header names, or `column N` when the file has no header.
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Tabs win over commas, commas over semicolons; whitespace is the fallback.
+pub fn detect_delimiter(sample: &str) -> Option<char> {
+    ['\t', ',', ';']
+        .into_iter()
+        .find(|d| sample.lines().take(16).any(|line| line.contains(*d)))
+}
+
+/// A header is a first row holding text where the following row holds numbers.
+pub fn detect_header(first: &[String], second: Option<&[String]>) -> bool {
+    let numeric = |cell: &String| cell.trim().parse::<f64>().is_ok();
+    let Some(second) = second else {
+        return !first.iter().any(numeric);
+    };
+    first
+        .iter()
+        .zip(second)
+        .any(|(a, b)| !numeric(a) && numeric(b))
+}
+
+/// Splits a record on `delimiter`, honouring double-quoted fields. `None` splits on whitespace.
+pub fn split_record(line: &str, delimiter: Option<char>) -> Vec<String> {
+    let Some(delimiter) = delimiter else {
+        return line.split_whitespace().map(str::to_string).collect();
+    };
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields.iter_mut().for_each(|f| *f = f.trim().to_string());
+    fields
+}
+
+pub fn preview_table(path: &Path) -> Result<TablePreview, LoadError> {
+    let mut sample = String::new();
+    File::open(path)
+        .and_then(|file| file.take(PREVIEW_BYTES).read_to_string(&mut sample))
+        .map_err(|e| LoadError::Io(path.to_path_buf(), e))?;
+    let delimiter = detect_delimiter(&sample);
+    let records: Vec<Vec<String>> = sample
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(PREVIEW_ROWS + 1)
+        .map(|line| split_record(line, delimiter))
+        .collect();
+    let Some(first) = records.first() else {
+        return Err(LoadError::Empty(path.to_path_buf()));
+    };
+    let has_header = detect_header(first, records.get(1).map(Vec::as_slice));
+    let columns = records.iter().map(Vec::len).max().unwrap_or(0);
+    let headers = if has_header {
+        first.clone()
+    } else {
+        (0..columns).map(|i| format!("column {i}")).collect()
+    };
+    let skip = usize::from(has_header);
+    Ok(TablePreview {
+        delimiter,
+        has_header,
+        headers,
+        rows: records.into_iter().skip(skip).take(PREVIEW_ROWS).collect(),
+    })
+}
+
+/// Detects the table shape once the user picks the table flow, and pre-fills the mapping.
+pub fn preview_table_system(
+    mut commands: Commands,
+    dropped: Res<DroppedFile>,
+    mut selection: ResMut<FileTypeSelection>,
+) {
+    let FileTypeSelection::Table(_, cfg) = &mut *selection else {
+        return;
+    };
+    match preview_table(&dropped.0) {
+        Ok(preview) => {
+            cfg.mapping = ColumnMapping::guess(&preview.headers);
+            cfg.layout = match (&cfg.mapping.x, &cfg.mapping.y, &cfg.mapping.tick) {
+                (Some(_), Some(_), _) => TableLayout::Long,
+                (_, _, Some(_)) => TableLayout::Wide,
+                _ => TableLayout::Matrix,
+            };
+            commands.insert_resource(preview);
+        }
+        Err(err) => {
+            report_error(
+                &mut commands,
+                VisualizationError::from_load(ErrorStage::Detect, &err),
+            );
+            commands.insert_resource(TablePreview {
+                delimiter: None,
+                has_header: false,
+                headers: Vec::new(),
+                rows: Vec::new(),
+            });
+        }
+    }
+}
+
+/// Parses the whole table into a story, reporting bytes read to `progress`. Meant to run on a
+/// background task.
+pub fn load_table_story(
+    path: &Path,
+    cfg: &TableConfiguration,
+    preview: &TablePreview,
+    progress: &LoadProgress,
+) -> Result<Story, LoadError> {
+    let io_err = |e| LoadError::Io(path.to_path_buf(), e);
+    let file = File::open(path).map_err(io_err)?;
+    progress.set_total(file.metadata().map_err(io_err)?.len());
+    let mut reader = BufReader::new(file);
+    progress.set_stage("Reading rows");
+
+    let mut builder = TableBuilder::new(cfg);
+    let mut line = String::new();
+    let mut header_pending = preview.has_header;
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).map_err(io_err)?;
+        if read == 0 {
+            break;
+        }
+        line_number += 1;
+        progress.advance(read as u64);
+        if progress.is_cancelled() {
+            return Err(LoadError::Cancelled);
+        }
+        let trimmed = line.trim_end_matches(['\r', '\n']);
+        if trimmed.trim().is_empty() {
+            builder.blank_line();
+            continue;
+        }
+        if std::mem::take(&mut header_pending) {
+            continue;
+        }
+        builder
+            .record(split_record(trimmed, preview.delimiter))
+            .map_err(|why| LoadError::Line(path.to_path_buf(), line_number, why))?;
+    }
+
+    progress.set_stage("Building frames");
+    let frames = builder
+        .finish()
+        .map_err(|why| LoadError::Invalid(format!("{}: {why}", path.display())))?;
+    if frames.is_empty() {
+        return Err(LoadError::Empty(path.to_path_buf()));
+    }
+    Ok(Story::new(path.to_path_buf(), frames))
+}
+
+/// Cells of a long-format tick: position to value or category.
+type LongCells = Vec<(usize, usize, Option<f32>, Option<char>)>;
+
+struct TableBuilder<'a> {
+    cfg: &'a TableConfiguration,
+    frames: Vec<Frame>,
+    rows: Vec<Vec<f32>>,
+    long: BTreeMap<u64, LongCells>,
+}
+
+impl<'a> TableBuilder<'a> {
+    fn new(cfg: &'a TableConfiguration) -> Self {
+        Self {
+            cfg,
+            frames: Vec::new(),
+            rows: Vec::new(),
+            long: BTreeMap::new(),
+        }
+    }
+
+    fn blank_line(&mut self) {
+        if self.cfg.layout == TableLayout::Matrix {
+            self.push_matrix();
+        }
+    }
+
+    /// Matrix blocks may each start with their own header, a row without any number.
+    fn record(&mut self, record: Vec<String>) -> Result<(), String> {
+        let number = |i: Option<usize>| -> Option<f32> { record.get(i?)?.parse().ok() };
+        match self.cfg.layout {
+            TableLayout::Matrix => {
+                let row: Vec<f32> = record
+                    .iter()
+                    .map(|cell| cell.parse().unwrap_or(f32::NAN))
+                    .collect();
+                if !(self.rows.is_empty() && row.iter().all(|v| v.is_nan())) {
+                    self.rows.push(row);
+                }
+            }
+            TableLayout::Wide => {
+                let mapped = self.cfg.mapping.roles();
+                let values = record
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !mapped.contains(&Some(*i)))
+                    .map(|(_, cell)| cell.parse().unwrap_or(f32::NAN))
+                    .collect();
+                let tick =
+                    number(self.cfg.mapping.tick).map_or(self.frames.len() as u64, |t| t as u64);
+                self.frames.push(Frame {
+                    tick,
+                    state: FrameState::Scalar(ScalarGrid::from_rows(vec![values])),
+                });
+            }
+            TableLayout::Long => {
+                let (Some(x), Some(y)) = (number(self.cfg.mapping.x), number(self.cfg.mapping.y))
+                else {
+                    return Ok(());
+                };
+                let coordinate = |v: f32, axis: &str| {
+                    if v.is_finite() && v >= 0.0 && v < MAX_LONG_CELLS as f32 {
+                        Ok(v as usize)
+                    } else {
+                        Err(format!("{axis} coordinate {v} is out of range"))
+                    }
+                };
+                let (x, y) = (coordinate(x, "x")?, coordinate(y, "y")?);
+                let tick = number(self.cfg.mapping.tick).map_or(0, |t| t as u64);
+                let category = self
+                    .cfg
+                    .mapping
+                    .category
+                    .and_then(|i| record.get(i)?.chars().next());
+                self.long.entry(tick).or_default().push((
+                    x,
+                    y,
+                    number(self.cfg.mapping.value),
+                    category,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn push_matrix(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
+        self.frames.push(Frame {
+            tick: self.frames.len() as u64,
+            state: FrameState::Scalar(ScalarGrid::from_rows(std::mem::take(&mut self.rows))),
+        });
+    }
+
+    /// Long-format cells that are missing for a tick are left empty. Ticks with a mapped value
+    /// become scalar grids, otherwise the category's first letter is shown as text.
+    fn finish(mut self) -> Result<Vec<Frame>, String> {
+        self.push_matrix();
+        let cells = self.long.values().flatten();
+        let width = cells.clone().map(|c| c.0 + 1).max().unwrap_or(0);
+        let height = cells.map(|c| c.1 + 1).max().unwrap_or(0);
+        if width
+            .checked_mul(height)
+            .is_none_or(|count| count > MAX_LONG_CELLS)
+        {
+            return Err(format!(
+                "a {width}×{height} grid is larger than {MAX_LONG_CELLS} cells"
+            ));
+        }
+        let use_values = self.cfg.mapping.value.is_some();
+
+        for (tick, cells) in std::mem::take(&mut self.long) {
+            let state = if use_values {
+                let mut values = vec![f32::NAN; width * height];
+                for (x, y, value, _) in cells {
+                    values[y * width + x] = value.unwrap_or(f32::NAN);
+                }
+                FrameState::Scalar(ScalarGrid {
+                    width,
+                    height,
+                    values,
+                })
+            } else {
+                let mut rows = vec![vec![' '; width]; height];
+                for (x, y, _, category) in cells {
+                    rows[y][x] = category.unwrap_or('#');
+                }
+                FrameState::Text(TextGrid { rows })
+            };
+            self.frames.push(Frame { tick, state });
+        }
+        Ok(self.frames)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story::CellValue;
+
+    fn long() -> TableConfiguration {
+        TableConfiguration {
+            kind: None,
+            layout: TableLayout::Long,
+            mapping: ColumnMapping {
+                tick: Some(0),
+                x: Some(1),
+                y: Some(2),
+                value: Some(3),
+                category: None,
+            },
+        }
+    }
+
+    fn record(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|cell| cell.to_string()).collect()
+    }
+
+    #[test]
+    fn long_cells_fill_a_grid() {
+        let cfg = long();
+        let mut builder = TableBuilder::new(&cfg);
+        builder.record(record(&["0", "1", "0", "5"])).unwrap();
+        builder.record(record(&["1", "0", "1", "7"])).unwrap();
+        let frames = builder.finish().unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].state.dimensions(), (2, 2));
+        assert_eq!(frames[1].state.cell(0, 1), CellValue::Number(7.0));
+    }
+
+    #[test]
+    fn rejects_out_of_range_coordinates() {
+        let cfg = long();
+        for (x, y, expected) in [
+            ("-1", "0", "x coordinate -1 is out of range"),
+            ("0", "inf", "y coordinate inf is out of range"),
+            ("NaN", "0", "x coordinate NaN is out of range"),
+            ("1e9", "0", "x coordinate 1000000000 is out of range"),
+        ] {
+            let mut builder = TableBuilder::new(&cfg);
+            assert_eq!(
+                builder.record(record(&["0", x, y, "1"])),
+                Err(expected.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_grids_past_the_cell_limit() {
+        let cfg = long();
+        let mut builder = TableBuilder::new(&cfg);
+        let far = (MAX_LONG_CELLS - 1).to_string();
+        builder.record(record(&["0", &far, &far, "1"])).unwrap();
+        let Err(why) = builder.finish() else {
+            panic!("a grid past the limit was built");
+        };
+        assert!(why.ends_with(&format!("larger than {MAX_LONG_CELLS} cells")));
+    }
+
+    #[test]
+    fn matrix_blocks_skip_their_header() {
+        let cfg = TableConfiguration::default();
+        let mut builder = TableBuilder::new(&cfg);
+        builder.record(record(&["a", "b"])).unwrap();
+        builder.record(record(&["1", "2"])).unwrap();
+        builder.blank_line();
+        builder.record(record(&["3", "x"])).unwrap();
+        let frames = builder.finish().unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].state.dimensions(), (2, 1));
+        assert_eq!(frames[1].state.cell(1, 0), CellValue::Empty);
+    }
+}
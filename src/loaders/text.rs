@@ -0,0 +1,144 @@
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::{
+    loaders::LoadError,
+    loading::{LoadProgress, read_text},
+    story::{Frame, FrameState, Story, TextGrid},
+    visualization::VisualizationKind,
+};
+
+/// Blank lines, or lines such as `--- tick 12 ---` whose first capture group is the tick.
+pub const DEFAULT_SEPARATOR: &str = r"^\s*$|^\s*-+\s*tick\s+(\d+)\s*-+\s*$";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextConfiguration {
+    /// Regex matched against each line. Matching lines split frames and are not part of them.
+    pub separator: String,
+    /// Renderer picked by the user, `None` to let the registry choose.
+    pub kind: Option<VisualizationKind>,
+    compiled: Option<CompiledSeparator>,
+}
+
+/// A separator regex, or why it does not compile, along with the pattern it came from.
+#[derive(Clone, Debug)]
+struct CompiledSeparator {
+    pattern: String,
+    regex: Result<Regex, String>,
+}
+
+impl CompiledSeparator {
+    fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            regex: Regex::new(pattern).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn regex(&self) -> Result<&Regex, LoadError> {
+        self.regex
+            .as_ref()
+            .map_err(|e| LoadError::InvalidPattern(e.clone()))
+    }
+}
+
+impl PartialEq for CompiledSeparator {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Eq for CompiledSeparator {}
+
+impl Default for TextConfiguration {
+    fn default() -> Self {
+        Self {
+            separator: DEFAULT_SEPARATOR.to_string(),
+            kind: None,
+            compiled: None,
+        }
+    }
+}
+
+impl TextConfiguration {
+    /// The separator regex, compiled again only when the pattern text changed.
+    pub fn separator_regex(&mut self) -> Result<&Regex, LoadError> {
+        if self
+            .compiled
+            .as_ref()
+            .is_none_or(|compiled| compiled.pattern != self.separator)
+        {
+            self.compiled = None;
+        }
+        self.compiled
+            .get_or_insert_with(|| CompiledSeparator::new(&self.separator))
+            .regex()
+    }
+
+    /// Like [`Self::separator_regex`], for a configuration that cannot keep the result.
+    pub fn compile(&self) -> Result<Regex, LoadError> {
+        match &self.compiled {
+            Some(compiled) if compiled.pattern == self.separator => compiled.regex().cloned(),
+            _ => CompiledSeparator::new(&self.separator).regex().cloned(),
+        }
+    }
+}
+
+pub fn load_text_story(
+    path: &Path,
+    cfg: &TextConfiguration,
+    progress: &LoadProgress,
+) -> Result<Story, LoadError> {
+    let separator = cfg.compile()?;
+    progress.set_stage("Reading the file");
+    let content = read_text(path, progress)?;
+    progress.set_stage("Splitting frames");
+    let frames = split_frames(&content, &separator, progress)?;
+    if frames.is_empty() {
+        return Err(LoadError::Empty(path.to_path_buf()));
+    }
+    Ok(Story::new(path.to_path_buf(), frames))
+}
+
+/// Splits `content` into frames on every line matching `separator`.
+///
+/// A separator's first capture group, when present and numeric, sets the tick of the frame that
+/// follows it. Frames without an explicit tick continue from the previous one.
+pub fn split_frames(
+    content: &str,
+    separator: &Regex,
+    progress: &LoadProgress,
+) -> Result<Vec<Frame>, LoadError> {
+    let mut frames = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+    let mut pending_tick = None;
+
+    for line in content.lines() {
+        let Some(captures) = separator.captures(line) else {
+            lines.push(line);
+            continue;
+        };
+        if !lines.is_empty() {
+            progress.check()?;
+            push_frame(&mut frames, &mut lines, pending_tick.take());
+        }
+        if let Some(tick) = captures.get(1).and_then(|m| m.as_str().parse().ok()) {
+            pending_tick = Some(tick);
+        }
+    }
+    push_frame(&mut frames, &mut lines, pending_tick);
+
+    Ok(frames)
+}
+
+fn push_frame(frames: &mut Vec<Frame>, lines: &mut Vec<&str>, tick: Option<u64>) {
+    if lines.is_empty() {
+        return;
+    }
+    let tick = tick.unwrap_or_else(|| frames.last().map_or(0, |f: &Frame| f.tick + 1));
+    frames.push(Frame {
+        tick,
+        state: FrameState::Text(TextGrid::from_lines(lines.drain(..))),
+    });
+}
@@ -0,0 +1,154 @@
+//! 3D scalar fields.
+//!
+//! Raw volumes are recognised by their magic, whatever their extension, and laid out little-endian
+//! as:
+//!
+//! | offset | type       | content                                       |
+//! |--------|------------|-----------------------------------------------|
+//! | 0      | `[u8; 4]`  | magic `SVOL`                                  |
+//! | 4      | `u32`      | width (x)                                     |
+//! | 8      | `u32`      | height (y, up)                                |
+//! | 12     | `u32`      | depth (z)                                     |
+//! | 16     | `u32`      | frame count                                   |
+//! | 20     | `f32`...   | values, x varying fastest, then y, z, frame   |
+//!
+//! JSON volumes go through the readable flow: bind either nested `[z][y][x]` arrays, or an
+//! object `{ "shape": [depth, height, width], "data": [...] }` with the same flat ordering.
+
+use std::path::Path;
+
+use crate::{
+    loaders::LoadError,
+    loading::{LoadProgress, read_file},
+    story::{Frame, FrameState, ScalarVolume, Story},
+};
+
+pub const RAW_VOLUME_MAGIC: &[u8; 4] = b"SVOL";
+const HEADER_LEN: usize = 20;
+
+pub fn load_raw_volume_story(path: &Path, progress: &LoadProgress) -> Result<Story, LoadError> {
+    progress.set_stage("Reading voxels");
+    let bytes = read_file(path, progress)?;
+    let frames = raw_volume_frames(path, &bytes, progress)?;
+    if frames.is_empty() {
+        return Err(LoadError::Empty(path.to_path_buf()));
+    }
+    Ok(Story::new(path.to_path_buf(), frames))
+}
+
+/// One frame per volume of the raw `bytes`, `path` only naming the file in errors.
+fn raw_volume_frames(
+    path: &Path,
+    bytes: &[u8],
+    progress: &LoadProgress,
+) -> Result<Vec<Frame>, LoadError> {
+    let invalid = |why: &str| LoadError::Invalid(format!("{}: {why}", path.display()));
+
+    if bytes.len() < HEADER_LEN || &bytes[..4] != RAW_VOLUME_MAGIC {
+        return Err(invalid("missing SVOL header"));
+    }
+    let header = |i: usize| {
+        let offset = 4 + i * 4;
+        u32::from_le_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ]) as usize
+    };
+    let (width, height, depth, frame_count) = (header(0), header(1), header(2), header(3));
+    let sizes = width
+        .checked_mul(height)
+        .and_then(|area| area.checked_mul(depth))
+        .and_then(|voxels| Some((voxels, voxels.checked_mul(frame_count)?.checked_mul(4)?)));
+    let Some((voxels, byte_len)) = sizes else {
+        return Err(invalid("header sizes overflow"));
+    };
+    if bytes.len() - HEADER_LEN < byte_len {
+        return Err(invalid("file is shorter than its header claims"));
+    }
+
+    progress.set_stage("Building frames");
+    bytes[HEADER_LEN..HEADER_LEN + byte_len]
+        .chunks_exact(voxels.max(1).saturating_mul(4))
+        .enumerate()
+        .map(|(tick, chunk)| {
+            progress.check()?;
+            let values = chunk
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            Ok(Frame {
+                tick: tick as u64,
+                state: FrameState::Volume(ScalarVolume {
+                    width,
+                    height,
+                    depth,
+                    values,
+                }),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn svol(sizes: [u32; 4], values: &[f32]) -> Vec<u8> {
+        let mut bytes = RAW_VOLUME_MAGIC.to_vec();
+        for size in sizes {
+            bytes.extend(size.to_le_bytes());
+        }
+        for value in values {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn frames(bytes: &[u8]) -> Result<Vec<Frame>, LoadError> {
+        raw_volume_frames(Path::new("v.svol"), bytes, &LoadProgress::default())
+    }
+
+    fn assert_invalid(result: Result<Vec<Frame>, LoadError>, expected: &str) {
+        match result {
+            Err(LoadError::Invalid(why)) => assert_eq!(why, format!("v.svol: {expected}")),
+            other => panic!("expected '{expected}', got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn reads_every_frame() {
+        let frames = frames(&svol([2, 1, 1, 2], &[1.0, 2.0, 3.0, 4.0])).unwrap();
+        assert_eq!(frames.len(), 2);
+        let FrameState::Volume(volume) = &frames[1].state else {
+            panic!("not a volume");
+        };
+        assert_eq!((volume.width, volume.height, volume.depth), (2, 1, 1));
+        assert_eq!(volume.values, [3.0, 4.0]);
+    }
+
+    #[test]
+    fn rejects_a_missing_header() {
+        assert_invalid(frames(b"SVOL\x01\0\0\0"), "missing SVOL header");
+        assert_invalid(frames(&[0; 24]), "missing SVOL header");
+    }
+
+    #[test]
+    fn rejects_overflowing_sizes() {
+        assert_invalid(frames(&svol([u32::MAX; 4], &[])), "header sizes overflow");
+        // The voxel count fits, the byte length of every frame does not.
+        assert_invalid(
+            frames(&svol([u32::MAX, u32::MAX, 1, 2], &[])),
+            "header sizes overflow",
+        );
+    }
+
+    #[test]
+    fn rejects_missing_values() {
+        assert_invalid(
+            frames(&svol([2, 2, 1, 1], &[1.0, 2.0])),
+            "file is shorter than its header claims",
+        );
+    }
+}
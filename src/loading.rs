@@ -0,0 +1,224 @@
+use std::{
+    fs::File,
+    io::Read,
+    path::Path,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, futures::check_ready},
+};
+
+use crate::{
+    error::{ErrorStage, VisualizationError, report_error},
+    loaders::{LoadError, numpy::NumpyFile, structured::StructuredDocument},
+    story::Story,
+    visualization::{DroppedFile, VisualizationKind, start_story},
+};
+
+type LoadFn = Arc<dyn Fn(&LoadProgress) -> Result<Loaded, LoadError> + Send + Sync>;
+type CancelFn = Arc<dyn Fn(&mut Commands) + Send + Sync>;
+
+/// Files are read in chunks of this size, so progress moves and cancelling is noticed.
+const READ_CHUNK: usize = 1024 * 1024;
+
+/// What a loading task produces: a story to render, or a parsed file for the selection menu.
+pub enum Loaded {
+    Story(Story),
+    Numpy(NumpyFile),
+    Document(StructuredDocument),
+}
+
+/// Shared between a loading task and the UI. Loaders report bytes and the current stage as they
+/// go, and check for cancellation between chunks of work.
+#[derive(Debug, Default)]
+pub struct LoadProgress {
+    done: AtomicU64,
+    total: AtomicU64,
+    cancelled: AtomicBool,
+    stage: Mutex<String>,
+}
+
+impl LoadProgress {
+    pub fn set_stage(&self, stage: impl Into<String>) {
+        if let Ok(mut current) = self.stage.lock() {
+            *current = stage.into();
+        }
+    }
+
+    pub fn stage(&self) -> String {
+        self.stage.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    pub fn set_total(&self, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, bytes: u64) {
+        self.done.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Fraction in `[0, 1]`, or `None` while the total is unknown.
+    pub fn fraction(&self) -> Option<f32> {
+        let total = self.total.load(Ordering::Relaxed);
+        (total > 0).then(|| self.done.load(Ordering::Relaxed) as f32 / total as f32)
+    }
+
+    pub fn bytes_done(&self) -> u64 {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// `Err(LoadError::Cancelled)` once cancelled, for loaders to bail out with `?`.
+    pub fn check(&self) -> Result<(), LoadError> {
+        if self.is_cancelled() {
+            return Err(LoadError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+/// Reads the whole file at `path`, reporting its size as the total.
+pub fn read_file(path: &Path, progress: &LoadProgress) -> Result<Vec<u8>, LoadError> {
+    let io_err = |e| LoadError::Io(path.to_path_buf(), e);
+    let mut file = File::open(path).map_err(io_err)?;
+    let len = file.metadata().map_err(io_err)?.len();
+    progress.set_total(len);
+    let mut bytes = Vec::with_capacity(len as usize);
+    let mut chunk = vec![0; READ_CHUNK];
+    loop {
+        let read = file.read(&mut chunk).map_err(io_err)?;
+        if read == 0 {
+            return Ok(bytes);
+        }
+        bytes.extend_from_slice(&chunk[..read]);
+        progress.advance(read as u64);
+        progress.check()?;
+    }
+}
+
+/// [`read_file`] for text files.
+pub fn read_text(path: &Path, progress: &LoadProgress) -> Result<String, LoadError> {
+    String::from_utf8(read_file(path, progress)?).map_err(|e| {
+        LoadError::Io(
+            path.to_path_buf(),
+            std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+        )
+    })
+}
+
+/// A story being parsed on the async compute pool. Started with [`LoadingTask::spawn`] and
+/// handed to the renderer by [`poll_loading_task`] once done. Files the selection menu needs
+/// parsed before it can offer options are read the same way, with [`LoadingTask::read`].
+#[derive(Resource)]
+pub struct LoadingTask {
+    task: Task<Result<Loaded, LoadError>>,
+    pub progress: Arc<LoadProgress>,
+    kind: Option<VisualizationKind>,
+    load: LoadFn,
+    on_cancel: CancelFn,
+}
+
+impl LoadingTask {
+    pub fn spawn(
+        kind: Option<VisualizationKind>,
+        load: impl Fn(&LoadProgress) -> Result<Story, LoadError> + Send + Sync + 'static,
+    ) -> Self {
+        let load = Arc::new(move |p: &LoadProgress| load(p).map(Loaded::Story));
+        Self::start(kind, load, Arc::new(keep_selection))
+    }
+
+    /// Reads a file for the selection menu. `on_cancel` stands in for the result when the user
+    /// cancels, so the menu does not start reading again.
+    pub fn read(
+        load: impl Fn(&LoadProgress) -> Result<Loaded, LoadError> + Send + Sync + 'static,
+        on_cancel: impl Fn(&mut Commands) + Send + Sync + 'static,
+    ) -> Self {
+        Self::start(None, Arc::new(load), Arc::new(on_cancel))
+    }
+
+    fn start(kind: Option<VisualizationKind>, load: LoadFn, on_cancel: CancelFn) -> Self {
+        let progress = Arc::new(LoadProgress::default());
+        let (task_progress, task_load) = (progress.clone(), load.clone());
+        let task = AsyncComputeTaskPool::get().spawn(async move { task_load(&task_progress) });
+        Self {
+            task,
+            progress,
+            kind,
+            load,
+            on_cancel,
+        }
+    }
+
+    /// Stops the task and drops it, leaving the selection and its configuration as they were.
+    pub fn cancel(&self, commands: &mut Commands) {
+        self.progress.cancel();
+        commands.remove_resource::<LoadingTask>();
+        (self.on_cancel)(commands);
+        info!("Loading cancelled");
+    }
+}
+
+/// Stories need nothing in place of a cancelled load, the selection menu is shown as it was.
+fn keep_selection(_: &mut Commands) {}
+
+/// The last load that failed, kept so the error screen can run it again.
+#[derive(Resource)]
+pub struct FailedLoad {
+    kind: Option<VisualizationKind>,
+    load: LoadFn,
+    on_cancel: CancelFn,
+}
+
+impl FailedLoad {
+    pub fn retry(&self) -> LoadingTask {
+        LoadingTask::start(self.kind, self.load.clone(), self.on_cancel.clone())
+    }
+}
+
+pub fn poll_loading_task(
+    mut commands: Commands,
+    mut loading: ResMut<LoadingTask>,
+    dropped: Option<Res<DroppedFile>>,
+) {
+    let Some(result) = check_ready(&mut loading.task) else {
+        return;
+    };
+    commands.remove_resource::<LoadingTask>();
+    match result {
+        Ok(loaded) => {
+            commands.remove_resource::<FailedLoad>();
+            match loaded {
+                Loaded::Story(story) => start_story(&mut commands, story, loading.kind),
+                Loaded::Numpy(file) => commands.insert_resource(file),
+                Loaded::Document(document) => commands.insert_resource(document),
+            }
+        }
+        Err(LoadError::Cancelled) => info!("Loading cancelled"),
+        Err(err) => {
+            let mut error = VisualizationError::from_load(ErrorStage::Parse, &err);
+            if let Some(dropped) = dropped
+                && error.source.is_none()
+            {
+                error = error.with_source(&dropped.0);
+            }
+            commands.insert_resource(FailedLoad {
+                kind: loading.kind,
+                load: loading.load.clone(),
+                on_cancel: loading.on_cancel.clone(),
+            });
+            report_error(&mut commands, error);
+        }
+    }
+}
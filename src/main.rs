@@ -1,6 +1,6 @@
-use bevy::camera::Viewport;
+use std::path::Path;
+
 use bevy::camera::visibility::RenderLayers;
-use bevy::input::mouse::{MouseMotion, MouseWheel};
 use egui_taffy::taffy::prelude::{AlignItems, FlexDirection, JustifyContent};
 use egui_taffy::taffy::{self, prelude::*};
 // use bevy::hierarchy::DespawnRecursiveExt;
@@ -11,25 +11,91 @@ use bevy_egui::egui::{Align, Color32, ComboBox, Layout, RichText};
 // use bevy::render::camera::Camera3d;
 // use bevy::render::view::{ComputedVisibility, Visibility};
 use bevy_egui::{
-    EguiContext, EguiContexts, EguiGlobalSettings, EguiPlugin, EguiPrimaryContextPass,
-    PrimaryEguiContext, egui,
+    EguiContexts, EguiGlobalSettings, EguiPlugin, EguiPrimaryContextPass, PrimaryEguiContext, egui,
 };
+use storyframe::core::configuration::Configuration;
+mod breakpoints;
+mod camera;
+mod clock;
 mod config;
+mod error;
 mod file_id;
+mod history;
+mod loaders;
+mod loading;
+mod renderers;
+mod search;
+mod session;
+mod story;
 mod ui;
 mod viewports;
 mod visualization;
 use egui_taffy::{Tui, TuiBuilderLogic, TuiBuilderParams, tui};
 use visualization::{
-    Engine, LoadVisualization, TaggedEntity, VisualizerState, file_drop, load_visualization_system,
-    unload_visualization_system,
+    CloseVisualization, Engine, LoadVisualization, SimpleGrid, TaggedEntity, VisualizationSettings,
+    VisualizerState, close_visualization_system, configure_visualization_system, file_drop,
+    load_visualization_system, unload_visualization_system,
 };
 
+use crate::breakpoints::Breakpoints;
+use crate::camera::{
+    CameraLimits, CameraMode, CameraTransition, MoveCamera, OrbitCamera, SetCameraMode,
+    apply_camera_system, camera_controls, camera_follow_system, camera_limits_system,
+    camera_mode_system, camera_transition_system, frame_bounds_system, move_camera_system,
+};
+use crate::clock::{StoryClock, story_clock_system, story_tick_system};
+use crate::config::{AnimationConfig, HistoryConfig};
+use crate::error::{ReportError, in_error_state, report_error_system};
 use crate::file_id::FileTypeSuggestion;
+use crate::history::configure_history_system;
+use crate::loaders::binary::{
+    BinaryFile, HexConfiguration, PatternSearch, map_binary_system, poll_pattern_search,
+};
+use crate::loaders::graph::GraphConfiguration;
+use crate::loaders::numpy::{NumpyConfiguration, NumpyFile, parse_numpy_system};
+use crate::loaders::structured::{
+    ReadableConfiguration, StructuredDocument, parse_document_system,
+};
+use crate::loaders::table::{TableConfiguration, TablePreview, preview_table_system};
+use crate::loaders::text::TextConfiguration;
+use crate::loading::{LoadingTask, poll_loading_task};
+use crate::renderers::array::{ArrayScale, ArrayVis, array_render_system, setup_array};
+use crate::renderers::graph::{
+    GraphScene, GraphSettings, GraphVis, graph_render_system, setup_graph,
+};
+use crate::renderers::grid::{GridVis, spawn_story_grid, story_grid_system};
+use crate::renderers::heatmap::{
+    HeatmapSettings, HeatmapVis, heatmap_hover_system, heatmap_render_system, setup_heatmap,
+};
+use crate::renderers::registry::{
+    RendererCandidates, RendererRegistry, SelectRenderer, select_renderer_system,
+};
+use crate::renderers::tween::{Tween, tween_system};
+use crate::renderers::volume::{VolumeSettings, VolumeVis, setup_volume, volume_render_system};
+use crate::search::{SearchTask, poll_search_task};
+use crate::session::{Session, load_session_system, save_session_system};
+use crate::story::{Playhead, Story};
+use crate::ui::array::ui_array_hud;
+use crate::ui::breakpoints::ui_breakpoints_panel;
+use crate::ui::camera::{camera_shortcut_system, ui_camera_hud, ui_camera_panel};
 use crate::ui::components::{padded_button, separator};
+use crate::ui::error::ui_error_screen;
+use crate::ui::graph::ui_graph_panel;
+use crate::ui::heatmap::ui_heatmap_panel;
+use crate::ui::history::{history_overlay_enabled, ui_history_overlay};
+use crate::ui::loading::ui_loading_overlay;
+use crate::ui::markers::ui_markers_panel;
+use crate::ui::playback::ui_playback_panel;
+use crate::ui::renderer::ui_renderer_switch;
+use crate::ui::search::ui_search_panel;
 use crate::ui::selection::ui_selection_menu;
-use crate::viewports::{UiSize, ViewportChanged, ViewportId, Viewports};
-use crate::visualization::{DroppedFile, HoveredFile};
+use crate::ui::transport::{transport_shortcut_system, ui_transport};
+use crate::ui::volume::ui_volume_panel;
+use crate::viewports::{
+    UiSize, ViewportChanged, ViewportId, Viewports, apply_viewports_system, viewport_event_system,
+    viewports_need_layout,
+};
+use crate::visualization::{DroppedFile, FrameBounds, HoveredFile, in_scene_state};
 
 #[derive(States, Default, Debug, Clone, Eq, PartialEq, Hash)]
 enum UiStatus {
@@ -44,8 +110,6 @@ enum VisualizationSystemSet {
     Load,
 }
 
-#[derive(Resource)]
-struct TickTimer(Timer);
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
@@ -60,9 +124,43 @@ fn main() {
         .init_state::<UiStatus>()
         .insert_resource(Viewports::default())
         .insert_resource(UiSize::default())
-        .insert_resource(TickTimer(Timer::from_seconds(0.01, TimerMode::Repeating)))
+        .init_resource::<HeatmapSettings>()
+        .init_resource::<VolumeSettings>()
+        .init_resource::<GraphSettings>()
+        .init_resource::<AnimationConfig>()
+        .init_resource::<HistoryConfig>()
+        .init_resource::<Breakpoints>()
+        .insert_resource(
+            RendererRegistry::default()
+                .with(GridVis)
+                .with(HeatmapVis)
+                .with(VolumeVis)
+                .with(GraphVis)
+                .with(ArrayVis),
+        )
+        .init_resource::<StoryClock>()
+        .init_resource::<CameraLimits>()
+        .add_systems(
+            PreUpdate,
+            (
+                story_clock_system,
+                story_tick_system.run_if(
+                    in_scene_state
+                        .and(resource_exists::<Story>)
+                        .and(resource_exists::<Playhead>),
+                ),
+            )
+                .chain(),
+        )
+        .add_systems(Update, tween_system)
         .add_message::<LoadVisualization>()
+        .add_message::<SelectRenderer>()
+        .add_message::<CloseVisualization>()
+        .add_message::<ReportError>()
         .add_message::<ViewportChanged>()
+        .add_message::<FrameBounds>()
+        .add_message::<MoveCamera>()
+        .add_message::<SetCameraMode>()
         .configure_sets(
             Update,
             (
@@ -75,16 +173,169 @@ fn main() {
             (
                 unload_visualization_system.in_set(VisualizationSystemSet::Unload),
                 load_visualization_system.in_set(VisualizationSystemSet::Load),
+                configure_visualization_system
+                    .in_set(VisualizationSystemSet::Load)
+                    .run_if(resource_exists::<Engine>),
             )
                 .run_if(on_message::<LoadVisualization>),
         )
+        .add_systems(
+            Update,
+            report_error_system
+                .after(VisualizationSystemSet::Load)
+                .run_if(on_message::<ReportError>),
+        )
+        .add_systems(
+            EguiPrimaryContextPass,
+            ui_error_screen.after(ui_system).run_if(in_error_state),
+        )
+        .add_systems(
+            Update,
+            close_visualization_system
+                .after(VisualizationSystemSet::Load)
+                .run_if(on_message::<CloseVisualization>),
+        )
+        .add_systems(
+            Update,
+            select_renderer_system
+                .before(VisualizationSystemSet::Unload)
+                .run_if(on_message::<SelectRenderer>.and(resource_exists::<Story>)),
+        )
+        .add_systems(
+            EguiPrimaryContextPass,
+            ui_playback_panel.run_if(resource_exists::<Story>.and(not(in_error_state))),
+        )
+        .add_systems(
+            Update,
+            configure_history_system.run_if(resource_exists::<Story>),
+        )
+        .add_systems(
+            Update,
+            (
+                load_session_system.run_if(resource_added::<Story>),
+                save_session_system
+                    .run_if(resource_exists::<Story>.and(resource_exists::<Session>)),
+            )
+                .chain()
+                .before(close_visualization_system),
+        )
+        .add_systems(
+            EguiPrimaryContextPass,
+            ui_breakpoints_panel.run_if(resource_exists::<Story>.and(not(in_error_state))),
+        )
+        .add_systems(
+            EguiPrimaryContextPass,
+            ui_search_panel.run_if(
+                resource_exists::<Story>
+                    .and(resource_exists::<Playhead>)
+                    .and(not(in_error_state)),
+            ),
+        )
+        .add_systems(
+            Update,
+            poll_search_task.run_if(resource_exists::<SearchTask>),
+        )
+        .add_systems(
+            EguiPrimaryContextPass,
+            ui_markers_panel.run_if(
+                resource_exists::<Story>
+                    .and(resource_exists::<Session>)
+                    .and(not(in_error_state)),
+            ),
+        )
+        .add_systems(
+            EguiPrimaryContextPass,
+            ui_history_overlay.run_if(resource_exists::<Story>.and(history_overlay_enabled)),
+        )
+        .add_systems(
+            EguiPrimaryContextPass,
+            ui_renderer_switch.run_if(
+                resource_exists::<RendererCandidates>
+                    .and(resource_exists::<Story>)
+                    .and(not(in_error_state)),
+            ),
+        )
         // --- Grid ---
         .add_systems(Startup, setup_orbiting_camera)
-        .add_systems(Update, camera_orbit_controls)
+        .add_systems(
+            Update,
+            (
+                camera_limits_system,
+                frame_bounds_system,
+                camera_mode_system,
+                move_camera_system,
+                camera_transition_system.run_if(any_with_component::<CameraTransition>),
+                camera_controls,
+                camera_follow_system,
+                apply_camera_system,
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            camera_shortcut_system
+                .before(camera_mode_system)
+                .run_if(in_scene_state),
+        )
+        .add_systems(
+            EguiPrimaryContextPass,
+            (ui_camera_panel, ui_camera_hud).run_if(in_scene_state),
+        )
         .add_systems(OnEnter(VisualizerState::Grid), setup_grid)
         // .add_systems(OnExit(AppState::Grid), cleanup_grid)
         // ^ This will be run in unload_vis anyway
-        .add_systems(Update, tick_system.run_if(in_state(VisualizerState::Grid)))
+        .add_systems(
+            Update,
+            tick_system.run_if(in_state(VisualizerState::Grid).and(not(resource_exists::<Story>))),
+        )
+        .add_systems(
+            Update,
+            story_grid_system.run_if(in_state(VisualizerState::Grid).and(resource_exists::<Story>)),
+        )
+        // --- Heatmap ---
+        .add_systems(OnEnter(VisualizerState::Heatmap), setup_heatmap)
+        .add_systems(
+            Update,
+            (heatmap_render_system, heatmap_hover_system)
+                .chain()
+                .run_if(in_state(VisualizerState::Heatmap)),
+        )
+        .add_systems(
+            EguiPrimaryContextPass,
+            ui_heatmap_panel.run_if(in_state(VisualizerState::Heatmap)),
+        )
+        // --- Volume ---
+        .add_systems(OnEnter(VisualizerState::Volume), setup_volume)
+        .add_systems(
+            Update,
+            volume_render_system.run_if(in_state(VisualizerState::Volume)),
+        )
+        .add_systems(
+            EguiPrimaryContextPass,
+            ui_volume_panel.run_if(in_state(VisualizerState::Volume)),
+        )
+        // --- Graph ---
+        .add_systems(OnEnter(VisualizerState::Graph), setup_graph)
+        .add_systems(
+            Update,
+            graph_render_system
+                .run_if(in_state(VisualizerState::Graph).and(resource_exists::<GraphScene>)),
+        )
+        .add_systems(
+            EguiPrimaryContextPass,
+            ui_graph_panel.run_if(in_state(VisualizerState::Graph)),
+        )
+        // --- Array ---
+        .add_systems(OnEnter(VisualizerState::Array), setup_array)
+        .add_systems(
+            Update,
+            array_render_system
+                .run_if(in_state(VisualizerState::Array).and(resource_exists::<ArrayScale>)),
+        )
+        .add_systems(
+            EguiPrimaryContextPass,
+            ui_array_hud.run_if(in_state(VisualizerState::Array)),
+        )
         .add_systems(Update, file_drop.run_if(in_state(VisualizerState::Input)))
         .add_systems(
             Update,
@@ -93,11 +344,61 @@ fn main() {
                     .and(resource_exists::<FileTypeSuggestion>),
             ),
         )
+        .add_systems(
+            Update,
+            parse_document_system.run_if(
+                resource_exists::<FileTypeSelection>
+                    .and(resource_exists::<DroppedFile>)
+                    .and(not(resource_exists::<StructuredDocument>))
+                    .and(not(resource_exists::<LoadingTask>)),
+            ),
+        )
+        .add_systems(
+            Update,
+            preview_table_system.run_if(
+                resource_exists::<FileTypeSelection>
+                    .and(resource_exists::<DroppedFile>)
+                    .and(not(resource_exists::<TablePreview>)),
+            ),
+        )
+        .add_systems(
+            Update,
+            parse_numpy_system.run_if(
+                resource_exists::<FileTypeSelection>
+                    .and(resource_exists::<DroppedFile>)
+                    .and(not(resource_exists::<NumpyFile>))
+                    .and(not(resource_exists::<LoadingTask>)),
+            ),
+        )
+        .add_systems(
+            Update,
+            map_binary_system.run_if(
+                resource_exists::<FileTypeSelection>
+                    .and(resource_exists::<DroppedFile>)
+                    .and(not(resource_exists::<BinaryFile>)),
+            ),
+        )
+        .add_systems(
+            Update,
+            poll_loading_task.run_if(resource_exists::<LoadingTask>),
+        )
+        .add_systems(
+            Update,
+            poll_pattern_search.run_if(resource_exists::<PatternSearch>),
+        )
         // .add_systems(
         //     EguiPrimaryContextPass,
         //     ui_selection_menu.run_if(in_state(VisualizerState::Loading)),
         // )
         .add_systems(OnEnter(UiStatus::Invisible), cleanup_ui)
+        .add_systems(
+            Update,
+            (
+                viewport_event_system.run_if(on_message::<ViewportChanged>),
+                apply_viewports_system.run_if(viewports_need_layout),
+            )
+                .chain(),
+        )
         // --- UI ---
         // .add_systems(
         //     EguiPrimaryContextPass,
@@ -113,24 +414,30 @@ fn main() {
                 ui_selection_menu.run_if(
                     in_state(VisualizerState::Loading).and(resource_exists::<FileTypeSelection>),
                 ),
+                ui_loading_overlay.run_if(resource_exists::<LoadingTask>),
             )
                 .chain(),
         )
         // --- Input toggle ---
         .add_systems(Update, handle_input)
+        .add_systems(
+            Update,
+            transport_shortcut_system
+                .run_if(resource_exists::<Story>.and(resource_exists::<Playhead>)),
+        )
         .run();
 }
 
-fn cleanup_ui(
-    mut camera: Single<&mut Camera, Without<EguiContext>>,
-    window: Single<&mut Window, With<PrimaryWindow>>,
-) {
-    let pos = UVec2::new(0, 0);
-    let size = UVec2::new(window.physical_width(), window.physical_height());
-    camera.viewport = Some(Viewport {
-        physical_position: pos,
-        physical_size: size,
-        ..default()
+/// Gives the whole window back to the scene while the panels are hidden. `ui_system` sends the
+/// panel sizes again once they are shown.
+fn cleanup_ui(mut ui_size: ResMut<UiSize>, mut writer: MessageWriter<ViewportChanged>) {
+    *ui_size = UiSize::default();
+    writer.write(ViewportChanged {
+        id: ViewportId::Ui,
+        top: 0.,
+        bottom: 0.,
+        left: 0.,
+        right: 0.,
     });
 }
 fn setup_orbiting_camera(
@@ -150,6 +457,7 @@ fn setup_orbiting_camera(
             yaw: 0.,
             target: Vec3::default(),
         },
+        CameraMode::default(),
     ));
 
     commands.spawn((
@@ -169,6 +477,8 @@ fn setup_grid(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    story: Option<Res<Story>>,
+    settings: Option<Res<VisualizationSettings<Configuration>>>,
 ) {
     // Light
     commands.spawn((
@@ -181,6 +491,20 @@ fn setup_grid(
         TaggedEntity,
     ));
 
+    // A program's engine renderer sits next to the cells drawn from its output.
+    if let Some(settings) = &settings {
+        SimpleGrid::spawn(&mut commands, settings);
+    }
+    if let Some(story) = story {
+        spawn_story_grid(&mut commands, &mut meshes, &mut materials, &story);
+        info!("Entered Grid state");
+        return;
+    }
+    if settings.is_some() {
+        info!("Entered Grid state");
+        return;
+    }
+
     // Cubes
     let mesh_handle = meshes.add(Cuboid::new(1.0, 1.0, 1.0));
     let material_handle = materials.add(StandardMaterial {
@@ -191,10 +515,13 @@ fn setup_grid(
 
     for x in -3..=3 {
         for z in -3..=3 {
+            let transform =
+                Transform::from_translation(Vec3::new(x as f32 * 1.5, 0.0, z as f32 * 1.5));
             commands.spawn((
                 Mesh3d(mesh_handle.clone()),
                 MeshMaterial3d(material_handle.clone()),
-                Transform::from_translation(Vec3::new(x as f32 * 1.5, 0.0, z as f32 * 1.5)),
+                transform,
+                Tween::new(transform),
                 GlobalTransform::default(),
                 Visibility::default(),
                 // ComputedVisibility::default(),
@@ -209,93 +536,17 @@ fn setup_grid(
 #[derive(Component)]
 struct AnimatedCube;
 
-#[derive(Component)]
-struct OrbitCamera {
-    radius: f32,
-    yaw: f32,
-    pitch: f32,
-    target: Vec3,
-}
-
-fn camera_orbit_controls(
-    mouse: Res<ButtonInput<MouseButton>>,
-    mut motion: MessageReader<MouseMotion>,
-    mut scroll: MessageReader<MouseWheel>,
-    mut query: Query<(&mut Transform, &mut OrbitCamera), With<Camera>>,
-) -> Result {
-    let (mut transform, mut orbit) = query.single_mut()?;
-
-    // Handle input
-    let (delta_yaw, delta_pitch) = read_mouse_motion(&mut motion, &mouse);
-    orbit.yaw += delta_yaw;
-    orbit.pitch = (orbit.pitch + delta_pitch).clamp(-1.5, 1.5);
-
-    // Handle zoom
-    orbit.radius = adjust_zoom(orbit.radius, &mut scroll);
-
-    // Handle pan
-    if mouse.pressed(MouseButton::Middle) {
-        orbit.target += compute_pan_vector(&mut motion, transform.rotation);
-    }
-
-    // Apply the actual transform update
-    apply_orbit_transform(&mut transform, &orbit);
-    Ok(())
-}
-
-fn read_mouse_motion(
-    motion: &mut MessageReader<MouseMotion>,
-    mouse: &ButtonInput<MouseButton>,
-) -> (f32, f32) {
-    if !mouse.pressed(MouseButton::Left) {
-        return (0.0, 0.0);
-    }
-
-    let mut delta = Vec2::ZERO;
-    for ev in motion.read() {
-        delta += ev.delta;
-    }
-
-    let sensitivity = 0.005;
-    (-delta.x * sensitivity, -delta.y * sensitivity)
-}
-
-fn adjust_zoom(radius: f32, scroll: &mut MessageReader<MouseWheel>) -> f32 {
-    let mut new_radius = radius;
-    for ev in scroll.read() {
-        new_radius -= ev.y * 0.3;
-    }
-    new_radius.clamp(1.0, 20.0)
-}
-
-fn compute_pan_vector(motion: &mut MessageReader<MouseMotion>, rotation: Quat) -> Vec3 {
-    let mut delta = Vec2::ZERO;
-    for ev in motion.read() {
-        delta += ev.delta;
-    }
-    let sensitivity = 0.05;
-    let right = rotation * Vec3::X;
-    let up = rotation * Vec3::Y;
-    (-right * delta.x * sensitivity) + (up * delta.y * sensitivity)
-}
-
-fn apply_orbit_transform(transform: &mut Transform, orbit: &OrbitCamera) {
-    let rotation = Quat::from_rotation_y(orbit.yaw) * Quat::from_rotation_x(orbit.pitch);
-    let offset = rotation * Vec3::new(0.0, 0.0, orbit.radius);
-    transform.translation = orbit.target + offset;
-    transform.look_at(orbit.target, Vec3::Y);
-}
-/// Fixed tick system (0.5 s)
+/// Retargets the demo cubes once per story clock tick, `tween_system` eases them there
 fn tick_system(
     time: Res<Time>,
-    mut timer: ResMut<TickTimer>,
-    mut q: Query<&mut Transform, With<AnimatedCube>>, //     mut materials: ResMut<Assets<StandardMaterial>>,
+    clock: Res<StoryClock>,
+    mut q: Query<(&Transform, &mut Tween), With<AnimatedCube>>, //     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    if timer.0.tick(time.delta()).just_finished() {
+    if clock.due() > 0 {
         // for mesh in material_handles
         // fn random_scale_system() {
         let t = time.elapsed_secs();
-        for mut transform in &mut q {
+        for (transform, mut tween) in &mut q {
             // use entity position as a seed to vary the result
 
             let base_height = 1.0;
@@ -303,8 +554,12 @@ fn tick_system(
                 + (t + transform.translation.x + transform.translation.z)
                     .sin()
                     .abs(); // example animation
-            transform.scale.y = scale_y;
-            transform.translation.y = (base_height * scale_y) / 2.0;
+            let target = Transform {
+                translation: transform.translation.with_y((base_height * scale_y) / 2.0),
+                scale: transform.scale.with_y(scale_y),
+                ..*transform
+            };
+            tween.retarget(transform, target, t);
         }
     }
 }
@@ -329,30 +584,57 @@ pub struct ExecutableConfiguration {
 pub enum FileTypeSelection {
     Directory(&'static str),
     Executable(&'static str, ExecutableConfiguration),
-    Text(&'static str),
-    Readable(&'static str),
-    Unknown,
+    Text(&'static str, TextConfiguration),
+    Readable(&'static str, ReadableConfiguration),
+    Table(&'static str, TableConfiguration),
+    Volume(&'static str),
+    NumPy(&'static str, NumpyConfiguration),
+    Graph(&'static str, GraphConfiguration),
+    Events(&'static str),
+    Unknown(&'static str, HexConfiguration),
 }
 
 impl FileTypeSelection {
-    fn new(file_type_suggestion: FileTypeSuggestion) -> Self {
+    fn new(file_type_suggestion: FileTypeSuggestion, path: Option<&Path>) -> Self {
         match file_type_suggestion {
             FileTypeSuggestion::Directory => Self::Directory("Directory"),
             FileTypeSuggestion::Executable => {
                 Self::Executable("Executable", ExecutableConfiguration::default())
             }
-            FileTypeSuggestion::Text => Self::Text("Text"),
-            FileTypeSuggestion::Readable => Self::Readable("Readable file (json, yaml, etc.)"),
-            FileTypeSuggestion::Unknown => Self::Unknown,
+            FileTypeSuggestion::Text => Self::Text("Text", TextConfiguration::default()),
+            FileTypeSuggestion::Readable => Self::Readable(
+                "Readable file (json, yaml, etc.)",
+                ReadableConfiguration::default(),
+            ),
+            FileTypeSuggestion::Table => {
+                Self::Table("Table (csv, tsv)", TableConfiguration::default())
+            }
+            FileTypeSuggestion::Volume => Self::Volume("Raw volume"),
+            FileTypeSuggestion::NumPy => {
+                Self::NumPy("NumPy (npy, npz)", NumpyConfiguration::default())
+            }
+            FileTypeSuggestion::Graph => Self::Graph(
+                "Graph (dot, edge list)",
+                path.map(GraphConfiguration::for_path).unwrap_or_default(),
+            ),
+            FileTypeSuggestion::Events => Self::Events("Story protocol events"),
+            FileTypeSuggestion::Unknown => {
+                Self::Unknown("Unknown (hex dump)", HexConfiguration::default())
+            }
         }
     }
     fn to_text(&self) -> &str {
         match self {
             FileTypeSelection::Directory(s) => s,
             FileTypeSelection::Executable(s, _) => s,
-            FileTypeSelection::Text(s) => s,
-            FileTypeSelection::Readable(s) => s,
-            FileTypeSelection::Unknown => "...",
+            FileTypeSelection::Text(s, _) => s,
+            FileTypeSelection::Readable(s, _) => s,
+            FileTypeSelection::Table(s, _) => s,
+            FileTypeSelection::Volume(s) => s,
+            FileTypeSelection::NumPy(s, _) => s,
+            FileTypeSelection::Graph(s, _) => s,
+            FileTypeSelection::Events(s) => s,
+            FileTypeSelection::Unknown(s, _) => s,
         }
     }
 
@@ -406,24 +688,65 @@ impl FileTypeSelection {
 
         response |= ui.selectable_value(
             selected,
-            FileTypeSelection::Text("Text"),
+            FileTypeSelection::Text("Text", TextConfiguration::default()),
             RichText::new("Text").size(32.),
         );
 
         response |= ui.selectable_value(
             selected,
-            FileTypeSelection::Readable("Readable"),
+            FileTypeSelection::Readable("Readable", ReadableConfiguration::default()),
             RichText::new("Readable").size(32.),
         );
 
+        response |= ui.selectable_value(
+            selected,
+            FileTypeSelection::Table("Table", TableConfiguration::default()),
+            RichText::new("Table").size(32.),
+        );
+
+        response |= ui.selectable_value(
+            selected,
+            FileTypeSelection::Volume("Raw volume"),
+            RichText::new("Raw volume").size(32.),
+        );
+
+        response |= ui.selectable_value(
+            selected,
+            FileTypeSelection::NumPy("NumPy", NumpyConfiguration::default()),
+            RichText::new("NumPy").size(32.),
+        );
+
+        response |= ui.selectable_value(
+            selected,
+            FileTypeSelection::Graph("Graph", GraphConfiguration::default()),
+            RichText::new("Graph").size(32.),
+        );
+
+        response |= ui.selectable_value(
+            selected,
+            FileTypeSelection::Events("Story protocol events"),
+            RichText::new("Story protocol events").size(32.),
+        );
+
+        response |= ui.selectable_value(
+            selected,
+            FileTypeSelection::Unknown("Hex dump", HexConfiguration::default()),
+            RichText::new("Hex dump").size(32.),
+        );
+
         // response |= ui.selectable_value(selected, FileTypeSelection::Unknown, "Unknown");
 
         response
     }
 }
 
-fn process_suggestion(suggestion: Res<FileTypeSuggestion>, mut commands: Commands) {
-    commands.insert_resource(FileTypeSelection::new(suggestion.clone()));
+fn process_suggestion(
+    suggestion: Res<FileTypeSuggestion>,
+    dropped: Option<Res<DroppedFile>>,
+    mut commands: Commands,
+) {
+    let path = dropped.as_ref().map(|dropped| dropped.0.as_path());
+    commands.insert_resource(FileTypeSelection::new(suggestion.clone(), path));
 }
 fn check(ui: &mut egui::Ui, value: &mut bool) {
     ui.checkbox(value, "Checkbox");
@@ -764,12 +1087,17 @@ fn ui_dnd(hovered: Option<Res<HoveredFile>>, mut contexts: EguiContexts) -> Resu
     Ok(())
 }
 /// Simple UI for the Ui state
+#[allow(clippy::too_many_arguments)]
 fn ui_system(
     // mut commands: Commands,
     mut contexts: EguiContexts,
     mut ui_size: ResMut<UiSize>,
     mut writer: MessageWriter<ViewportChanged>,
-    // mut camera: Single<&mut Camera, Without<EguiContext>>,
+    mut close: MessageWriter<CloseVisualization>,
+    state: Res<State<VisualizerState>>,
+    story: Option<Res<Story>>,
+    mut playhead: Option<ResMut<Playhead>>,
+    mut session: Option<ResMut<Session>>,
     window: Single<&mut Window, With<PrimaryWindow>>,
     // mut next_state: ResMut<NextState<AppState>>
 ) -> Result {
@@ -793,10 +1121,23 @@ fn ui_system(
         .resizable(true)
         .show(ctx, |ui| {
             ui.horizontal_centered(|ui| {
-                if ui.button(RichText::new("Load")).clicked() {};
-                ui.separator();
-                if ui.button(RichText::new("Start")).clicked() {};
+                let visualizing = !matches!(
+                    state.get(),
+                    VisualizerState::Input | VisualizerState::Loading
+                );
+                if ui
+                    .add_enabled(visualizing, egui::Button::new(RichText::new("Load")))
+                    .on_hover_text("Close this visualization and drop another file")
+                    .clicked()
+                {
+                    close.write(CloseVisualization);
+                }
                 ui.separator();
+                if let Some(story) = &story
+                    && let Some(playhead) = &mut playhead
+                {
+                    ui_transport(ui, story, playhead, session.as_mut().map(|s| s.reborrow()));
+                }
             });
         })
         .response
@@ -815,17 +1156,6 @@ fn ui_system(
             right: 0.,
         });
     }
-    //FIXME: Fix the window dying on minimize
-
-    // let pos = UVecpub 2::new(0, top as u32);
-    // let size = UVec2::new(window.physical_width(), window.physical_height())
-    //     - pos
-    //     - UVec2::new(0, bottom as u32);
-    // camera.viewport = Some(Viewport {
-    //     physical_position: pos,
-    //     physical_size: size,
-    //     ..default()
-    // });
     Ok(())
 }
 
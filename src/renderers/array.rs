@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+
+use crate::{
+    error::{ReportError, unexpected_frame},
+    renderers::{registry::StoryRenderer, tween::Tween},
+    story::{ArrayState, FrameState, Playhead, Story},
+    visualization::{FrameBounds, TaggedEntity, VisualizationKind},
+};
+
+const BAR_SPACING: f32 = 1.2;
+/// World height of the tallest bar.
+const ARRAY_HEIGHT: f32 = 8.0;
+
+const BAR_COLOR: Color = Color::srgb(0.3, 0.7, 1.0);
+pub const COMPARED_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+pub const SWAPPED_COLOR: Color = Color::srgb(1.0, 0.25, 0.25);
+pub const WRITTEN_COLOR: Color = Color::srgb(0.3, 0.9, 0.4);
+
+/// One bar per array slot.
+#[derive(Component)]
+pub struct ArrayBar {
+    index: usize,
+    material: Handle<StandardMaterial>,
+}
+
+/// Value range used to scale the bars, fixed for the whole story so heights are comparable.
+#[derive(Resource, Debug)]
+pub struct ArrayScale {
+    low: f32,
+    high: f32,
+    width: usize,
+}
+
+impl ArrayScale {
+    fn height(&self, value: f32) -> f32 {
+        if value.is_nan() {
+            return 0.0;
+        }
+        let t = if self.high > self.low {
+            (value - self.low) / (self.high - self.low)
+        } else {
+            1.0
+        };
+        (t * ARRAY_HEIGHT).max(0.05)
+    }
+
+    fn x(&self, index: usize) -> f32 {
+        (index as f32 - (self.width as f32 - 1.0) / 2.0) * BAR_SPACING
+    }
+}
+
+/// Bars whose heights follow the values of a 1D array, highlighting what each tick touched.
+pub struct ArrayVis;
+
+impl ArrayVis {
+    pub fn spawn(
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        story: &Story,
+    ) {
+        let (width, _) = story.bounds();
+        let (low, high) = story.value_range().unwrap_or((0.0, 1.0));
+        // Bars grow from zero, or from the lowest value when it is negative.
+        let scale = ArrayScale {
+            low: low.min(0.0),
+            high,
+            width,
+        };
+
+        let mesh = meshes.add(Cuboid::new(1.0, 1.0, 1.0));
+        for index in 0..width {
+            let material = materials.add(StandardMaterial {
+                base_color: BAR_COLOR,
+                ..default()
+            });
+            let transform = Transform::from_xyz(scale.x(index), 0.0, 0.0);
+            commands.spawn((
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                transform,
+                Tween::new(transform),
+                GlobalTransform::default(),
+                Visibility::Hidden,
+                ArrayBar { index, material },
+                TaggedEntity,
+            ));
+        }
+        commands.spawn((
+            DirectionalLight {
+                illuminance: 10_000.0,
+                ..default()
+            },
+            Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+            GlobalTransform::default(),
+            TaggedEntity,
+        ));
+        let half_width = width as f32 * BAR_SPACING / 2.0;
+        commands.write_message(FrameBounds {
+            center: Vec3::new(0.0, ARRAY_HEIGHT / 2.0, 0.0),
+            half_extents: Vec3::new(half_width, ARRAY_HEIGHT / 2.0, 1.0),
+        });
+        commands.insert_resource(scale);
+        info!("Spawned an array of {width} bars");
+    }
+}
+
+impl StoryRenderer for ArrayVis {
+    type StateSnapshot = ArrayState;
+
+    fn renderer_name(&self) -> &'static str {
+        "Array bars"
+    }
+
+    fn kind(&self) -> VisualizationKind {
+        VisualizationKind::Array
+    }
+
+    fn snapshot<'a>(&self, state: &'a FrameState) -> Option<&'a ArrayState> {
+        match state {
+            FrameState::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    fn score(&self, _story: &Story) -> u32 {
+        100
+    }
+}
+
+pub fn setup_array(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    story: Res<Story>,
+) {
+    ArrayVis::spawn(&mut commands, &mut meshes, &mut materials, &story);
+}
+
+/// Eases the bars towards the current frame: heights and highlights, with swapped bars sliding
+/// over from the slot they left.
+pub fn array_render_system(
+    story: Res<Story>,
+    time: Res<Time>,
+    mut playhead: ResMut<Playhead>,
+    scale: Res<ArrayScale>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut errors: MessageWriter<ReportError>,
+    mut bars: Query<(&ArrayBar, &mut Transform, &mut Tween, &mut Visibility)>,
+) {
+    if playhead.rendered == Some(playhead.index) {
+        return;
+    }
+    let state = story.state(playhead.index);
+    let Some(FrameState::Array(array)) = state.as_deref() else {
+        errors.write(ReportError(unexpected_frame(
+            &story,
+            playhead.index,
+            "array",
+        )));
+        return;
+    };
+    // Replay the swaps on slot indices to know which slot each element came from.
+    let mut origin: Vec<usize> = (0..array.values.len()).collect();
+    for &(a, b) in &array.swapped {
+        if a < origin.len() && b < origin.len() {
+            origin.swap(a, b);
+        }
+    }
+
+    let now = time.elapsed_secs();
+    for (bar, mut transform, mut tween, mut visibility) in &mut bars {
+        let Some(&value) = array.values.get(bar.index) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let height = scale.height(value);
+        let target = Transform::from_xyz(scale.x(bar.index), height / 2.0, 0.0)
+            .with_scale(Vec3::new(1.0, height, 1.0));
+
+        let color = if array
+            .swapped
+            .iter()
+            .any(|&(a, b)| a == bar.index || b == bar.index)
+        {
+            SWAPPED_COLOR
+        } else if array.written.contains(&bar.index) {
+            WRITTEN_COLOR
+        } else if array.compared.contains(&bar.index) {
+            COMPARED_COLOR
+        } else {
+            BAR_COLOR
+        };
+        let material = materials.get_mut(&bar.material);
+        if *visibility == Visibility::Hidden {
+            *visibility = Visibility::Visible;
+            *transform = target;
+            *tween = Tween::new(target);
+            if let Some(material) = material {
+                material.base_color = color;
+            }
+            continue;
+        }
+        // A moved element keeps its height and slides over from the slot it left.
+        let from = if origin[bar.index] == bar.index {
+            *transform
+        } else {
+            target.with_translation(target.translation.with_x(scale.x(origin[bar.index])))
+        };
+        tween.retarget(&from, target, now);
+        if let Some(material) = material {
+            tween.retarget_color(material.base_color, color);
+        }
+    }
+    playhead.rendered = Some(playhead.index);
+}
@@ -0,0 +1,511 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, futures::check_ready},
+};
+
+use crate::{
+    error::{ErrorStage, ReportError, VisualizationError, report_error, unexpected_frame},
+    renderers::{registry::StoryRenderer, tween::Tween},
+    story::{FrameState, GraphState, Playhead, Story},
+    visualization::{FrameBounds, TaggedEntity, VisualizationKind},
+};
+
+/// World size of the longest side of the layout.
+const GRAPH_EXTENT: f32 = 10.0;
+/// Pairwise force evaluations allowed per layout; large graphs get fewer iterations.
+const FORCE_BUDGET: usize = 20_000_000;
+const FORCE_ITERATIONS: usize = 300;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GraphLayout {
+    /// Fruchterman-Reingold spring embedding.
+    #[default]
+    Force,
+    /// Breadth-first layers from the nodes without incoming edges.
+    Layered,
+}
+
+impl GraphLayout {
+    pub const ALL: [GraphLayout; 2] = [GraphLayout::Force, GraphLayout::Layered];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            GraphLayout::Force => "Force-directed",
+            GraphLayout::Layered => "Layered",
+        }
+    }
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct GraphSettings {
+    pub layout: GraphLayout,
+}
+
+/// Positions and sizes shared by every frame. The layout is computed once over the whole story
+/// so nodes do not move between ticks.
+#[derive(Resource, Debug)]
+pub struct GraphScene {
+    layout: GraphLayout,
+    positions: Vec<Vec3>,
+    /// Positions for `layout` being computed on the async pool. The force layout of a large
+    /// graph takes seconds.
+    pending: Option<Task<Vec<Vec3>>>,
+    node_radius: f32,
+    node_weights: Option<(f32, f32)>,
+    edge_weights: Option<(f32, f32)>,
+}
+
+#[derive(Component)]
+pub struct GraphNodeEntity {
+    index: usize,
+    material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+pub struct GraphEdgeEntity {
+    index: usize,
+    material: Handle<StandardMaterial>,
+}
+
+/// Colour of a node or edge state. Well-known traversal states get fixed colours, anything else
+/// a stable colour derived from its name.
+pub fn state_color(state: Option<&str>) -> Color {
+    match state.map(str::to_lowercase).as_deref() {
+        None | Some("" | "none" | "unvisited") => Color::srgb(0.5, 0.5, 0.55),
+        Some("frontier" | "queued" | "open") => Color::srgb(1.0, 0.6, 0.1),
+        Some("visited" | "closed" | "done") => Color::srgb(0.2, 0.5, 1.0),
+        Some("current" | "active") => Color::srgb(1.0, 0.2, 0.2),
+        Some("path" | "tree" | "selected") => Color::srgb(0.2, 0.9, 0.3),
+        Some(other) => {
+            let hash = other
+                .bytes()
+                .fold(0u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32));
+            Color::hsl((hash % 360) as f32, 0.7, 0.55)
+        }
+    }
+}
+
+/// Nodes and edges as spheres and thin boxes, recoloured and resized by their per-tick state.
+pub struct GraphVis;
+
+impl GraphVis {
+    pub fn spawn(
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        settings: &GraphSettings,
+        story: &Story,
+    ) {
+        // Nodes and edges are only appended, so the last frame holds every one of them.
+        let last = story.last();
+        let Some(FrameState::Graph(graph)) = last.as_deref() else {
+            report_error(
+                commands,
+                VisualizationError::new(ErrorStage::Spawn, "the story holds no graph")
+                    .with_source(&story.source),
+            );
+            return;
+        };
+        let (mut node_weights, mut edge_weights) = (Vec::new(), Vec::new());
+        story.all_states(|state| {
+            if let FrameState::Graph(graph) = state {
+                node_weights.push(graph.node_weight_range());
+                edge_weights.push(graph.edge_weight_range());
+            }
+            true
+        });
+        let mut scene = GraphScene {
+            layout: settings.layout,
+            positions: Vec::new(),
+            pending: None,
+            node_radius: (GRAPH_EXTENT / (graph.nodes.len().max(1) as f32).sqrt() * 0.15)
+                .clamp(0.05, 0.4),
+            node_weights: merge_ranges(node_weights.into_iter()),
+            edge_weights: merge_ranges(edge_weights.into_iter()),
+        };
+        scene.start_layout(settings.layout, graph);
+
+        let sphere = meshes.add(Sphere::new(1.0));
+        for index in 0..graph.nodes.len() {
+            let material = materials.add(StandardMaterial::default());
+            commands.spawn((
+                Mesh3d(sphere.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::default(),
+                Tween::new(Transform::default()),
+                GlobalTransform::default(),
+                Visibility::Hidden,
+                GraphNodeEntity { index, material },
+                TaggedEntity,
+            ));
+        }
+        let cuboid = meshes.add(Cuboid::from_length(1.0));
+        for index in 0..graph.edges.len() {
+            let material = materials.add(StandardMaterial::default());
+            commands.spawn((
+                Mesh3d(cuboid.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::default(),
+                Tween::new(Transform::default()),
+                GlobalTransform::default(),
+                Visibility::Hidden,
+                GraphEdgeEntity { index, material },
+                TaggedEntity,
+            ));
+        }
+
+        commands.spawn((
+            DirectionalLight {
+                illuminance: 10_000.0,
+                ..default()
+            },
+            Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+            GlobalTransform::default(),
+            TaggedEntity,
+        ));
+        info!(
+            "Spawned a graph of {} nodes and {} edges",
+            graph.nodes.len(),
+            graph.edges.len()
+        );
+        commands.insert_resource(scene);
+    }
+}
+
+impl GraphScene {
+    /// Lays the nodes out on the async pool, they stay where they are until it is done.
+    fn start_layout(&mut self, layout: GraphLayout, graph: &GraphState) {
+        let count = graph.nodes.len();
+        let edges: Vec<(usize, usize)> = graph.edges.iter().map(|e| (e.from, e.to)).collect();
+        self.layout = layout;
+        self.pending = Some(
+            AsyncComputeTaskPool::get().spawn(async move { positions(layout, count, &edges) }),
+        );
+    }
+
+    pub fn is_laying_out(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    fn bounds(&self) -> FrameBounds {
+        let (min, max) = self
+            .positions
+            .iter()
+            .fold((Vec3::MAX, Vec3::MIN), |(lo, hi), p| {
+                (lo.min(*p), hi.max(*p))
+            });
+        if min.x > max.x {
+            return FrameBounds {
+                center: Vec3::ZERO,
+                half_extents: Vec3::ONE,
+            };
+        }
+        FrameBounds {
+            center: (min + max) / 2.0,
+            half_extents: (max - min) / 2.0 + Vec3::splat(self.node_radius),
+        }
+    }
+}
+
+fn merge_ranges(ranges: impl Iterator<Item = Option<(f32, f32)>>) -> Option<(f32, f32)> {
+    ranges
+        .flatten()
+        .reduce(|(lo, hi), (l, h)| (lo.min(l), hi.max(h)))
+}
+
+/// Normalized weight in `[0, 1]`, or one half when there is no weight to compare.
+fn normalized(weight: Option<f32>, range: Option<(f32, f32)>) -> f32 {
+    match (weight, range) {
+        (Some(w), Some((lo, hi))) if hi > lo => ((w - lo) / (hi - lo)).clamp(0.0, 1.0),
+        _ => 0.5,
+    }
+}
+
+impl StoryRenderer for GraphVis {
+    type StateSnapshot = GraphState;
+
+    fn renderer_name(&self) -> &'static str {
+        "Node-link graph"
+    }
+
+    fn kind(&self) -> VisualizationKind {
+        VisualizationKind::Graph
+    }
+
+    fn snapshot<'a>(&self, state: &'a FrameState) -> Option<&'a GraphState> {
+        match state {
+            FrameState::Graph(graph) => Some(graph),
+            _ => None,
+        }
+    }
+
+    fn score(&self, _story: &Story) -> u32 {
+        100
+    }
+}
+
+pub fn setup_graph(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    settings: Res<GraphSettings>,
+    story: Res<Story>,
+) {
+    GraphVis::spawn(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &settings,
+        &story,
+    );
+}
+
+/// Eases nodes and edges towards the current frame's states and, once the layout for the layout
+/// setting is ready, to their new positions. Nodes and edges that just appeared start where they
+/// belong.
+#[allow(clippy::too_many_arguments)]
+pub fn graph_render_system(
+    time: Res<Time>,
+    story: Res<Story>,
+    mut playhead: ResMut<Playhead>,
+    settings: Res<GraphSettings>,
+    mut scene: ResMut<GraphScene>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut bounds: MessageWriter<FrameBounds>,
+    mut errors: MessageWriter<ReportError>,
+    mut nodes: Query<
+        (
+            &GraphNodeEntity,
+            &mut Transform,
+            &mut Tween,
+            &mut Visibility,
+        ),
+        Without<GraphEdgeEntity>,
+    >,
+    mut edges: Query<
+        (
+            &GraphEdgeEntity,
+            &mut Transform,
+            &mut Tween,
+            &mut Visibility,
+        ),
+        Without<GraphNodeEntity>,
+    >,
+) {
+    if scene.layout != settings.layout {
+        let last = story.last();
+        let Some(FrameState::Graph(last)) = last.as_deref() else {
+            return;
+        };
+        scene.start_layout(settings.layout, last);
+    }
+    if let Some(task) = scene.pending.as_mut()
+        && let Some(positions) = check_ready(task)
+    {
+        scene.pending = None;
+        scene.positions = positions;
+        bounds.write(scene.bounds());
+        playhead.rendered = None;
+    }
+    // Nothing can be placed before the first layout is done.
+    if scene.positions.is_empty() && scene.pending.is_some() {
+        return;
+    }
+    if playhead.rendered == Some(playhead.index) {
+        return;
+    }
+    let state = story.state(playhead.index);
+    let Some(FrameState::Graph(graph)) = state.as_deref() else {
+        errors.write(ReportError(unexpected_frame(
+            &story,
+            playhead.index,
+            "graph",
+        )));
+        return;
+    };
+
+    let now = time.elapsed_secs();
+    for (node, mut transform, mut tween, mut visibility) in &mut nodes {
+        let Some(state) = graph.nodes.get(node.index) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let size = 0.6 + 0.8 * normalized(state.weight, scene.node_weights);
+        let target = Transform::from_translation(scene.positions[node.index])
+            .with_scale(Vec3::splat(scene.node_radius * size));
+        let color = state_color(state.state.as_deref());
+        show(
+            &mut transform,
+            &mut tween,
+            &mut visibility,
+            target,
+            materials.get_mut(&node.material),
+            color,
+            now,
+        );
+    }
+
+    for (edge, mut transform, mut tween, mut visibility) in &mut edges {
+        let Some(state) = graph.edges.get(edge.index) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let (from, to) = (scene.positions[state.from], scene.positions[state.to]);
+        let thickness =
+            scene.node_radius * (0.15 + 0.35 * normalized(state.weight, scene.edge_weights));
+        let direction = (to - from).normalize_or(Vec3::X);
+        let target = Transform::from_translation((from + to) / 2.0)
+            .with_rotation(Quat::from_rotation_arc(Vec3::Y, direction))
+            .with_scale(Vec3::new(thickness, from.distance(to), thickness));
+        let color = state_color(state.state.as_deref());
+        show(
+            &mut transform,
+            &mut tween,
+            &mut visibility,
+            target,
+            materials.get_mut(&edge.material),
+            color,
+            now,
+        );
+    }
+    playhead.rendered = Some(playhead.index);
+}
+
+/// Retargets a visible node or edge, or places a hidden one straight at `target`.
+fn show(
+    transform: &mut Transform,
+    tween: &mut Tween,
+    visibility: &mut Visibility,
+    target: Transform,
+    material: Option<&mut StandardMaterial>,
+    color: Color,
+    now: f32,
+) {
+    if *visibility == Visibility::Hidden {
+        *visibility = Visibility::Visible;
+        *transform = target;
+        *tween = Tween::new(target);
+        if let Some(material) = material {
+            material.base_color = color;
+        }
+        return;
+    }
+    tween.retarget(transform, target, now);
+    if let Some(material) = material {
+        tween.retarget_color(material.base_color, color);
+    }
+}
+
+/// Positions for `count` nodes joined by `edges` on the XZ plane, fitted to [`GRAPH_EXTENT`].
+fn positions(layout: GraphLayout, count: usize, edges: &[(usize, usize)]) -> Vec<Vec3> {
+    let points = match layout {
+        GraphLayout::Force => force_layout(count, edges),
+        GraphLayout::Layered => layered_layout(count, edges),
+    };
+    let (min, max) = points.iter().fold((Vec2::MAX, Vec2::MIN), |(lo, hi), p| {
+        (lo.min(*p), hi.max(*p))
+    });
+    let extent = (max - min).max_element();
+    let scale = if extent > 0.0 {
+        GRAPH_EXTENT / extent
+    } else {
+        1.0
+    };
+    let center = (min + max) / 2.0;
+    points
+        .into_iter()
+        .map(|p| {
+            let p = (p - center) * scale;
+            Vec3::new(p.x, 0.0, p.y)
+        })
+        .collect()
+}
+
+fn force_layout(count: usize, edges: &[(usize, usize)]) -> Vec<Vec2> {
+    // Start on a sunflower spiral so the result is the same on every run.
+    let mut points: Vec<Vec2> = (0..count)
+        .map(|i| {
+            let radius = ((i as f32 + 0.5) / count as f32).sqrt();
+            Vec2::from_angle(i as f32 * 2.399_963) * radius
+        })
+        .collect();
+    let k = (1.0 / count.max(1) as f32).sqrt();
+    let iterations = (FORCE_BUDGET / (count * count).max(1)).clamp(20, FORCE_ITERATIONS);
+    let mut temperature = 0.1;
+    let cooling = temperature / iterations as f32;
+
+    let mut displacement = vec![Vec2::ZERO; count];
+    for _ in 0..iterations {
+        displacement.fill(Vec2::ZERO);
+        for i in 0..count {
+            for j in i + 1..count {
+                let delta = points[i] - points[j];
+                let distance = delta.length().max(0.01);
+                let push = delta / distance * (k * k / distance);
+                displacement[i] += push;
+                displacement[j] -= push;
+            }
+        }
+        for &(a, b) in edges {
+            let delta = points[a] - points[b];
+            let distance = delta.length().max(0.01);
+            let pull = delta / distance * (distance * distance / k);
+            displacement[a] -= pull;
+            displacement[b] += pull;
+        }
+        for (point, shift) in points.iter_mut().zip(&displacement) {
+            *point += shift.clamp_length_max(temperature);
+        }
+        temperature -= cooling;
+    }
+    points
+}
+
+fn layered_layout(count: usize, edges: &[(usize, usize)]) -> Vec<Vec2> {
+    let mut outgoing = vec![Vec::new(); count];
+    let mut has_incoming = vec![false; count];
+    for &(a, b) in edges {
+        outgoing[a].push(b);
+        has_incoming[b] = true;
+    }
+    let mut layer = vec![usize::MAX; count];
+    // Roots first, then any node left unreached (e.g. inside a cycle) starts its own search.
+    let starts = (0..count)
+        .filter(|&i| !has_incoming[i])
+        .chain(0..count)
+        .collect::<Vec<_>>();
+    for start in starts {
+        if layer[start] != usize::MAX {
+            continue;
+        }
+        layer[start] = 0;
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            for &next in &outgoing[node] {
+                if layer[next] == usize::MAX {
+                    layer[next] = layer[node] + 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    let depth = layer.iter().copied().max().map_or(0, |d| d + 1);
+    let mut widths = vec![0usize; depth];
+    let mut slots = vec![0usize; count];
+    for (node, &l) in layer.iter().enumerate() {
+        slots[node] = widths[l];
+        widths[l] += 1;
+    }
+    (0..count)
+        .map(|node| {
+            let l = layer[node];
+            Vec2::new(
+                l as f32,
+                slots[node] as f32 - (widths[l] as f32 - 1.0) / 2.0,
+            )
+        })
+        .collect()
+}
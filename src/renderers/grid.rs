@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+
+use crate::{
+    renderers::{registry::StoryRenderer, tween::Tween},
+    search::{SEARCH_HIGHLIGHT, SearchResults},
+    story::{CellValue, FrameState, Playhead, Story},
+    visualization::{TaggedEntity, VisualizationKind},
+};
+
+const CELL_SPACING: f32 = 1.1;
+
+#[derive(Component)]
+pub struct GridCell {
+    pub x: usize,
+    pub y: usize,
+}
+
+/// Spawns one cube per cell of the story's largest frame, each with its own material so it can
+/// be recoloured independently.
+pub fn spawn_story_grid(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    story: &Story,
+) {
+    let (width, height) = story.bounds();
+    let mesh_handle = meshes.add(Cuboid::new(1.0, 1.0, 1.0));
+    let origin = Vec3::new(width as f32, 0.0, height as f32) * CELL_SPACING / 2.0;
+
+    for y in 0..height {
+        for x in 0..width {
+            let material = materials.add(StandardMaterial {
+                base_color: Color::linear_rgb(0.3, 0.7, 1.0),
+                metallic: 0.2,
+                ..default()
+            });
+            let transform = Transform::from_translation(
+                Vec3::new(x as f32, 0.0, y as f32) * CELL_SPACING - origin,
+            );
+            commands.spawn((
+                Mesh3d(mesh_handle.clone()),
+                MeshMaterial3d(material),
+                transform,
+                Tween::new(transform),
+                GlobalTransform::default(),
+                Visibility::default(),
+                GridCell { x, y },
+                TaggedEntity,
+            ));
+        }
+    }
+    info!("Spawned a {width}x{height} story grid");
+}
+
+/// Cubes raised and coloured by each cell, suited to text grids.
+pub struct GridVis;
+
+impl StoryRenderer for GridVis {
+    /// Any 2D state, read cell by cell.
+    type StateSnapshot = FrameState;
+
+    fn renderer_name(&self) -> &'static str {
+        "Cell grid"
+    }
+
+    fn kind(&self) -> VisualizationKind {
+        VisualizationKind::Grid
+    }
+
+    fn snapshot<'a>(&self, state: &'a FrameState) -> Option<&'a FrameState> {
+        matches!(
+            state,
+            FrameState::Text(_) | FrameState::Scalar(_) | FrameState::Array(_)
+        )
+        .then_some(state)
+    }
+
+    fn score(&self, story: &Story) -> u32 {
+        // Every frame is empty, there would be no cell to draw.
+        if matches!(story.bounds(), (0, _) | (_, 0)) {
+            return 0;
+        }
+        match story.first() {
+            Some(FrameState::Text(_)) => 100,
+            Some(FrameState::Scalar(_)) => 50,
+            _ => 10,
+        }
+    }
+}
+
+/// Eases the cells towards the current frame, highlighting those that differ from the previous
+/// frame.
+#[allow(clippy::too_many_arguments)]
+pub fn story_grid_system(
+    time: Res<Time>,
+    story: Res<Story>,
+    search: Option<Res<SearchResults>>,
+    mut playhead: ResMut<Playhead>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut cells: Query<(
+        &GridCell,
+        &Transform,
+        &mut Tween,
+        &MeshMaterial3d<StandardMaterial>,
+    )>,
+    mut range: Local<Option<(f32, f32)>>,
+) {
+    if story.is_changed() || range.is_none() {
+        *range = Some(story.value_range().unwrap_or((0.0, 1.0)));
+        playhead.rendered = None;
+    }
+    if search.as_ref().is_some_and(|s| s.is_changed()) {
+        playhead.rendered = None;
+    }
+    if playhead.rendered == Some(playhead.index) {
+        return;
+    }
+    let range = range.unwrap_or((0.0, 1.0));
+    let Some(current) = story.state(playhead.index) else {
+        return;
+    };
+    let previous = playhead.index.checked_sub(1).and_then(|i| story.state(i));
+    let found: HashSet<_> = search
+        .iter()
+        .flat_map(|s| s.cells_at(playhead.index))
+        .collect();
+
+    let now = time.elapsed_secs();
+    for (cell, transform, mut tween, material) in &mut cells {
+        let value = current.cell(cell.x, cell.y);
+        let changed = previous
+            .as_deref()
+            .is_some_and(|prev: &FrameState| prev.cell(cell.x, cell.y) != value);
+
+        let scale_y = cell_height(value, range);
+        let target = Transform {
+            translation: transform.translation.with_y(scale_y / 2.0),
+            scale: transform.scale.with_y(scale_y),
+            ..*transform
+        };
+        tween.retarget(transform, target, now);
+
+        if let Some(material) = materials.get_mut(&material.0) {
+            tween.retarget_color(material.base_color, cell_color(value, range));
+            material.emissive = if found.contains(&(cell.x, cell.y)) {
+                SEARCH_HIGHLIGHT
+            } else if changed {
+                LinearRgba::rgb(1.0, 0.6, 0.1)
+            } else {
+                LinearRgba::BLACK
+            };
+        }
+    }
+    playhead.rendered = Some(playhead.index);
+}
+
+fn normalize(value: f32, (lo, hi): (f32, f32)) -> f32 {
+    if hi > lo {
+        ((value - lo) / (hi - lo)).clamp(0.0, 1.0)
+    } else {
+        0.5
+    }
+}
+
+fn cell_height(value: CellValue, range: (f32, f32)) -> f32 {
+    match value {
+        CellValue::Empty => 0.05,
+        CellValue::Number(v) => 0.1 + normalize(v, range) * 2.0,
+        CellValue::Char(c) if c.is_whitespace() => 0.05,
+        CellValue::Char('.' | '_') => 0.1,
+        CellValue::Char(c) if c.is_ascii_digit() => 0.2 + c.to_digit(10).unwrap_or(0) as f32 * 0.2,
+        CellValue::Char(_) => 1.0,
+    }
+}
+
+fn cell_color(value: CellValue, range: (f32, f32)) -> Color {
+    match value {
+        CellValue::Empty => Color::srgb(0.1, 0.1, 0.1),
+        CellValue::Char(c) if c.is_whitespace() => Color::srgb(0.1, 0.1, 0.1),
+        // Spread neighbouring code points around the wheel so similar glyphs stay distinguishable.
+        CellValue::Char(c) => Color::hsl(((c as u32).wrapping_mul(47) % 360) as f32, 0.6, 0.5),
+        CellValue::Number(v) => Color::hsl(240.0 * (1.0 - normalize(v, range)), 0.7, 0.5),
+    }
+}
@@ -0,0 +1,344 @@
+use std::ops::RangeInclusive;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    image::ImageSampler,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    tasks::{AsyncComputeTaskPool, Task, futures::check_ready},
+    window::PrimaryWindow,
+};
+
+use crate::{
+    renderers::registry::StoryRenderer,
+    search::{SEARCH_HIGHLIGHT, SearchResults},
+    session::{Session, play_frames},
+    story::{FrameState, Playhead, Story},
+    visualization::{TaggedEntity, VisualizationKind},
+};
+
+/// World size of the longest side of the heatmap, whatever the matrix dimensions.
+const HEATMAP_EXTENT: f32 = 10.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Colormap {
+    #[default]
+    Viridis,
+    Magma,
+    Coolwarm,
+    Grayscale,
+}
+
+impl Colormap {
+    pub const ALL: [Colormap; 4] = [
+        Colormap::Viridis,
+        Colormap::Magma,
+        Colormap::Coolwarm,
+        Colormap::Grayscale,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Colormap::Viridis => "Viridis",
+            Colormap::Magma => "Magma",
+            Colormap::Coolwarm => "Coolwarm",
+            Colormap::Grayscale => "Grayscale",
+        }
+    }
+
+    fn stops(&self) -> &'static [[u8; 3]] {
+        match self {
+            Colormap::Viridis => &[
+                [68, 1, 84],
+                [59, 82, 139],
+                [33, 145, 140],
+                [94, 201, 98],
+                [253, 231, 37],
+            ],
+            Colormap::Magma => &[
+                [0, 0, 4],
+                [81, 18, 124],
+                [183, 55, 121],
+                [252, 137, 97],
+                [252, 253, 191],
+            ],
+            Colormap::Coolwarm => &[
+                [59, 76, 192],
+                [141, 176, 254],
+                [221, 221, 221],
+                [244, 154, 123],
+                [180, 4, 38],
+            ],
+            Colormap::Grayscale => &[[0, 0, 0], [255, 255, 255]],
+        }
+    }
+
+    /// Colour at `t` in `[0, 1]`, linearly interpolated between the map's stops.
+    pub fn sample(&self, t: f32) -> [u8; 3] {
+        let stops = self.stops();
+        let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let i = (scaled.floor() as usize).min(stops.len() - 2);
+        let f = scaled - i as f32;
+        let (a, b) = (stops[i], stops[i + 1]);
+        std::array::from_fn(|c| (a[c] as f32 + (b[c] as f32 - a[c] as f32) * f).round() as u8)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum RangeMode {
+    /// Range over the in/out range, or the whole story without one, so colours are comparable
+    /// between ticks.
+    #[default]
+    Story,
+    /// Range of the current frame only.
+    Frame,
+    Fixed {
+        min: f32,
+        max: f32,
+    },
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct HeatmapSettings {
+    pub colormap: Colormap,
+    pub range: RangeMode,
+    /// Range used by the last render, shown in the legend.
+    pub resolved_range: (f32, f32),
+}
+
+/// Cell under the cursor and its exact value, if any.
+#[derive(Resource, Debug, Default)]
+pub struct HeatmapHover(pub Option<(usize, usize, Option<f32>)>);
+
+#[derive(Component)]
+pub struct HeatmapQuad {
+    image: Handle<Image>,
+    width: usize,
+    height: usize,
+    size: Vec2,
+}
+
+/// A 2D scalar field drawn as a single textured quad, its image refilled on the CPU each tick.
+pub struct HeatmapVis;
+
+impl HeatmapVis {
+    pub fn spawn(
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        images: &mut Assets<Image>,
+        story: &Story,
+    ) {
+        let (width, height) = story.bounds();
+        let (width, height) = (width.max(1), height.max(1));
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: width as u32,
+                height: height as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 255],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        // Keep cells crisp when zoomed in.
+        image.sampler = ImageSampler::nearest();
+        let image = images.add(image);
+
+        let longest = width.max(height) as f32;
+        let size = Vec2::new(width as f32, height as f32) / longest * HEATMAP_EXTENT;
+        commands.spawn((
+            Mesh3d(meshes.add(Plane3d::default().mesh().size(size.x, size.y))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color_texture: Some(image.clone()),
+                unlit: true,
+                ..default()
+            })),
+            Transform::default(),
+            GlobalTransform::default(),
+            Visibility::default(),
+            HeatmapQuad {
+                image,
+                width,
+                height,
+                size,
+            },
+            TaggedEntity,
+        ));
+        commands.insert_resource(HeatmapHover::default());
+        info!("Spawned a {width}x{height} heatmap");
+    }
+}
+
+impl StoryRenderer for HeatmapVis {
+    /// Any 2D state, read cell by cell as numbers.
+    type StateSnapshot = FrameState;
+
+    fn renderer_name(&self) -> &'static str {
+        "Heatmap"
+    }
+
+    fn kind(&self) -> VisualizationKind {
+        VisualizationKind::Heatmap
+    }
+
+    fn snapshot<'a>(&self, state: &'a FrameState) -> Option<&'a FrameState> {
+        matches!(
+            state,
+            FrameState::Text(_) | FrameState::Scalar(_) | FrameState::Array(_)
+        )
+        .then_some(state)
+    }
+
+    fn score(&self, story: &Story) -> u32 {
+        // Every frame is empty, there would be no cell to draw.
+        if matches!(story.bounds(), (0, _) | (_, 0)) {
+            return 0;
+        }
+        match story.first() {
+            Some(FrameState::Scalar(_)) => 100,
+            Some(FrameState::Array(_)) => 30,
+            _ => 20,
+        }
+    }
+}
+
+/// The value range of the in/out frames, for [`RangeMode::Story`]. Measuring anything short of
+/// the whole story replays the history, so it runs on the async pool.
+#[derive(Default)]
+pub struct StoryRange {
+    frames: Option<RangeInclusive<usize>>,
+    range: Option<(f32, f32)>,
+    pending: Option<Task<Option<(f32, f32)>>>,
+}
+
+/// Refills the heatmap image when the frame, the settings or the in/out range change.
+#[allow(clippy::too_many_arguments)]
+pub fn heatmap_render_system(
+    story: Res<Story>,
+    search: Option<Res<SearchResults>>,
+    mut playhead: ResMut<Playhead>,
+    mut settings: ResMut<HeatmapSettings>,
+    mut images: ResMut<Assets<Image>>,
+    quad: Single<&HeatmapQuad>,
+    session: Option<Res<Session>>,
+    mut story_range: Local<StoryRange>,
+) {
+    let frames = play_frames(&story, session.as_deref());
+    if story.is_changed() || story_range.frames.as_ref() != Some(&frames) {
+        story_range.frames = Some(frames.clone());
+        if story.is_whole(&frames) {
+            story_range.pending = None;
+            story_range.range = story.value_range();
+            playhead.rendered = None;
+        } else {
+            // The previous range stays on screen until this one is measured.
+            let (history, _) = story.shared();
+            story_range.pending = Some(
+                AsyncComputeTaskPool::get().spawn(async move { history.value_range_in(frames) }),
+            );
+        }
+    }
+    if let Some(task) = story_range.pending.as_mut()
+        && let Some(range) = check_ready(task)
+    {
+        story_range.pending = None;
+        story_range.range = range;
+        playhead.rendered = None;
+    }
+    if search.as_ref().is_some_and(|s| s.is_changed()) {
+        playhead.rendered = None;
+    }
+    if playhead.rendered == Some(playhead.index) && !settings.is_changed() {
+        return;
+    }
+    let Some(frame) = story.state(playhead.index) else {
+        return;
+    };
+    let frame = &*frame;
+    let (lo, hi) = match settings.range {
+        RangeMode::Story => story_range.range.unwrap_or((0.0, 1.0)),
+        RangeMode::Frame => frame.value_range().unwrap_or((0.0, 1.0)),
+        RangeMode::Fixed { min, max } => (min, max),
+    };
+    let Some(data) = images.get_mut(&quad.image).and_then(|i| i.data.as_mut()) else {
+        return;
+    };
+
+    let highlight = Srgba::from(SEARCH_HIGHLIGHT).to_u8_array_no_alpha();
+    for y in 0..quad.height {
+        for x in 0..quad.width {
+            let pixel = match cell_number(frame, x, y) {
+                Some(v) if hi > lo => settings.colormap.sample((v - lo) / (hi - lo)),
+                Some(_) => settings.colormap.sample(0.5),
+                None => [0, 0, 0],
+            };
+            let offset = (y * quad.width + x) * 4;
+            data[offset..offset + 3].copy_from_slice(&pixel);
+            data[offset + 3] = 255;
+        }
+    }
+    for (x, y) in search.iter().flat_map(|s| s.cells_at(playhead.index)) {
+        if x < quad.width && y < quad.height {
+            let offset = (y * quad.width + x) * 4;
+            data[offset..offset + 3].copy_from_slice(&highlight);
+        }
+    }
+    // Only touch the settings when the range actually moved, to keep change detection quiet.
+    if settings.resolved_range != (lo, hi) {
+        settings.resolved_range = (lo, hi);
+    }
+    playhead.rendered = Some(playhead.index);
+}
+
+/// Casts the cursor onto the heatmap plane to find the hovered cell.
+pub fn heatmap_hover_system(
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform), With<Camera3d>>,
+    quad: Single<(&HeatmapQuad, &GlobalTransform)>,
+    story: Res<Story>,
+    playhead: Res<Playhead>,
+    mut hover: ResMut<HeatmapHover>,
+) {
+    let (camera, camera_transform) = *camera;
+    let (quad, quad_transform) = *quad;
+    hover.0 = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor).ok())
+        .and_then(|ray| {
+            let origin = quad_transform.translation();
+            let distance = ray.intersect_plane(origin, InfinitePlane3d::new(Vec3::Y))?;
+            let local = ray.get_point(distance) - origin;
+            let uv = (Vec2::new(local.x, local.z) + quad.size / 2.0) / quad.size;
+            if !(0.0..1.0).contains(&uv.x) || !(0.0..1.0).contains(&uv.y) {
+                return None;
+            }
+            let x = (uv.x * quad.width as f32) as usize;
+            let y = (uv.y * quad.height as f32) as usize;
+            let value = story
+                .state(playhead.index)
+                .and_then(|state| cell_number(&state, x, y));
+            Some((x, y, value))
+        });
+}
+
+fn cell_number(frame: &FrameState, x: usize, y: usize) -> Option<f32> {
+    frame.cell(x, y).as_number()
+}
+
+pub fn setup_heatmap(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    story: Res<Story>,
+) {
+    HeatmapVis::spawn(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut images,
+        &story,
+    );
+}
@@ -0,0 +1,7 @@
+pub mod array;
+pub mod graph;
+pub mod grid;
+pub mod heatmap;
+pub mod registry;
+pub mod tween;
+pub mod volume;
@@ -0,0 +1,128 @@
+//! Renderers known to the app, and how well each one suits a loaded story.
+
+use std::any::type_name;
+
+use bevy::prelude::*;
+
+use crate::{
+    story::{FrameState, Story},
+    visualization::{LoadVisualization, VisualizationKind},
+};
+
+/// A renderer as seen by the registry: what it draws and how much it wants a story.
+///
+/// This mirrors `storyframe::Renderer` rather than extending it. A storyframe renderer is pushed
+/// snapshots by its engine through `render_state` and a render context, while stories are
+/// replayed from a [`crate::history::StateHistory`] and drawn by Bevy systems reading the
+/// playhead. What the registry needs is only whether a renderer can read a frame and how well it
+/// fits, which `storyframe::Renderer` has no way to say. `SimpleGrid`, the one storyframe
+/// renderer, draws engine output and is never chosen for a story, so it is not registered.
+pub trait StoryRenderer: Send + Sync + 'static {
+    /// The part of a frame the renderer reads.
+    type StateSnapshot: ?Sized;
+
+    fn renderer_name(&self) -> &'static str;
+
+    fn kind(&self) -> VisualizationKind;
+
+    /// The snapshot this renderer draws from `state`, `None` when it cannot draw it.
+    fn snapshot<'a>(&self, state: &'a FrameState) -> Option<&'a Self::StateSnapshot>;
+
+    /// How well the renderer suits `story`, higher is better. Only asked once the first frame
+    /// has a snapshot. Reads the measures cached on the story, such as its bounds, rather than
+    /// replaying its states.
+    fn score(&self, story: &Story) -> u32;
+}
+
+/// Object-safe side of [`StoryRenderer`], so renderers with different snapshots share a list.
+trait ErasedRenderer: Send + Sync {
+    fn candidate(&self, story: &Story) -> Option<RendererCandidate>;
+}
+
+/// Stories hold one kind of state throughout, so the first frame stands for all of them. A
+/// frame that does not fit anyway is reported by the renderer when it is reached.
+impl<R: StoryRenderer> ErasedRenderer for R {
+    fn candidate(&self, story: &Story) -> Option<RendererCandidate> {
+        story.first().and_then(|state| self.snapshot(state))?;
+        let score = self.score(story);
+        (score > 0).then(|| RendererCandidate {
+            name: self.renderer_name(),
+            kind: self.kind(),
+            snapshot: type_name::<R::StateSnapshot>(),
+            score,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RendererCandidate {
+    pub name: &'static str,
+    pub kind: VisualizationKind,
+    pub snapshot: &'static str,
+    pub score: u32,
+}
+
+#[derive(Resource, Default)]
+pub struct RendererRegistry {
+    renderers: Vec<Box<dyn ErasedRenderer>>,
+}
+
+impl RendererRegistry {
+    pub fn with(mut self, renderer: impl StoryRenderer) -> Self {
+        self.renderers.push(Box::new(renderer));
+        self
+    }
+
+    /// Renderers able to draw `story`, best first. Ties keep registration order.
+    pub fn candidates(&self, story: &Story) -> Vec<RendererCandidate> {
+        let mut candidates: Vec<_> = self
+            .renderers
+            .iter()
+            .filter_map(|r| r.candidate(story))
+            .collect();
+        candidates.sort_by(|a, b| b.score.cmp(&a.score));
+        candidates
+    }
+}
+
+/// Renderers compatible with the current story, computed once when it is loaded.
+#[derive(Resource, Debug, Default)]
+pub struct RendererCandidates(pub Vec<RendererCandidate>);
+
+/// Sent once a story is in place. Carries the renderer picked by the user, if any.
+#[derive(Message)]
+pub struct SelectRenderer(pub Option<VisualizationKind>);
+
+/// Loads the requested renderer when it can draw the story, the best compatible one otherwise.
+pub fn select_renderer_system(
+    mut commands: Commands,
+    mut events: MessageReader<SelectRenderer>,
+    mut writer: MessageWriter<LoadVisualization>,
+    registry: Res<RendererRegistry>,
+    story: Res<Story>,
+) {
+    let Some(SelectRenderer(requested)) = events.read().last() else {
+        return;
+    };
+    let candidates = registry.candidates(&story);
+    let kind = match (requested, candidates.first()) {
+        (Some(kind), _) if candidates.iter().any(|c| c.kind == *kind) => *kind,
+        (requested, Some(best)) => {
+            if let Some(kind) = requested {
+                warn!(
+                    "{} cannot draw this story, using {}",
+                    kind.label(),
+                    best.name
+                );
+            }
+            best.kind
+        }
+        (requested, None) => {
+            warn!("No registered renderer accepts this story");
+            requested.unwrap_or_default()
+        }
+    };
+    info!("Rendering {} with {}", story.source.display(), kind.label());
+    commands.insert_resource(RendererCandidates(candidates));
+    writer.write(LoadVisualization(kind));
+}
@@ -0,0 +1,86 @@
+//! Eases renderer entities from what they show towards the state of the current frame.
+//!
+//! The grid, array and graph renderers ease through [`Tween`]. The heatmap and volume renderers
+//! redraw textures and swap palette materials, so they snap to each state.
+
+use bevy::{color::Mix, prelude::*};
+
+use crate::config::AnimationConfig;
+
+/// Where an entity's transform, and optionally its material colour, is heading. Renderers
+/// retarget it when the frame changes and [`tween_system`] covers the distance over one tick.
+#[derive(Component, Debug, Clone)]
+pub struct Tween {
+    from: Transform,
+    to: Transform,
+    colors: Option<(LinearRgba, LinearRgba)>,
+    started: f32,
+    done: bool,
+}
+
+impl Tween {
+    /// Already settled on `transform`.
+    pub fn new(transform: Transform) -> Self {
+        Self {
+            from: transform,
+            to: transform,
+            colors: None,
+            started: 0.0,
+            done: true,
+        }
+    }
+
+    /// Starts over from `current`, so changing the target halfway does not jump.
+    pub fn retarget(&mut self, current: &Transform, to: Transform, now: f32) {
+        self.from = *current;
+        self.to = to;
+        self.started = now;
+        self.done = false;
+    }
+
+    /// Fades the material from `current` to `to` alongside the transform.
+    pub fn retarget_color(&mut self, current: Color, to: Color) {
+        self.colors = Some((current.into(), to.into()));
+        self.done = false;
+    }
+}
+
+pub fn tween_system(
+    time: Res<Time>,
+    config: Res<AnimationConfig>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tweens: Query<(
+        &mut Tween,
+        &mut Transform,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+    )>,
+) {
+    let now = time.elapsed_secs();
+    for (mut tween, mut transform, material) in &mut tweens {
+        if tween.done {
+            continue;
+        }
+        let t = (now - tween.started) / config.tick_seconds();
+        let finished = config.reduce_motion || t >= 1.0;
+        let eased = if finished {
+            1.0
+        } else {
+            config.interpolation.apply(t)
+        };
+        let (from, to) = (tween.from, tween.to);
+        transform.translation = from.translation.lerp(to.translation, eased);
+        transform.scale = from.scale.lerp(to.scale, eased);
+        transform.rotation = from.rotation.slerp(to.rotation, eased);
+        if let Some((from, to)) = tween.colors
+            && let Some(material) = material
+            && let Some(material) = materials.get_mut(&material.0)
+        {
+            // Overshooting easings must not push colours out of gamut.
+            material.base_color = from.mix(&to, eased.clamp(0.0, 1.0)).into();
+        }
+        if finished {
+            tween.done = true;
+            tween.colors = None;
+        }
+    }
+}
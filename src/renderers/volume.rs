@@ -0,0 +1,380 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    image::ImageSampler,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{
+    error::{ReportError, unexpected_frame},
+    renderers::{heatmap::Colormap, registry::StoryRenderer},
+    story::{FrameState, Playhead, ScalarVolume, Story},
+    visualization::{FrameBounds, TaggedEntity, VisualizationKind},
+};
+
+/// World size of the longest side of the volume, whatever its dimensions.
+const VOLUME_EXTENT: f32 = 10.0;
+/// Above this many voxels the voxel view is not spawned; slices still work.
+const MAX_VOXELS: usize = 64 * 64 * 64;
+/// Voxel colours are quantized to this many shared materials.
+const PALETTE_SIZE: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+    fn index(self) -> usize {
+        match self {
+            Axis::X => 0,
+            Axis::Y => 1,
+            Axis::Z => 2,
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct VolumeSettings {
+    pub colormap: Colormap,
+    pub show_slices: [bool; 3],
+    /// Slice position along each axis, in voxels.
+    pub slices: [usize; 3],
+    pub show_voxels: bool,
+    /// Voxels below this value are hidden.
+    pub threshold: f32,
+}
+
+impl Default for VolumeSettings {
+    fn default() -> Self {
+        Self {
+            colormap: Colormap::default(),
+            show_slices: [false, true, false],
+            slices: [0; 3],
+            show_voxels: false,
+            threshold: 0.5,
+        }
+    }
+}
+
+/// Dimensions and value range of the loaded volume, for the settings panel.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct VolumeInfo {
+    pub dims: [usize; 3],
+    pub range: (f32, f32),
+    pub voxel_size: f32,
+    pub voxels_available: bool,
+}
+
+impl VolumeInfo {
+    fn position(&self, axis: Axis, index: usize) -> f32 {
+        let i = axis.index();
+        (index as f32 + 0.5 - self.dims[i] as f32 / 2.0) * self.voxel_size
+    }
+}
+
+#[derive(Component)]
+pub struct VolumeSlice {
+    axis: Axis,
+    image: Handle<Image>,
+}
+
+#[derive(Component)]
+pub struct Voxel {
+    x: usize,
+    y: usize,
+    z: usize,
+}
+
+#[derive(Resource)]
+pub struct VoxelPalette(Vec<Handle<StandardMaterial>>);
+
+/// A 3D scalar field shown through scrubbable axis-aligned slices and a thresholded voxel view.
+pub struct VolumeVis;
+
+impl VolumeVis {
+    pub fn spawn(
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+        materials: &mut Assets<StandardMaterial>,
+        images: &mut Assets<Image>,
+        settings: &mut VolumeSettings,
+        story: &Story,
+    ) {
+        let (width, height, depth) = story.volume_bounds();
+        let dims = [width.max(1), height.max(1), depth.max(1)];
+        let voxel_size = VOLUME_EXTENT / *dims.iter().max().unwrap_or(&1) as f32;
+        let info = VolumeInfo {
+            dims,
+            range: story.value_range().unwrap_or((0.0, 1.0)),
+            voxel_size,
+            voxels_available: dims.iter().product::<usize>() <= MAX_VOXELS,
+        };
+        settings.slices = dims.map(|d| d / 2);
+        settings.threshold = (info.range.0 + info.range.1) / 2.0;
+
+        for axis in Axis::ALL {
+            // Each slice shows the two other axes; y always runs top to bottom in the image.
+            let (cols, rows, rotation) = match axis {
+                Axis::X => (dims[2], dims[1], Quat::from_rotation_y(-FRAC_PI_2)),
+                Axis::Y => (dims[0], dims[2], Quat::from_rotation_x(-FRAC_PI_2)),
+                Axis::Z => (dims[0], dims[1], Quat::IDENTITY),
+            };
+            let mut image = Image::new_fill(
+                Extent3d {
+                    width: cols as u32,
+                    height: rows as u32,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &[0, 0, 0, 255],
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::default(),
+            );
+            image.sampler = ImageSampler::nearest();
+            let image = images.add(image);
+            commands.spawn((
+                Mesh3d(meshes.add(Rectangle::new(
+                    cols as f32 * voxel_size,
+                    rows as f32 * voxel_size,
+                ))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color_texture: Some(image.clone()),
+                    unlit: true,
+                    double_sided: true,
+                    cull_mode: None,
+                    ..default()
+                })),
+                Transform::from_rotation(rotation),
+                GlobalTransform::default(),
+                Visibility::default(),
+                VolumeSlice { axis, image },
+                TaggedEntity,
+            ));
+        }
+
+        if info.voxels_available {
+            let palette: Vec<_> = (0..PALETTE_SIZE)
+                .map(|i| {
+                    let [r, g, b] = settings
+                        .colormap
+                        .sample(i as f32 / (PALETTE_SIZE - 1) as f32);
+                    materials.add(StandardMaterial {
+                        base_color: Color::srgb_u8(r, g, b),
+                        ..default()
+                    })
+                })
+                .collect();
+            let mesh = meshes.add(Cuboid::from_length(voxel_size * 0.95));
+            for z in 0..dims[2] {
+                for y in 0..dims[1] {
+                    for x in 0..dims[0] {
+                        commands.spawn((
+                            Mesh3d(mesh.clone()),
+                            MeshMaterial3d(palette[0].clone()),
+                            Transform::from_xyz(
+                                info.position(Axis::X, x),
+                                info.position(Axis::Y, y),
+                                info.position(Axis::Z, z),
+                            ),
+                            GlobalTransform::default(),
+                            Visibility::Hidden,
+                            Voxel { x, y, z },
+                            TaggedEntity,
+                        ));
+                    }
+                }
+            }
+            commands.insert_resource(VoxelPalette(palette));
+        } else {
+            warn!(
+                "Volume has more than {MAX_VOXELS} voxels, the voxel view is disabled. Use the slices instead."
+            );
+        }
+
+        commands.spawn((
+            DirectionalLight {
+                illuminance: 10_000.0,
+                ..default()
+            },
+            Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+            GlobalTransform::default(),
+            TaggedEntity,
+        ));
+        commands.insert_resource(info);
+        commands.write_message(FrameBounds {
+            center: Vec3::ZERO,
+            half_extents: Vec3::new(dims[0] as f32, dims[1] as f32, dims[2] as f32) * voxel_size
+                / 2.0,
+        });
+        info!("Spawned a {}x{}x{} volume", dims[0], dims[1], dims[2]);
+    }
+}
+
+impl StoryRenderer for VolumeVis {
+    type StateSnapshot = ScalarVolume;
+
+    fn renderer_name(&self) -> &'static str {
+        "Volume slices"
+    }
+
+    fn kind(&self) -> VisualizationKind {
+        VisualizationKind::Volume
+    }
+
+    fn snapshot<'a>(&self, state: &'a FrameState) -> Option<&'a ScalarVolume> {
+        match state {
+            FrameState::Volume(volume) => Some(volume),
+            _ => None,
+        }
+    }
+
+    fn score(&self, _story: &Story) -> u32 {
+        100
+    }
+}
+
+pub fn setup_volume(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    mut settings: ResMut<VolumeSettings>,
+    story: Res<Story>,
+) {
+    VolumeVis::spawn(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &mut images,
+        &mut settings,
+        &story,
+    );
+}
+
+/// Refreshes slices and voxels when the frame or the settings change.
+pub fn volume_render_system(
+    story: Res<Story>,
+    mut playhead: ResMut<Playhead>,
+    settings: Res<VolumeSettings>,
+    info: Res<VolumeInfo>,
+    palette: Option<Res<VoxelPalette>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    mut errors: MessageWriter<ReportError>,
+    mut slices: Query<(&VolumeSlice, &mut Transform, &mut Visibility), Without<Voxel>>,
+    mut voxels: Query<(
+        &Voxel,
+        &mut Visibility,
+        &mut MeshMaterial3d<StandardMaterial>,
+    )>,
+) {
+    if playhead.rendered == Some(playhead.index) && !settings.is_changed() {
+        return;
+    }
+    let state = story.state(playhead.index);
+    let Some(FrameState::Volume(volume)) = state.as_deref() else {
+        errors.write(ReportError(unexpected_frame(
+            &story,
+            playhead.index,
+            "volume",
+        )));
+        return;
+    };
+    let (lo, hi) = info.range;
+    let normalize = |v: f32| if hi > lo { (v - lo) / (hi - lo) } else { 0.5 };
+
+    for (slice, mut transform, mut visibility) in &mut slices {
+        let i = slice.axis.index();
+        *visibility = if settings.show_slices[i] {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        let index = settings.slices[i].min(info.dims[i] - 1);
+        let offset = info.position(slice.axis, index);
+        transform.translation = match slice.axis {
+            Axis::X => Vec3::X * offset,
+            Axis::Y => Vec3::Y * offset,
+            Axis::Z => Vec3::Z * offset,
+        };
+        if settings.show_slices[i] {
+            fill_slice(
+                &mut images,
+                slice,
+                volume,
+                index,
+                &settings.colormap,
+                normalize,
+            );
+        }
+    }
+
+    if let Some(palette) = palette {
+        if settings.is_changed() {
+            for (i, handle) in palette.0.iter().enumerate() {
+                if let Some(material) = materials.get_mut(handle) {
+                    let [r, g, b] = settings
+                        .colormap
+                        .sample(i as f32 / (PALETTE_SIZE - 1) as f32);
+                    material.base_color = Color::srgb_u8(r, g, b);
+                }
+            }
+        }
+        for (voxel, mut visibility, mut material) in &mut voxels {
+            let value = volume
+                .get(voxel.x, voxel.y, voxel.z)
+                .filter(|v| settings.show_voxels && *v >= settings.threshold);
+            let Some(value) = value else {
+                *visibility = Visibility::Hidden;
+                continue;
+            };
+            *visibility = Visibility::Visible;
+            let shade = (normalize(value).clamp(0.0, 1.0) * (PALETTE_SIZE - 1) as f32) as usize;
+            if material.0 != palette.0[shade] {
+                material.0 = palette.0[shade].clone();
+            }
+        }
+    }
+    playhead.rendered = Some(playhead.index);
+}
+
+fn fill_slice(
+    images: &mut Assets<Image>,
+    slice: &VolumeSlice,
+    volume: &ScalarVolume,
+    index: usize,
+    colormap: &Colormap,
+    normalize: impl Fn(f32) -> f32,
+) {
+    let Some(image) = images.get_mut(&slice.image) else {
+        return;
+    };
+    let cols = image.width() as usize;
+    let rows = image.height() as usize;
+    let Some(data) = image.data.as_mut() else {
+        return;
+    };
+    for row in 0..rows {
+        for col in 0..cols {
+            // Map image coordinates back to voxel coordinates, see the rotations in `spawn`.
+            let (x, y, z) = match slice.axis {
+                Axis::X => (index, rows - 1 - row, col),
+                Axis::Y => (col, index, row),
+                Axis::Z => (col, rows - 1 - row, index),
+            };
+            let pixel = volume
+                .get(x, y, z)
+                .map_or([0, 0, 0], |v| colormap.sample(normalize(v)));
+            let offset = (row * cols + col) * 4;
+            data[offset..offset + 3].copy_from_slice(&pixel);
+            data[offset + 3] = 255;
+        }
+    }
+}
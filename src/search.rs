@@ -0,0 +1,285 @@
+//! Scans every state of the story on the async compute pool for a value, a regex or a
+//! condition, without blocking playback.
+
+use std::{collections::HashSet, ops::RangeInclusive, sync::Arc};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, futures::check_ready},
+};
+use regex::Regex;
+
+use crate::{
+    breakpoints::expr::{Expr, Scope, Value},
+    loading::LoadProgress,
+    story::{CellValue, FrameState, Story},
+};
+
+/// Stop collecting past this many matches, the list would be useless anyway.
+pub const MAX_MATCHES: usize = 10_000;
+
+/// Colour of the cells matched on the current frame, in the renderers and the panel.
+pub const SEARCH_HIGHLIGHT: LinearRgba = LinearRgba::rgb(0.9, 0.2, 0.9);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// A number or a piece of text, compared with each cell.
+    #[default]
+    Value,
+    /// A regular expression over the text of each state.
+    Regex,
+    /// A breakpoint condition, per cell when it reads `cell`, `x` or `y`, per state otherwise.
+    Predicate,
+}
+
+impl SearchMode {
+    pub const ALL: [SearchMode; 3] = [SearchMode::Value, SearchMode::Regex, SearchMode::Predicate];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchMode::Value => "Value",
+            SearchMode::Regex => "Regex",
+            SearchMode::Predicate => "Condition",
+        }
+    }
+}
+
+pub enum SearchQuery {
+    Value(Value),
+    Regex(Regex),
+    Predicate(Expr),
+}
+
+impl SearchQuery {
+    pub fn parse(mode: SearchMode, text: &str) -> Result<Self, String> {
+        if text.trim().is_empty() {
+            return Err("nothing to search for".to_string());
+        }
+        match mode {
+            SearchMode::Value => Ok(SearchQuery::Value(
+                text.trim()
+                    .parse()
+                    .map(Value::Number)
+                    .unwrap_or_else(|_| Value::Text(text.to_string())),
+            )),
+            SearchMode::Regex => Regex::new(text)
+                .map(SearchQuery::Regex)
+                .map_err(|e| e.to_string()),
+            SearchMode::Predicate => Expr::parse(text).map(SearchQuery::Predicate),
+        }
+    }
+
+    /// Matching cells of `state`, or `None` for a match on the state as a whole.
+    fn matches(
+        &self,
+        state: &FrameState,
+        previous: Option<&FrameState>,
+        tick: u64,
+        index: usize,
+    ) -> Vec<Option<(usize, usize)>> {
+        let scope = Scope::new(state, previous, tick, index);
+        match self {
+            SearchQuery::Value(value) => match state {
+                FrameState::Graph(graph) => graph
+                    .nodes
+                    .iter()
+                    .any(|n| {
+                        value.equals(&Value::Text(n.id.clone()))
+                            || n.state
+                                .as_ref()
+                                .is_some_and(|s| value.equals(&Value::Text(s.clone())))
+                            || n.weight
+                                .is_some_and(|w| value.equals(&Value::Number(f64::from(w))))
+                    })
+                    .then_some(None)
+                    .into_iter()
+                    .collect(),
+                state => scope
+                    .cells()
+                    .filter(|&(x, y)| value.equals(&state.cell(x, y).into()))
+                    .map(Some)
+                    .collect(),
+            },
+            SearchQuery::Regex(regex) => match state {
+                FrameState::Text(grid) => grid
+                    .rows
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(y, row)| {
+                        let line: String = row.iter().collect();
+                        regex
+                            .find_iter(&line)
+                            .map(|m| Some((line[..m.start()].chars().count(), y)))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect(),
+                FrameState::Graph(graph) => graph
+                    .nodes
+                    .iter()
+                    .any(|n| {
+                        regex.is_match(&format!("{} {}", n.id, n.state.as_deref().unwrap_or("")))
+                    })
+                    .then_some(None)
+                    .into_iter()
+                    .collect(),
+                state => scope
+                    .cells()
+                    .filter(|&(x, y)| match state.cell(x, y) {
+                        CellValue::Empty => false,
+                        CellValue::Char(c) => regex.is_match(c.encode_utf8(&mut [0; 4])),
+                        CellValue::Number(v) => regex.is_match(&v.to_string()),
+                    })
+                    .map(Some)
+                    .collect(),
+            },
+            SearchQuery::Predicate(expr) if expr.uses_cell() => scope
+                .cells()
+                .filter(|&cell| matches!(expr.eval(&scope.at(cell)), Ok(Value::Bool(true))))
+                .map(Some)
+                .collect(),
+            SearchQuery::Predicate(expr) => matches!(expr.eval(&scope), Ok(Value::Bool(true)))
+                .then_some(None)
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub index: usize,
+    pub tick: u64,
+    pub cell: Option<(usize, usize)>,
+}
+
+/// Matches of the last search, in frame order.
+#[derive(Resource, Debug, Default)]
+pub struct SearchResults {
+    pub query: String,
+    pub matches: Vec<SearchMatch>,
+    /// More matches were found than [`MAX_MATCHES`].
+    pub truncated: bool,
+    /// Match selected with next/previous, or by clicking.
+    pub selected: Option<usize>,
+}
+
+impl SearchResults {
+    /// Matching cells of frame `index`.
+    pub fn cells_at(&self, index: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let start = self.matches.partition_point(|m| m.index < index);
+        self.matches[start..]
+            .iter()
+            .take_while(move |m| m.index == index)
+            .filter_map(|m| m.cell)
+    }
+
+    /// First match after frame `index`, or before it when `backwards`, wrapping around.
+    pub fn step_from(&self, index: usize, backwards: bool) -> Option<usize> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let last = self.matches.len() - 1;
+        Some(if backwards {
+            match self.matches.partition_point(|m| m.index < index) {
+                0 => last,
+                i => i - 1,
+            }
+        } else {
+            match self.matches.partition_point(|m| m.index <= index) {
+                i if i > last => 0,
+                i => i,
+            }
+        })
+    }
+}
+
+/// A search running on the async compute pool, see [`SearchTask::spawn`].
+#[derive(Resource)]
+pub struct SearchTask {
+    task: Task<Option<SearchResults>>,
+    pub progress: Arc<LoadProgress>,
+}
+
+impl SearchTask {
+    /// Searches the states of `frames`. With `onset`, a cell only matches on the frames where it
+    /// starts matching, which answers "when did this cell first become 7".
+    pub fn spawn(
+        story: &Story,
+        text: String,
+        query: SearchQuery,
+        onset: bool,
+        frames: RangeInclusive<usize>,
+    ) -> Self {
+        let progress = Arc::new(LoadProgress::default());
+        // Earlier states are still replayed to rebuild the first one searched.
+        progress.set_total(*frames.end() as u64 + 1);
+        let task_progress = progress.clone();
+        let (history, ticks) = story.shared();
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            let mut results = SearchResults {
+                query: text,
+                ..default()
+            };
+            let mut index = 0;
+            let mut previous: Option<FrameState> = None;
+            let mut before = HashSet::new();
+            let predicate = matches!(query, SearchQuery::Predicate(_));
+            history.all(|state| {
+                if index > *frames.end() {
+                    return false;
+                }
+                if index < *frames.start() {
+                    if predicate && index + 1 == *frames.start() {
+                        previous = Some(state.clone());
+                    }
+                    index += 1;
+                    task_progress.advance(1);
+                    return !task_progress.is_cancelled();
+                }
+                let tick = ticks.get(index).copied().unwrap_or_default();
+                let found: HashSet<_> = query
+                    .matches(state, previous.as_ref(), tick, index)
+                    .into_iter()
+                    .collect();
+                let mut cells: Vec<_> = found
+                    .iter()
+                    .filter(|cell| !onset || !before.contains(*cell))
+                    .copied()
+                    .collect();
+                cells.sort_by_key(|cell| cell.map(|(x, y)| (y, x)));
+                for cell in cells {
+                    if results.matches.len() == MAX_MATCHES {
+                        results.truncated = true;
+                        break;
+                    }
+                    results.matches.push(SearchMatch { index, tick, cell });
+                }
+                before = found;
+                // Only conditions reading the previous state need a copy of it.
+                if predicate {
+                    previous = Some(state.clone());
+                }
+                index += 1;
+                task_progress.advance(1);
+                !results.truncated && !task_progress.is_cancelled()
+            });
+            (!task_progress.is_cancelled()).then_some(results)
+        });
+        Self { task, progress }
+    }
+}
+
+pub fn poll_search_task(mut commands: Commands, mut search: ResMut<SearchTask>) {
+    let Some(results) = check_ready(&mut search.task) else {
+        return;
+    };
+    commands.remove_resource::<SearchTask>();
+    if let Some(results) = results {
+        info!(
+            "Search for {} found {} matches",
+            results.query,
+            results.matches.len()
+        );
+        commands.insert_resource(results);
+    }
+}
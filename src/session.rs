@@ -0,0 +1,321 @@
+//! Per-story state saved in the per-user data directory, keyed by the story's path, and restored
+//! when the same file is loaded again. Story folders are left untouched, they may be read-only
+//! or shared.
+
+use std::{
+    env, fs, io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    prelude::*,
+    window::{WindowCloseRequested, WindowFocused},
+};
+use serde_json::{Value, json};
+
+use crate::{
+    camera::OrbitCamera,
+    story::{LoopMode, Story},
+    visualization::CloseVisualization,
+};
+
+/// Edits are written once they have settled for this many seconds.
+const SAVE_DELAY: f32 = 1.0;
+
+/// A user-created mark on the timeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub tick: u64,
+    pub name: String,
+    pub color: [u8; 3],
+    pub note: String,
+}
+
+/// Cycled through as bookmarks are added, the user can recolour them afterwards.
+const BOOKMARK_COLORS: [[u8; 3]; 6] = [
+    [90, 170, 255],
+    [255, 120, 90],
+    [120, 220, 120],
+    [220, 120, 220],
+    [255, 200, 80],
+    [80, 220, 220],
+];
+
+impl Bookmark {
+    /// A fresh bookmark, named and coloured after the `count` bookmarks already there.
+    pub fn new(tick: u64, count: usize) -> Self {
+        Self {
+            tick,
+            name: format!("Bookmark {}", count + 1),
+            color: BOOKMARK_COLORS[count % BOOKMARK_COLORS.len()],
+            note: String::new(),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "tick": self.tick,
+            "name": self.name,
+            "color": self.color,
+            "note": self.note,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        let color = value.get("color")?.as_array()?;
+        let channel = |i: usize| color.get(i)?.as_u64().map(|c| c.min(255) as u8);
+        Some(Self {
+            tick: value.get("tick")?.as_u64()?,
+            name: value.get("name")?.as_str()?.to_string(),
+            color: [channel(0)?, channel(1)?, channel(2)?],
+            note: value
+                .get("note")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        })
+    }
+}
+
+/// A named camera view, saved from the camera panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraBookmark {
+    pub name: String,
+    pub view: OrbitCamera,
+}
+
+impl CameraBookmark {
+    fn to_json(&self) -> Value {
+        let OrbitCamera {
+            radius,
+            yaw,
+            pitch,
+            target,
+        } = self.view;
+        json!({
+            "name": self.name,
+            "target": target.to_array(),
+            "radius": radius,
+            "yaw": yaw,
+            "pitch": pitch,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        let number = |key: &str| value.get(key)?.as_f64().map(|v| v as f32);
+        let target = value.get("target")?.as_array()?;
+        let axis = |i: usize| target.get(i)?.as_f64().map(|v| v as f32);
+        Some(Self {
+            name: value.get("name")?.as_str()?.to_string(),
+            view: OrbitCamera {
+                radius: number("radius")?,
+                yaw: number("yaw")?,
+                pitch: number("pitch")?,
+                target: Vec3::new(axis(0)?, axis(1)?, axis(2)?),
+            },
+        })
+    }
+}
+
+/// What is remembered about the story currently loaded.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct Session {
+    /// Kept in tick order.
+    pub bookmarks: Vec<Bookmark>,
+    pub loop_mode: LoopMode,
+    /// In and out points, as ticks so they survive a reload of a story that grew.
+    pub range: Option<(u64, u64)>,
+    /// Saved camera views, in the order they were saved.
+    pub views: Vec<CameraBookmark>,
+}
+
+impl Session {
+    pub fn add_bookmark(&mut self, bookmark: Bookmark) {
+        let at = self.bookmarks.partition_point(|b| b.tick <= bookmark.tick);
+        self.bookmarks.insert(at, bookmark);
+    }
+
+    /// Moves the in point to `tick`, pushing the out point along when it would come first.
+    pub fn set_in(&mut self, tick: u64, story: &Story) {
+        let out = self.range.map_or(last_tick(story), |(_, out)| out);
+        self.range = Some((tick, out.max(tick)));
+    }
+
+    /// Moves the out point to `tick`, pulling the in point along when it would come after.
+    pub fn set_out(&mut self, tick: u64, story: &Story) {
+        let start = self.range.map_or(first_tick(story), |(start, _)| start);
+        self.range = Some((start.min(tick), tick));
+    }
+
+    /// Frames playback is confined to: the in/out range when set, the whole story otherwise.
+    pub fn frames(&self, story: &Story) -> RangeInclusive<usize> {
+        match self.range {
+            Some((start, out)) => story.index_of_tick(start)..=story.index_of_tick(out),
+            None => 0..=story.len().saturating_sub(1),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "bookmarks": self.bookmarks.iter().map(Bookmark::to_json).collect::<Vec<_>>(),
+            "loop": self.loop_mode.key(),
+            "range": self.range.map(|(start, out)| [start, out]),
+            "views": self.views.iter().map(CameraBookmark::to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    fn from_json(value: &Value) -> Self {
+        let mut bookmarks: Vec<Bookmark> = value
+            .get("bookmarks")
+            .and_then(Value::as_array)
+            .map(|b| b.iter().filter_map(Bookmark::from_json).collect())
+            .unwrap_or_default();
+        bookmarks.sort_by_key(|b| b.tick);
+        let loop_mode = value
+            .get("loop")
+            .and_then(Value::as_str)
+            .and_then(LoopMode::from_key)
+            .unwrap_or_default();
+        let range = value
+            .get("range")
+            .and_then(Value::as_array)
+            .and_then(|r| Some((r.first()?.as_u64()?, r.get(1)?.as_u64()?)))
+            .filter(|(start, out)| start <= out);
+        let views = value
+            .get("views")
+            .and_then(Value::as_array)
+            .map(|v| v.iter().filter_map(CameraBookmark::from_json).collect())
+            .unwrap_or_default();
+        Self {
+            bookmarks,
+            loop_mode,
+            range,
+            views,
+        }
+    }
+}
+
+fn first_tick(story: &Story) -> u64 {
+    story.tick(0).unwrap_or_default()
+}
+
+fn last_tick(story: &Story) -> u64 {
+    story
+        .tick(story.len().saturating_sub(1))
+        .unwrap_or_default()
+}
+
+/// Frames to play and search, see [`Session::frames`].
+pub fn play_frames(story: &Story, session: Option<&Session>) -> RangeInclusive<usize> {
+    session.map_or(0..=story.len().saturating_sub(1), |s| s.frames(story))
+}
+
+/// Where sessions are kept, e.g. `~/.local/share/storyteller/sessions` on Linux.
+fn sessions_dir() -> Option<PathBuf> {
+    let home = || env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".local/share")))
+    };
+    Some(base?.join("storyteller").join("sessions"))
+}
+
+/// The file name keeps the story's name for people browsing the directory, and a hash of its
+/// full path so that stories sharing a name do not share a session. Falls back to beside the
+/// story when the platform has no data directory.
+pub fn session_path(source: &Path) -> PathBuf {
+    let Some(dir) = sessions_dir() else {
+        let mut name = source.file_name().unwrap_or_default().to_os_string();
+        name.push(".session.json");
+        return source.with_file_name(name);
+    };
+    let source = fs::canonicalize(source).unwrap_or_else(|_| source.to_path_buf());
+    // FNV-1a, which unlike the std hasher is stable across builds.
+    let hash = source
+        .as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    let name = source.file_name().unwrap_or_default().to_string_lossy();
+    dir.join(format!("{name}-{hash:016x}.json"))
+}
+
+/// An empty session when there is none yet. A session that cannot be read is logged and
+/// replaced, rather than keeping the story from loading.
+pub fn load_session(source: &Path) -> Session {
+    let path = session_path(source);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Session::default(),
+        Err(err) => {
+            warn!("Could not read {}: {err}", path.display());
+            return Session::default();
+        }
+    };
+    match serde_json::from_str(&content) {
+        Ok(value) => Session::from_json(&value),
+        Err(err) => {
+            warn!("Ignoring malformed session {}: {err}", path.display());
+            Session::default()
+        }
+    }
+}
+
+pub fn save_session(source: &Path, session: &Session) -> io::Result<()> {
+    let content = serde_json::to_string_pretty(&session.to_json())?;
+    let path = session_path(source);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, content)
+}
+
+/// Restores the session of a story as soon as it is loaded.
+pub fn load_session_system(mut commands: Commands, story: Res<Story>) {
+    commands.insert_resource(load_session(&story.source));
+}
+
+/// Writes the session back once edits have settled for [`SAVE_DELAY`], so dragging a slider
+/// does not write a file every frame. Pending edits are written straight away when the story is
+/// closed, the window loses focus or is about to close.
+pub fn save_session_system(
+    story: Res<Story>,
+    session: Res<Session>,
+    time: Res<Time>,
+    mut closed: MessageReader<CloseVisualization>,
+    mut focus: MessageReader<WindowFocused>,
+    mut close_requested: MessageReader<WindowCloseRequested>,
+    mut last_edit: Local<Option<f32>>,
+) {
+    let now = time.elapsed_secs();
+    if session.is_added() {
+        *last_edit = None;
+    } else if session.is_changed() {
+        *last_edit = Some(now);
+    }
+    let closed = closed.read().count() > 0;
+    let blurred = focus.read().any(|event| !event.focused);
+    let closing = close_requested.read().count() > 0;
+    let Some(edited) = *last_edit else {
+        return;
+    };
+    if now - edited < SAVE_DELAY && !(closed || blurred || closing) {
+        return;
+    }
+    *last_edit = None;
+    if let Err(err) = save_session(&story.source, &session) {
+        warn!(
+            "Could not save the session of {}: {err}",
+            story.source.display()
+        );
+    }
+}
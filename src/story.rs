@@ -0,0 +1,494 @@
+use std::{ops::RangeInclusive, path::PathBuf, sync::Arc};
+
+use bevy::prelude::*;
+
+use crate::history::StateHistory;
+
+/// A loaded sequence of states, independent of the file format it came from. States are kept as
+/// deltas, see [`StateHistory`].
+#[derive(Resource, Debug)]
+pub struct Story {
+    pub source: PathBuf,
+    /// Moments pointed out by the story itself, in tick order.
+    pub markers: Vec<Marker>,
+    ticks: Vec<u64>,
+    history: Arc<StateHistory>,
+    bounds: (usize, usize),
+    volume_bounds: (usize, usize, usize),
+    value_range: Option<(f32, f32)>,
+}
+
+impl Story {
+    /// Measures the frames, then compacts them into a history.
+    pub fn new(source: PathBuf, frames: Vec<Frame>) -> Self {
+        let mut bounds = (0, 0);
+        let mut volume_bounds = (0, 0, 0);
+        let mut value_range: Option<(f32, f32)> = None;
+        for frame in &frames {
+            let (w, h) = frame.state.dimensions();
+            bounds = (bounds.0.max(w), bounds.1.max(h));
+            volume_bounds = (
+                volume_bounds.0.max(w),
+                volume_bounds.1.max(h),
+                volume_bounds.2.max(frame.state.depth()),
+            );
+            if let Some((l, h)) = frame.state.value_range() {
+                value_range = Some(value_range.map_or((l, h), |(lo, hi)| (lo.min(l), hi.max(h))));
+            }
+        }
+        let ticks = frames.iter().map(|f| f.tick).collect();
+        Self {
+            source,
+            markers: Vec::new(),
+            ticks,
+            history: Arc::new(StateHistory::new(frames.into_iter().map(|f| f.state))),
+            bounds,
+            volume_bounds,
+            value_range,
+        }
+    }
+
+    pub fn with_markers(mut self, mut markers: Vec<Marker>) -> Self {
+        markers.sort_by_key(|m| m.tick);
+        self.markers = markers;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.ticks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ticks.is_empty()
+    }
+
+    pub fn tick(&self, index: usize) -> Option<u64> {
+        self.ticks.get(index).copied()
+    }
+
+    /// First frame at or after `tick`, the last frame when the story ends before it.
+    pub fn index_of_tick(&self, tick: u64) -> usize {
+        self.ticks
+            .partition_point(|t| *t < tick)
+            .min(self.len().saturating_sub(1))
+    }
+
+    /// State of frame `index`, rebuilt from the history when it is not cached.
+    pub fn state(&self, index: usize) -> Option<Arc<FrameState>> {
+        self.history.get(index)
+    }
+
+    pub fn first(&self) -> Option<&FrameState> {
+        self.history.first()
+    }
+
+    pub fn last(&self) -> Option<Arc<FrameState>> {
+        self.state(self.len().checked_sub(1)?)
+    }
+
+    /// Whether `f` holds for every state, visited in order.
+    pub fn all_states(&self, f: impl FnMut(&FrameState) -> bool) -> bool {
+        self.history.all(f)
+    }
+
+    pub fn history(&self) -> &StateHistory {
+        &self.history
+    }
+
+    /// The history and ticks, for tasks that walk the story off the main thread.
+    pub fn shared(&self) -> (Arc<StateHistory>, Vec<u64>) {
+        (self.history.clone(), self.ticks.clone())
+    }
+
+    /// Largest (width, height) over every frame, so renderers can spawn once.
+    pub fn bounds(&self) -> (usize, usize) {
+        self.bounds
+    }
+
+    /// Largest (width, height, depth) over every frame; 2D frames have a depth of one.
+    pub fn volume_bounds(&self) -> (usize, usize, usize) {
+        self.volume_bounds
+    }
+
+    /// Smallest and largest numeric value over every frame, if any frame holds numbers.
+    pub fn value_range(&self) -> Option<(f32, f32)> {
+        self.value_range
+    }
+
+    /// Whether `frames` span the whole story, so that [`Story::value_range`] covers them. Other
+    /// ranges are measured with [`StateHistory::value_range_in`], which replays the history.
+    pub fn is_whole(&self, frames: &RangeInclusive<usize>) -> bool {
+        *frames.start() == 0 && *frames.end() + 1 >= self.len()
+    }
+}
+
+/// A labelled tick, e.g. `marker phase 2 begins` in the story protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    pub tick: u64,
+    pub label: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub tick: u64,
+    pub state: FrameState,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameState {
+    Text(TextGrid),
+    Scalar(ScalarGrid),
+    Volume(ScalarVolume),
+    Graph(GraphState),
+    Array(ArrayState),
+}
+
+impl FrameState {
+    pub fn dimensions(&self) -> (usize, usize) {
+        match self {
+            FrameState::Text(grid) => (grid.width(), grid.height()),
+            FrameState::Scalar(grid) => (grid.width, grid.height),
+            FrameState::Volume(volume) => (volume.width, volume.height),
+            FrameState::Graph(_) => (0, 0),
+            FrameState::Array(array) => (array.values.len(), 1),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        match self {
+            FrameState::Volume(volume) => volume.depth,
+            _ => 1,
+        }
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> CellValue {
+        match self {
+            FrameState::Text(grid) => CellValue::Char(grid.get(x, y)),
+            FrameState::Scalar(grid) => grid.get(x, y).map_or(CellValue::Empty, CellValue::Number),
+            FrameState::Volume(volume) => volume
+                .get(x, y, 0)
+                .map_or(CellValue::Empty, CellValue::Number),
+            FrameState::Graph(_) => CellValue::Empty,
+            FrameState::Array(array) => match (y, array.values.get(x)) {
+                (0, Some(v)) if !v.is_nan() => CellValue::Number(*v),
+                _ => CellValue::Empty,
+            },
+        }
+    }
+
+    /// Smallest and largest numeric value, counting digits of text boards as numbers.
+    pub fn value_range(&self) -> Option<(f32, f32)> {
+        match self {
+            FrameState::Scalar(grid) => finite_range(&grid.values),
+            FrameState::Volume(volume) => finite_range(&volume.values),
+            FrameState::Graph(graph) => graph.node_weight_range(),
+            FrameState::Array(array) => finite_range(&array.values),
+            FrameState::Text(grid) => grid
+                .rows
+                .iter()
+                .flatten()
+                .filter_map(|c| c.to_digit(10))
+                .fold(None, |acc, d| {
+                    let d = d as f32;
+                    acc.map(|(lo, hi): (f32, f32)| (lo.min(d), hi.max(d)))
+                        .or(Some((d, d)))
+                }),
+        }
+    }
+}
+
+/// A single cell as seen by renderers, whatever the frame representation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellValue {
+    Empty,
+    Char(char),
+    Number(f32),
+}
+
+impl CellValue {
+    pub fn as_number(self) -> Option<f32> {
+        match self {
+            CellValue::Number(v) => Some(v),
+            CellValue::Char(c) => c.to_digit(10).map(|d| d as f32),
+            CellValue::Empty => None,
+        }
+    }
+}
+
+/// A board of characters, one row per line. Rows may be ragged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextGrid {
+    pub rows: Vec<Vec<char>>,
+}
+
+impl TextGrid {
+    pub fn from_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            rows: lines.into_iter().map(|l| l.chars().collect()).collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Missing cells of ragged rows read as a space.
+    pub fn get(&self, x: usize, y: usize) -> char {
+        self.rows
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or(' ')
+    }
+}
+
+/// A dense row-major matrix of numbers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScalarGrid {
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<f32>,
+}
+
+impl ScalarGrid {
+    /// Builds a grid from rows, padding ragged rows with `NaN`.
+    pub fn from_rows(rows: Vec<Vec<f32>>) -> Self {
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let height = rows.len();
+        let mut values = Vec::with_capacity(width * height);
+        for mut row in rows {
+            row.resize(width, f32::NAN);
+            values.extend(row);
+        }
+        Self {
+            width,
+            height,
+            values,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.values[y * self.width + x]).filter(|v| !v.is_nan())
+    }
+}
+
+/// A dense 3D field of numbers, x varying fastest, then y, then z.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScalarVolume {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    pub values: Vec<f32>,
+}
+
+impl ScalarVolume {
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Option<f32> {
+        if x >= self.width || y >= self.height || z >= self.depth {
+            return None;
+        }
+        Some(self.values[(z * self.height + y) * self.width + x]).filter(|v| !v.is_nan())
+    }
+}
+
+/// Nodes and edges of a graph at one tick. Nodes and edges are only ever appended, so an index
+/// refers to the same node or edge in every frame of a story.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphState {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    pub id: String,
+    /// Free-form state such as `visited` or `frontier`.
+    pub state: Option<String>,
+    pub weight: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    pub state: Option<String>,
+    pub weight: Option<f32>,
+}
+
+impl GraphState {
+    pub fn node_weight_range(&self) -> Option<(f32, f32)> {
+        let weights: Vec<f32> = self.nodes.iter().filter_map(|n| n.weight).collect();
+        finite_range(&weights)
+    }
+
+    pub fn edge_weight_range(&self) -> Option<(f32, f32)> {
+        let weights: Vec<f32> = self.edges.iter().filter_map(|e| e.weight).collect();
+        finite_range(&weights)
+    }
+}
+
+/// A 1D array and the operations the tick applied to it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArrayState {
+    pub values: Vec<f32>,
+    /// Indices read by a comparison during the tick.
+    pub compared: Vec<usize>,
+    /// Pairs swapped during the tick, in order.
+    pub swapped: Vec<(usize, usize)>,
+    pub written: Vec<usize>,
+    /// Operations of this tick alone.
+    pub counts: OperationCounts,
+    /// Operations since the start of the story.
+    pub totals: OperationCounts,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OperationCounts {
+    pub compares: usize,
+    pub swaps: usize,
+    pub writes: usize,
+}
+
+impl OperationCounts {
+    /// Operations counted since `earlier`, a total taken before this one.
+    pub fn since(self, earlier: OperationCounts) -> OperationCounts {
+        OperationCounts {
+            compares: self.compares.saturating_sub(earlier.compares),
+            swaps: self.swaps.saturating_sub(earlier.swaps),
+            writes: self.writes.saturating_sub(earlier.writes),
+        }
+    }
+}
+
+fn finite_range(values: &[f32]) -> Option<(f32, f32)> {
+    values
+        .iter()
+        .filter(|v| v.is_finite())
+        .fold(None, |acc, &v| match acc {
+            None => Some((v, v)),
+            Some((lo, hi)) => Some((lo.min(v), hi.max(v))),
+        })
+}
+
+/// What playback does when it reaches the end of the story, or of the in/out range.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoopMode {
+    /// Plays once and holds the last frame.
+    #[default]
+    Once,
+    /// Starts over from the other end.
+    Loop,
+    /// Turns around and plays back the other way.
+    PingPong,
+}
+
+impl LoopMode {
+    pub const ALL: [LoopMode; 3] = [LoopMode::Once, LoopMode::Loop, LoopMode::PingPong];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LoopMode::Once => "Once",
+            LoopMode::Loop => "Loop",
+            LoopMode::PingPong => "Ping-pong",
+        }
+    }
+
+    /// Name used in saved sessions.
+    pub fn key(&self) -> &'static str {
+        match self {
+            LoopMode::Once => "once",
+            LoopMode::Loop => "loop",
+            LoopMode::PingPong => "ping-pong",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.key() == key)
+    }
+
+    /// Frame played after `index` within `frames`, `None` when a single pass is over. Ping-pong
+    /// turns around by flipping `reverse`. Playback from outside `frames` enters it from the end
+    /// it plays away from.
+    pub fn next(
+        &self,
+        index: usize,
+        reverse: &mut bool,
+        frames: &RangeInclusive<usize>,
+    ) -> Option<usize> {
+        let (first, last) = (*frames.start(), *frames.end());
+        if !frames.contains(&index) {
+            return Some(if *reverse { last } else { first });
+        }
+        let at_end = if *reverse {
+            index == first
+        } else {
+            index == last
+        };
+        if !at_end {
+            return Some(if *reverse { index - 1 } else { index + 1 });
+        }
+        match self {
+            LoopMode::Once => None,
+            LoopMode::Loop => Some(if *reverse { last } else { first }),
+            LoopMode::PingPong if first == last => Some(index),
+            LoopMode::PingPong => {
+                *reverse = !*reverse;
+                Some(if *reverse { index - 1 } else { index + 1 })
+            }
+        }
+    }
+}
+
+/// Index of the frame currently shown.
+#[derive(Resource, Debug, Default)]
+pub struct Playhead {
+    pub index: usize,
+    /// Frame that was last pushed to the renderer, used to detect changes.
+    pub rendered: Option<usize>,
+    pub paused: bool,
+    /// Plays towards the first frame.
+    pub reverse: bool,
+}
+
+impl Playhead {
+    /// Moves to `index`, clamped to a story of `len` frames. The renderers pick it up on their
+    /// next run since it no longer matches `rendered`.
+    pub fn seek(&mut self, index: usize, len: usize) {
+        self.index = index.min(len.saturating_sub(1));
+    }
+
+    /// Pauses and moves `delta` frames.
+    pub fn step(&mut self, delta: isize, len: usize) {
+        self.paused = true;
+        self.seek(self.index.saturating_add_signed(delta), len);
+    }
+
+    /// Plays `frames` in the given direction, starting over from the other end when already at
+    /// the end it plays towards or outside of them.
+    pub fn play(&mut self, reverse: bool, frames: RangeInclusive<usize>) {
+        let (first, last) = frames.into_inner();
+        if reverse && (self.index <= first || self.index > last) {
+            self.index = last;
+        } else if !reverse && (self.index >= last || self.index < first) {
+            self.index = first;
+        }
+        self.reverse = reverse;
+        self.paused = false;
+    }
+
+    /// Pauses, or resumes in the last direction played.
+    pub fn toggle(&mut self, frames: RangeInclusive<usize>) {
+        if self.paused {
+            self.play(self.reverse, frames);
+        } else {
+            self.paused = true;
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, Color32, RichText},
+};
+
+use crate::{
+    renderers::array::{COMPARED_COLOR, SWAPPED_COLOR, WRITTEN_COLOR},
+    session::{Session, play_frames},
+    story::{FrameState, OperationCounts, Playhead, Story},
+};
+
+/// Operation counts of the current tick and since the start, or since the in point once the
+/// playhead is past it, with the highlight legend.
+pub fn ui_array_hud(
+    mut contexts: EguiContexts,
+    story: Option<Res<Story>>,
+    playhead: Option<Res<Playhead>>,
+    session: Option<Res<Session>>,
+    mut before_in: Local<Option<(usize, OperationCounts)>>,
+) -> Result {
+    let (Some(story), Some(playhead)) = (story, playhead) else {
+        return Ok(());
+    };
+    let state = story.state(playhead.index);
+    let Some(FrameState::Array(array)) = state.as_deref() else {
+        return Ok(());
+    };
+    // Totals just before the in point, replayed once per in point.
+    let start = *play_frames(&story, session.as_deref()).start();
+    if story.is_changed() || before_in.is_none_or(|(index, _)| index != start) {
+        let totals = match story.state(start).as_deref() {
+            Some(FrameState::Array(first)) => first.totals.since(first.counts),
+            _ => OperationCounts::default(),
+        };
+        *before_in = Some((start, totals));
+    }
+    let (totals, totals_label) = match *before_in {
+        Some((start, earlier)) if start > 0 && playhead.index >= start => {
+            (array.totals.since(earlier), "since in")
+        }
+        _ => (array.totals, "total"),
+    };
+    let tick = story.tick(playhead.index).unwrap_or_default();
+    let ctx = contexts.ctx_mut()?;
+    egui::Window::new("Operations")
+        .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10., 90.))
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(RichText::new(format!("Tick {tick}")).strong());
+            egui::Grid::new("ARRAY_OPERATIONS")
+                .num_columns(3)
+                .spacing([16., 4.])
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.label(RichText::new("tick").small());
+                    ui.label(RichText::new(totals_label).small());
+                    ui.end_row();
+                    let rows = [
+                        (
+                            "Compares",
+                            COMPARED_COLOR,
+                            array.counts.compares,
+                            totals.compares,
+                        ),
+                        ("Swaps", SWAPPED_COLOR, array.counts.swaps, totals.swaps),
+                        ("Writes", WRITTEN_COLOR, array.counts.writes, totals.writes),
+                    ];
+                    for (label, color, tick, total) in rows {
+                        let [r, g, b, _] = color.to_srgba().to_u8_array();
+                        ui.label(RichText::new(label).color(Color32::from_rgb(r, g, b)));
+                        ui.label(RichText::new(tick.to_string()).monospace());
+                        ui.label(RichText::new(total.to_string()).monospace());
+                        ui.end_row();
+                    }
+                });
+        });
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, Color32, RichText},
+};
+
+use crate::{
+    breakpoints::{Breakpoint, Breakpoints, expr::Expr},
+    story::Story,
+};
+
+const HELP: &str = "any(cell == 'X')   a cell holds X
+sum > 1000         numbers add up past 1000
+changed(3, 4)      cell (3, 4) changed
+tick % 100 == 0    every hundredth tick
+
+Variables: tick index width height sum min max mean
+Functions: cell(x, y) changed() changed(x, y) abs(n)
+Per cell: any(..) all(..) count(..) with cell, x and y
+Operators: + - * / % == != < <= > >= and or not";
+
+/// Adds, edits and toggles breakpoints, with their hit counts and errors.
+pub fn ui_breakpoints_panel(
+    mut contexts: EguiContexts,
+    story: Res<Story>,
+    mut breakpoints: ResMut<Breakpoints>,
+    mut draft: Local<String>,
+) -> Result {
+    let ctx = contexts.ctx_mut()?;
+    egui::Window::new("Breakpoints")
+        .anchor(egui::Align2::LEFT_CENTER, egui::Vec2::new(10., 40.))
+        .resizable(false)
+        .default_open(false)
+        .show(ctx, |ui| {
+            let parsed = Expr::parse(&draft);
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut *draft)
+                        .hint_text("any(cell == 'X')")
+                        .code_editor(),
+                );
+                let submitted =
+                    response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if (ui
+                    .add_enabled(parsed.is_ok(), egui::Button::new("Add"))
+                    .clicked()
+                    || submitted)
+                    && parsed.is_ok()
+                {
+                    breakpoints.list.push(Breakpoint::new(draft.trim()));
+                    draft.clear();
+                }
+            });
+            if let Err(err) = &parsed
+                && !draft.trim().is_empty()
+            {
+                ui.label(RichText::new(err).small().color(Color32::LIGHT_RED));
+            }
+            ui.collapsing("Syntax", |ui| {
+                ui.label(RichText::new(HELP).monospace().small());
+            });
+            ui.separator();
+
+            let last_hit = breakpoints.last_hit;
+            let mut removed = None;
+            for (i, breakpoint) in breakpoints.list.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut breakpoint.enabled, "");
+                    let mut source = breakpoint.source().to_string();
+                    if ui
+                        .add(egui::TextEdit::singleline(&mut source).code_editor())
+                        .changed()
+                    {
+                        breakpoint.set_source(source);
+                    }
+                    let hits = RichText::new(format!("{} hits", breakpoint.hits)).monospace();
+                    let hits = if last_hit.is_some_and(|(hit, _)| hit == i) {
+                        hits.strong().color(Color32::LIGHT_YELLOW)
+                    } else {
+                        hits
+                    };
+                    ui.label(hits);
+                    if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                });
+                if let Some(err) = breakpoint.parse_error().or(breakpoint.error.as_deref()) {
+                    ui.label(RichText::new(err).small().color(Color32::LIGHT_RED));
+                }
+            }
+            if breakpoints.list.is_empty() {
+                ui.label(RichText::new("No breakpoints yet").italics());
+            }
+            if let Some(i) = removed {
+                breakpoints.list.remove(i);
+                breakpoints.last_hit = None;
+            }
+            if let Some((i, index)) = breakpoints.last_hit
+                && let Some(breakpoint) = breakpoints.list.get(i)
+            {
+                ui.separator();
+                ui.label(format!(
+                    "Paused at tick {} by {}",
+                    story.tick(index).unwrap_or_default(),
+                    breakpoint.source()
+                ));
+            }
+            if !breakpoints.list.is_empty() && ui.button("Reset hit counts").clicked() {
+                for breakpoint in &mut breakpoints.list {
+                    breakpoint.hits = 0;
+                }
+                breakpoints.last_hit = None;
+            }
+        });
+    Ok(())
+}
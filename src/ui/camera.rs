@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, RichText},
+};
+
+use crate::{
+    camera::{CameraFollow, CameraMode, CameraPreset, MoveCamera, OrbitCamera, SetCameraMode},
+    renderers::grid::GridCell,
+    session::{CameraBookmark, Session},
+    viewports::ViewportId,
+};
+
+/// Camera mode, preset views, frame all, and the views saved with the session of the story.
+#[allow(clippy::too_many_arguments)]
+pub fn ui_camera_panel(
+    mut contexts: EguiContexts,
+    mut writer: MessageWriter<MoveCamera>,
+    mut modes: MessageWriter<SetCameraMode>,
+    cameras: Query<(
+        &ViewportId,
+        &CameraMode,
+        &OrbitCamera,
+        Option<&CameraFollow>,
+    )>,
+    cells: Query<&GridCell>,
+    session: Option<ResMut<Session>>,
+    mut name: Local<String>,
+) -> Result {
+    let ctx = contexts.ctx_mut()?;
+    let primary = cameras.iter().find(|(id, ..)| **id == ViewportId::Primary);
+    let current = primary.map(|(_, _, orbit, _)| *orbit);
+    egui::Window::new("Camera")
+        .anchor(egui::Align2::RIGHT_CENTER, egui::Vec2::new(-10., 0.))
+        .resizable(false)
+        .default_open(false)
+        .show(ctx, |ui| {
+            if let Some((_, &mode, _, follow)) = primary {
+                ui.horizontal(|ui| {
+                    for option in CameraMode::ALL {
+                        if ui
+                            .selectable_label(mode == option, option.label())
+                            .on_hover_text(option.hint())
+                            .clicked()
+                            && mode != option
+                        {
+                            modes.write(SetCameraMode(option));
+                        }
+                    }
+                });
+                if mode == CameraMode::Follow {
+                    let following = match follow.map(|f| (f.0, cells.get(f.0))) {
+                        Some((_, Ok(cell))) => format!("Following cell ({}, {})", cell.x, cell.y),
+                        Some((entity, Err(_))) => format!("Following {entity}"),
+                        None => "Click an object to follow it".to_string(),
+                    };
+                    ui.label(following);
+                }
+                ui.separator();
+            }
+            ui.horizontal(|ui| {
+                for (preset, key) in CameraPreset::ALL.into_iter().zip(["7", "1", "3", "5"]) {
+                    if ui
+                        .button(preset.label())
+                        .on_hover_text(format!("Numpad {key}"))
+                        .clicked()
+                    {
+                        writer.write(MoveCamera::Preset(preset));
+                    }
+                }
+                if ui.button("Frame all").on_hover_text("F").clicked() {
+                    writer.write(MoveCamera::FrameAll);
+                }
+            });
+            let Some(mut session) = session else {
+                return;
+            };
+            ui.separator();
+            // Edit a copy so that drawing the panel does not save the session every frame.
+            let mut views = session.views.clone();
+            ui.horizontal(|ui| {
+                let hint = format!("View {}", views.len() + 1);
+                ui.add(egui::TextEdit::singleline(&mut *name).hint_text(&hint));
+                if ui.button("Save view").clicked()
+                    && let Some(view) = current
+                {
+                    let name = std::mem::take(&mut *name);
+                    views.push(CameraBookmark {
+                        name: if name.trim().is_empty() {
+                            hint
+                        } else {
+                            name.trim().to_string()
+                        },
+                        view,
+                    });
+                }
+            });
+            let mut removed = None;
+            for (i, bookmark) in views.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.button("Go").clicked() {
+                        writer.write(MoveCamera::View(bookmark.view));
+                    }
+                    ui.text_edit_singleline(&mut bookmark.name);
+                    if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = removed {
+                views.remove(i);
+            }
+            if views != session.views {
+                session.views = views;
+            }
+        });
+    Ok(())
+}
+
+/// Mode of the primary camera and how to drive it, over the 3D view.
+pub fn ui_camera_hud(
+    mut contexts: EguiContexts,
+    cameras: Query<(&ViewportId, &CameraMode)>,
+) -> Result {
+    let Some((_, mode)) = cameras.iter().find(|(id, _)| **id == ViewportId::Primary) else {
+        return Ok(());
+    };
+    let ctx = contexts.ctx_mut()?;
+    egui::Area::new(egui::Id::new("CAMERA_HUD"))
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::Vec2::new(0., -60.))
+        .interactable(false)
+        .show(ctx, |ui| {
+            ui.label(
+                RichText::new(format!("{} camera (C): {}", mode.label(), mode.hint()))
+                    .small()
+                    .weak(),
+            );
+        });
+    Ok(())
+}
+
+/// Numpad 7, 1, 3 and 5 move to the top, front, side and isometric views, F frames the whole
+/// scene and C cycles through the camera modes. Ignored while egui has keyboard focus.
+pub fn camera_shortcut_system(
+    mut contexts: EguiContexts,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut writer: MessageWriter<MoveCamera>,
+    mut modes: MessageWriter<SetCameraMode>,
+    cameras: Query<(&ViewportId, &CameraMode)>,
+) -> Result {
+    if contexts.ctx_mut()?.wants_keyboard_input() {
+        return Ok(());
+    }
+    let presets = [
+        (KeyCode::Numpad7, CameraPreset::Top),
+        (KeyCode::Numpad1, CameraPreset::Front),
+        (KeyCode::Numpad3, CameraPreset::Side),
+        (KeyCode::Numpad5, CameraPreset::Isometric),
+    ];
+    for (key, preset) in presets {
+        if keys.just_pressed(key) {
+            writer.write(MoveCamera::Preset(preset));
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyF) {
+        writer.write(MoveCamera::FrameAll);
+    }
+    if keys.just_pressed(KeyCode::KeyC)
+        && let Some((_, mode)) = cameras.iter().find(|(id, _)| **id == ViewportId::Primary)
+    {
+        let next = CameraMode::ALL
+            .into_iter()
+            .cycle()
+            .skip_while(|m| m != mode)
+            .nth(1)
+            .unwrap_or_default();
+        modes.write(SetCameraMode(next));
+    }
+    Ok(())
+}
@@ -15,6 +15,12 @@ pub fn padded_button(
     .inner
 }
 
+pub fn start_button(ui: &mut egui::Ui) -> egui::Response {
+    let start = egui::Button::new(egui::RichText::new("Start").size(32.).strong())
+        .fill(egui::Color32::DARK_GREEN);
+    padded_button(ui, start, egui::Vec2::new(25., 12.))
+}
+
 pub fn separator(ui: &mut egui::Ui) {
     // let ui = ui.egui_ui_mut();
     ui.scope(|ui| {
@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, Color32, RichText},
+};
+
+use crate::{
+    FileTypeSelection,
+    error::VisualizationError,
+    file_id::suggestion,
+    loading::FailedLoad,
+    renderers::registry::SelectRenderer,
+    story::Story,
+    visualization::{
+        DroppedFile, Engine, LoadVisualization, TaggedEntity, VisualizationKind, VisualizerState,
+        clear_dropped_file, clear_visualization,
+    },
+};
+
+/// What Retry does, in order of preference: redraw a loaded story, rerun a failed load,
+/// reconfigure a running engine, or detect the dropped file again.
+enum Retry {
+    Render,
+    Load,
+    Engine,
+    Detect,
+}
+
+/// Shows the error held by [`VisualizerState::Error`] with ways to recover from it.
+#[allow(clippy::too_many_arguments)]
+pub fn ui_error_screen(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    state: Res<State<VisualizerState>>,
+    story: Option<Res<Story>>,
+    engine: Option<Res<Engine>>,
+    failed: Option<Res<FailedLoad>>,
+    dropped: Option<Res<DroppedFile>>,
+    selection: Option<Res<FileTypeSelection>>,
+    tagged: Query<Entity, With<TaggedEntity>>,
+) -> Result {
+    let VisualizerState::Error(error) = state.get() else {
+        return Ok(());
+    };
+    let retry = if story.is_some() {
+        Some(Retry::Render)
+    } else if failed.is_some() {
+        Some(Retry::Load)
+    } else if engine.is_some() {
+        Some(Retry::Engine)
+    } else if dropped.is_some() {
+        Some(Retry::Detect)
+    } else {
+        None
+    };
+
+    let ctx = contexts.ctx_mut()?;
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(40.);
+            ui.label(
+                RichText::new("Something went wrong")
+                    .size(38.)
+                    .color(Color32::LIGHT_RED),
+            );
+            ui.add_space(16.);
+            ui_error_details(ui, error);
+            ui.add_space(24.);
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        retry.is_some(),
+                        egui::Button::new(RichText::new("Retry").size(28.)),
+                    )
+                    .clicked()
+                {
+                    for entity in &tagged {
+                        commands.entity(entity).despawn();
+                    }
+                    match retry {
+                        Some(Retry::Render) => {
+                            commands.write_message(SelectRenderer(None));
+                        }
+                        Some(Retry::Engine) => {
+                            commands.write_message(LoadVisualization(VisualizationKind::Grid));
+                        }
+                        Some(Retry::Load) => {
+                            if let Some(failed) = &failed {
+                                commands.insert_resource(failed.retry());
+                            }
+                            commands.set_state(VisualizerState::Loading);
+                        }
+                        Some(Retry::Detect) => {
+                            if let Some(dropped) = &dropped {
+                                commands.remove_resource::<FileTypeSelection>();
+                                commands.insert_resource(suggestion(&dropped.0));
+                            }
+                            commands.set_state(VisualizerState::Loading);
+                        }
+                        None => {}
+                    }
+                }
+                let back = if selection.is_some() {
+                    "Back to selection"
+                } else {
+                    "Back"
+                };
+                if ui.button(RichText::new(back).size(28.)).clicked() {
+                    for entity in &tagged {
+                        commands.entity(entity).despawn();
+                    }
+                    clear_visualization(&mut commands);
+                    if selection.is_some() {
+                        commands.set_state(VisualizerState::Loading);
+                    } else {
+                        clear_dropped_file(&mut commands);
+                        commands.set_state(VisualizerState::Input);
+                    }
+                }
+                if ui.button(RichText::new("Copy details").size(28.)).clicked() {
+                    ui.ctx().copy_text(error.details());
+                }
+            });
+        });
+    });
+    Ok(())
+}
+
+fn ui_error_details(ui: &mut egui::Ui, error: &VisualizationError) {
+    egui::Grid::new("ERROR_DETAILS")
+        .num_columns(2)
+        .spacing([24., 8.])
+        .show(ui, |ui| {
+            let mut row = |label: &str, value: String| {
+                ui.label(RichText::new(label).size(22.).color(Color32::GRAY));
+                ui.label(RichText::new(value).size(22.).monospace());
+                ui.end_row();
+            };
+            row("Stage", error.stage.label().to_string());
+            if let Some(source) = &error.source {
+                row("File", source.display().to_string());
+            }
+            row("Error", error.message.clone());
+            if let Some(context) = &error.context {
+                row("Context", context.to_string());
+            }
+        });
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, Color32, RichText},
+};
+
+use crate::{
+    renderers::graph::{GraphLayout, GraphScene, GraphSettings, state_color},
+    story::{FrameState, Playhead, Story},
+};
+
+/// Layout choice and a legend of the node states in the current frame.
+pub fn ui_graph_panel(
+    mut contexts: EguiContexts,
+    mut settings: ResMut<GraphSettings>,
+    scene: Option<Res<GraphScene>>,
+    story: Option<Res<Story>>,
+    playhead: Option<Res<Playhead>>,
+) -> Result {
+    let ctx = contexts.ctx_mut()?;
+    egui::Window::new("Graph")
+        .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10., 90.))
+        .resizable(false)
+        .show(ctx, |ui| {
+            // Edit a copy so that drawing the panel does not flag the settings as changed.
+            let mut layout = settings.layout;
+            egui::ComboBox::from_id_salt("GRAPH_LAYOUT")
+                .selected_text(layout.label())
+                .show_ui(ui, |ui| {
+                    for option in GraphLayout::ALL {
+                        ui.selectable_value(&mut layout, option, option.label());
+                    }
+                });
+            if layout != settings.layout {
+                settings.layout = layout;
+            }
+            if scene.is_some_and(|scene| scene.is_laying_out()) {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Laying out the graph...");
+                });
+            }
+
+            let state = story
+                .as_ref()
+                .zip(playhead.as_ref())
+                .and_then(|(story, playhead)| story.state(playhead.index));
+            let Some(FrameState::Graph(graph)) = state.as_deref() else {
+                return;
+            };
+            let mut states: BTreeMap<&str, usize> = BTreeMap::new();
+            for node in &graph.nodes {
+                *states
+                    .entry(node.state.as_deref().unwrap_or("none"))
+                    .or_default() += 1;
+            }
+            ui.separator();
+            for (state, count) in states {
+                ui.horizontal(|ui| {
+                    let [r, g, b, _] = state_color(Some(state)).to_srgba().to_u8_array();
+                    let (rect, _) =
+                        ui.allocate_exact_size(egui::Vec2::splat(12.), egui::Sense::hover());
+                    ui.painter()
+                        .rect_filled(rect, 2., Color32::from_rgb(r, g, b));
+                    ui.label(RichText::new(format!("{state} : {count}")).monospace());
+                });
+            }
+            ui.label(
+                RichText::new(format!(
+                    "{} nodes, {} edges",
+                    graph.nodes.len(),
+                    graph.edges.len()
+                ))
+                .small(),
+            );
+        });
+    Ok(())
+}
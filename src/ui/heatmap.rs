@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, Color32, RichText},
+};
+
+use crate::renderers::heatmap::{Colormap, HeatmapHover, HeatmapSettings, RangeMode};
+
+/// Colormap and range controls, the colour legend and the hovered cell readout.
+pub fn ui_heatmap_panel(
+    mut contexts: EguiContexts,
+    mut settings: ResMut<HeatmapSettings>,
+    hover: Option<Res<HeatmapHover>>,
+) -> Result {
+    let ctx = contexts.ctx_mut()?;
+    egui::Window::new("Heatmap")
+        .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10., 90.))
+        .resizable(false)
+        .show(ctx, |ui| {
+            // Read through a copy so that merely drawing the panel does not mark the settings as
+            // changed, which would re-render the heatmap every frame.
+            let mut colormap = settings.colormap;
+            egui::ComboBox::from_id_salt("HEATMAP_COLORMAP")
+                .selected_text(colormap.label())
+                .show_ui(ui, |ui| {
+                    for map in Colormap::ALL {
+                        ui.selectable_value(&mut colormap, map, map.label());
+                    }
+                });
+            if colormap != settings.colormap {
+                settings.colormap = colormap;
+            }
+
+            let mut range = settings.range;
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut range, RangeMode::Story, "Story");
+                ui.radio_value(&mut range, RangeMode::Frame, "Frame");
+                let (lo, hi) = settings.resolved_range;
+                if ui
+                    .radio(matches!(range, RangeMode::Fixed { .. }), "Fixed")
+                    .clicked()
+                {
+                    range = RangeMode::Fixed { min: lo, max: hi };
+                }
+            });
+            if let RangeMode::Fixed { min, max } = &mut range {
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(min).speed(0.1).prefix("min "));
+                    ui.add(egui::DragValue::new(max).speed(0.1).prefix("max "));
+                });
+            }
+            if range != settings.range {
+                settings.range = range;
+            }
+
+            ui.separator();
+            ui_legend(ui, settings.colormap, settings.resolved_range);
+
+            ui.separator();
+            let readout = match hover.as_ref().and_then(|h| h.0) {
+                Some((x, y, Some(value))) => format!("({x}, {y}) = {value}"),
+                Some((x, y, None)) => format!("({x}, {y}) = empty"),
+                None => "Hover a cell to read its value".to_string(),
+            };
+            ui.label(RichText::new(readout).monospace());
+        });
+    Ok(())
+}
+
+fn ui_legend(ui: &mut egui::Ui, colormap: Colormap, (lo, hi): (f32, f32)) {
+    const STEPS: usize = 64;
+    let (rect, _) = ui.allocate_exact_size(egui::Vec2::new(220., 16.), egui::Sense::hover());
+    let step = rect.width() / STEPS as f32;
+    for i in 0..STEPS {
+        let [r, g, b] = colormap.sample(i as f32 / (STEPS - 1) as f32);
+        let min = rect.left_top() + egui::Vec2::new(i as f32 * step, 0.);
+        ui.painter().rect_filled(
+            egui::Rect::from_min_size(min, egui::Vec2::new(step + 0.5, rect.height())),
+            0.,
+            Color32::from_rgb(r, g, b),
+        );
+    }
+    ui.label(RichText::new(format!("{lo:.3} → {hi:.3}")).monospace());
+}
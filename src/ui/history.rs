@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, RichText},
+};
+
+use crate::{config::HistoryConfig, story::Story};
+
+pub fn history_overlay_enabled(config: Res<HistoryConfig>) -> bool {
+    config.overlay
+}
+
+pub fn mib(bytes: usize) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024. * 1024.))
+}
+
+/// Seek latency and memory use of the story history, toggled with F3.
+pub fn ui_history_overlay(mut contexts: EguiContexts, story: Res<Story>) -> Result {
+    let stats = story.history().stats();
+    let ctx = contexts.ctx_mut()?;
+    egui::Area::new(egui::Id::new("HISTORY_OVERLAY"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::Vec2::new(-10., -60.))
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                let lines = [
+                    format!(
+                        "seek      {:.2} ms, {} deltas replayed",
+                        stats.last_seek.as_secs_f64() * 1000.,
+                        stats.last_replayed
+                    ),
+                    format!(
+                        "keyframes {} using {} of {}",
+                        stats.keyframes,
+                        mib(stats.keyframe_bytes),
+                        mib(stats.budget)
+                    ),
+                    format!(
+                        "deltas    {} for {} ticks",
+                        mib(stats.delta_bytes),
+                        story.len()
+                    ),
+                    format!("lookups   {} hits, {} misses", stats.hits, stats.misses),
+                ];
+                for line in lines {
+                    ui.label(RichText::new(line).monospace().small());
+                }
+            });
+        });
+    Ok(())
+}
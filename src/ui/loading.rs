@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, RichText},
+};
+
+use crate::loading::LoadingTask;
+
+/// Blocks the selection menu while a file is read or a story loads, with its progress and a way
+/// out. Cancelling drops the task and leaves the selection and its configuration as they were.
+pub fn ui_loading_overlay(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    loading: Res<LoadingTask>,
+) -> Result {
+    let ctx = contexts.ctx_mut()?;
+    let progress = &loading.progress;
+    egui::Modal::new(egui::Id::new("LOADING_OVERLAY")).show(ctx, |ui| {
+        ui.set_width(420.);
+        let stage = progress.stage();
+        ui.label(RichText::new(if stage.is_empty() { "Loading" } else { &stage }).size(28.));
+        let read = progress.bytes_done();
+        let text = (read > 0).then(|| format!("{} KiB read", read / 1024));
+        let bar = match progress.fraction() {
+            Some(fraction) => egui::ProgressBar::new(fraction).show_percentage(),
+            None => egui::ProgressBar::new(0.).animate(true),
+        };
+        ui.add(match text {
+            Some(text) => bar.text(text),
+            None => bar,
+        });
+        ui.add_space(8.);
+        ui.vertical_centered(|ui| {
+            if ui.button(RichText::new("Cancel").size(22.)).clicked() {
+                loading.cancel(&mut commands);
+            }
+        });
+    });
+    // The task reports from another thread, so keep redrawing while it runs.
+    ctx.request_repaint();
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, Color32, RichText},
+};
+
+use crate::{
+    session::{Bookmark, Session},
+    story::{Playhead, Story},
+};
+
+/// Markers emitted by the story itself, bookmarks have their own colour.
+pub const STORY_MARKER_COLOR: Color32 = Color32::from_rgb(240, 210, 90);
+
+fn matches(query: &str, texts: &[&str]) -> bool {
+    let query = query.trim().to_lowercase();
+    query.is_empty() || texts.iter().any(|t| t.to_lowercase().contains(&query))
+}
+
+/// Story markers and bookmarks, filtered by a search. Clicking one jumps to its tick, bookmarks
+/// can be renamed, recoloured, annotated and removed.
+pub fn ui_markers_panel(
+    mut contexts: EguiContexts,
+    story: Res<Story>,
+    mut playhead: ResMut<Playhead>,
+    mut session: ResMut<Session>,
+    mut search: Local<String>,
+) -> Result {
+    let ctx = contexts.ctx_mut()?;
+    egui::Window::new("Markers")
+        .anchor(egui::Align2::LEFT_CENTER, egui::Vec2::new(10., 0.))
+        .resizable(false)
+        .default_open(false)
+        .show(ctx, |ui| {
+            let len = story.len();
+            ui.add(egui::TextEdit::singleline(&mut *search).hint_text("Search markers"));
+            let tick = story.tick(playhead.index).unwrap_or_default();
+            if ui
+                .button(format!("Bookmark tick {tick}"))
+                .on_hover_text("B")
+                .clicked()
+            {
+                let count = session.bookmarks.len();
+                session.add_bookmark(Bookmark::new(tick, count));
+            }
+            ui.separator();
+
+            let mut bookmarks = session.bookmarks.clone();
+            let mut removed = None;
+            egui::ScrollArea::vertical()
+                .max_height(360.)
+                .show(ui, |ui| {
+                    for marker in &story.markers {
+                        if !matches(&search, &[&marker.label]) {
+                            continue;
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("◆").color(STORY_MARKER_COLOR));
+                            let text = format!("{}  {}", marker.tick, marker.label);
+                            if ui.selectable_label(false, text).clicked() {
+                                playhead.seek(story.index_of_tick(marker.tick), len);
+                            }
+                        });
+                    }
+                    for (i, bookmark) in bookmarks.iter_mut().enumerate() {
+                        if !matches(&search, &[&bookmark.name, &bookmark.note]) {
+                            continue;
+                        }
+                        ui.horizontal(|ui| {
+                            ui.color_edit_button_srgb(&mut bookmark.color);
+                            let text = format!("{}  {}", bookmark.tick, bookmark.name);
+                            let response = ui.selectable_label(false, text);
+                            let response = if bookmark.note.is_empty() {
+                                response
+                            } else {
+                                response.on_hover_text(&bookmark.note)
+                            };
+                            if response.clicked() {
+                                playhead.seek(story.index_of_tick(bookmark.tick), len);
+                            }
+                            if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                                removed = Some(i);
+                            }
+                        });
+                        egui::CollapsingHeader::new("Edit")
+                            .id_salt(("BOOKMARK", i))
+                            .show(ui, |ui| {
+                                ui.text_edit_singleline(&mut bookmark.name);
+                                ui.add(
+                                    egui::TextEdit::multiline(&mut bookmark.note)
+                                        .hint_text("Note")
+                                        .desired_rows(2),
+                                );
+                            });
+                    }
+                    if story.markers.is_empty() && bookmarks.is_empty() {
+                        ui.label(RichText::new("No markers yet").italics());
+                    }
+                });
+            if let Some(i) = removed {
+                bookmarks.remove(i);
+            }
+            if bookmarks != session.bookmarks {
+                session.bookmarks = bookmarks;
+            }
+        });
+    Ok(())
+}
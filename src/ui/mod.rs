@@ -1,5 +1,19 @@
+pub mod array;
+pub mod breakpoints;
+pub mod camera;
 pub mod components;
 pub mod egui_loader;
+pub mod error;
 pub mod font_system;
+pub mod graph;
+pub mod heatmap;
+pub mod history;
+pub mod loading;
+pub mod markers;
+pub mod playback;
+pub mod renderer;
+pub mod search;
 pub mod selection;
 pub mod style;
+pub mod transport;
+pub mod volume;
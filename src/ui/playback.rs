@@ -0,0 +1,215 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, Color32, RichText, Stroke},
+};
+
+use crate::{
+    clock::StoryClock,
+    config::{AnimationConfig, HistoryConfig, InterpolationKind, LagPolicy, SPEED_RANGE},
+};
+
+/// Live controls for the story clock: tick length, speed, lag policy and easing, plus the
+/// history cache.
+pub fn ui_playback_panel(
+    mut contexts: EguiContexts,
+    mut config: ResMut<AnimationConfig>,
+    mut history: ResMut<HistoryConfig>,
+    clock: Res<StoryClock>,
+) -> Result {
+    let ctx = contexts.ctx_mut()?;
+    egui::Window::new("Playback")
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::Vec2::new(10., -60.))
+        .resizable(false)
+        .default_open(false)
+        .show(ctx, |ui| {
+            // Edit a copy so that drawing the panel does not mark the config as changed.
+            let mut edited = config.clone();
+            egui::Grid::new("PLAYBACK_SETTINGS")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Tick length");
+                    ui.add(
+                        egui::DragValue::new(&mut edited.tick_rate)
+                            .range(0.001..=10.0)
+                            .speed(0.01)
+                            .suffix(" s"),
+                    );
+                    ui.end_row();
+
+                    ui.label("Speed");
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::Slider::new(&mut edited.animation_speed, SPEED_RANGE)
+                                .logarithmic(true)
+                                .suffix("×"),
+                        );
+                        if ui.small_button("1×").clicked() {
+                            edited.animation_speed = 1.0;
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("When behind");
+                    ui.horizontal(|ui| {
+                        for policy in LagPolicy::ALL {
+                            ui.radio_value(&mut edited.lag_policy, policy, policy.label());
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Easing");
+                    ui.horizontal(|ui| {
+                        ui.add_enabled_ui(!edited.reduce_motion, |ui| {
+                            ui_easing_picker(ui, &mut edited.interpolation);
+                        });
+                    });
+                    ui.end_row();
+
+                    ui.label("");
+                    ui.checkbox(&mut edited.reduce_motion, "Reduce motion")
+                        .on_hover_text("Snap to each state instead of easing towards it");
+                    ui.end_row();
+                });
+            if !edited.reduce_motion {
+                ui_easing_curve(ui, &mut edited.interpolation);
+            }
+            ui.separator();
+            ui.label(
+                RichText::new(format!(
+                    "{:.1} ticks/s, {} dropped",
+                    1.0 / edited.tick_seconds(),
+                    clock.dropped
+                ))
+                .monospace(),
+            );
+            if edited != *config {
+                *config = edited;
+            }
+
+            ui.collapsing("History cache", |ui| {
+                let mut edited = history.clone();
+                ui.horizontal(|ui| {
+                    ui.label("Keyframe every");
+                    ui.add(
+                        egui::DragValue::new(&mut edited.keyframe_interval)
+                            .range(1..=4096)
+                            .suffix(" ticks"),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Budget");
+                    let mut budget = edited.cache_budget / (1024 * 1024);
+                    ui.add(
+                        egui::DragValue::new(&mut budget)
+                            .range(1..=16384)
+                            .suffix(" MiB"),
+                    );
+                    edited.cache_budget = budget * 1024 * 1024;
+                });
+                ui.checkbox(&mut edited.overlay, "Show overlay (F3)");
+                if edited != *history {
+                    *history = edited;
+                }
+            });
+        });
+    Ok(())
+}
+
+/// Kind of easing, plus the parameters of the one picked.
+fn ui_easing_picker(ui: &mut egui::Ui, kind: &mut InterpolationKind) {
+    egui::ComboBox::from_id_salt("PLAYBACK_EASING")
+        .selected_text(kind.label())
+        .show_ui(ui, |ui| {
+            for preset in InterpolationKind::PRESETS {
+                let selected = std::mem::discriminant(kind) == std::mem::discriminant(&preset);
+                if ui.selectable_label(selected, preset.label()).clicked() && !selected {
+                    *kind = preset;
+                }
+            }
+        });
+    match kind {
+        InterpolationKind::Exponential { exponent } => {
+            ui.add(egui::DragValue::new(exponent).range(1.0..=8.0).speed(0.05));
+        }
+        InterpolationKind::Spring { damping } => {
+            ui.add(
+                egui::DragValue::new(damping)
+                    .range(0.1..=1.0)
+                    .speed(0.01)
+                    .prefix("damping "),
+            );
+        }
+        InterpolationKind::Step { steps } => {
+            ui.add(egui::DragValue::new(steps).range(1..=16).suffix(" steps"));
+        }
+        _ => {}
+    }
+}
+
+/// Plots the easing over one tick. For a cubic bezier both control points can be dragged.
+fn ui_easing_curve(ui: &mut egui::Ui, kind: &mut InterpolationKind) {
+    // Leave room above and below for springs and beziers that overshoot.
+    const LOW: f32 = -0.25;
+    const SPAN: f32 = 1.5;
+    let (rect, _) = ui.allocate_exact_size(egui::Vec2::new(220., 160.), egui::Sense::hover());
+    let to_screen = |p: Vec2| {
+        egui::pos2(
+            rect.left() + p.x * rect.width(),
+            rect.bottom() - (p.y - LOW) / SPAN * rect.height(),
+        )
+    };
+    let from_screen = |pos: egui::Pos2| {
+        Vec2::new(
+            (pos.x - rect.left()) / rect.width(),
+            (rect.bottom() - pos.y) / rect.height() * SPAN + LOW,
+        )
+    };
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 4., ui.visuals().extreme_bg_color);
+    for y in [0., 1.] {
+        painter.line_segment(
+            [to_screen(Vec2::new(0., y)), to_screen(Vec2::new(1., y))],
+            Stroke::new(1., Color32::DARK_GRAY),
+        );
+    }
+
+    if let InterpolationKind::CubicBezier { p1, p2 } = kind {
+        for (i, (anchor, point)) in [(Vec2::ZERO, p1), (Vec2::ONE, p2)].into_iter().enumerate() {
+            let response = ui.interact(
+                egui::Rect::from_center_size(to_screen(*point), egui::Vec2::splat(14.)),
+                ui.id().with(("BEZIER_HANDLE", i)),
+                egui::Sense::drag(),
+            );
+            if response.dragged()
+                && let Some(pos) = response.interact_pointer_pos()
+            {
+                // x stays in [0, 1] so the curve remains a function of time.
+                let p = from_screen(pos);
+                *point = Vec2::new(p.x.clamp(0., 1.), p.y.clamp(LOW, LOW + SPAN));
+            }
+            painter.line_segment(
+                [to_screen(anchor), to_screen(*point)],
+                Stroke::new(1., Color32::GRAY),
+            );
+            let color = if response.hovered() || response.dragged() {
+                Color32::WHITE
+            } else {
+                Color32::LIGHT_BLUE
+            };
+            painter.circle_filled(to_screen(*point), 5., color);
+        }
+    }
+
+    const SAMPLES: usize = 64;
+    let points = (0..=SAMPLES)
+        .map(|i| {
+            let t = i as f32 / SAMPLES as f32;
+            to_screen(Vec2::new(t, kind.apply(t)))
+        })
+        .collect();
+    painter.add(egui::Shape::line(
+        points,
+        Stroke::new(2., Color32::LIGHT_BLUE),
+    ));
+}
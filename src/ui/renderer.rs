@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, RichText},
+};
+
+use crate::{
+    renderers::registry::RendererCandidates,
+    visualization::{LoadVisualization, VisualizerState},
+};
+
+/// Lists the renderers able to draw the current story and swaps to the one clicked, keeping the
+/// story and the playhead.
+pub fn ui_renderer_switch(
+    mut contexts: EguiContexts,
+    candidates: Res<RendererCandidates>,
+    state: Res<State<VisualizerState>>,
+    mut writer: MessageWriter<LoadVisualization>,
+) -> Result {
+    if candidates.0.len() < 2 {
+        return Ok(());
+    }
+    let ctx = contexts.ctx_mut()?;
+    egui::Window::new("Renderer")
+        .anchor(egui::Align2::LEFT_TOP, egui::Vec2::new(10., 90.))
+        .resizable(false)
+        .show(ctx, |ui| {
+            for candidate in &candidates.0 {
+                let active = VisualizerState::from(candidate.kind) == *state.get();
+                let response = ui
+                    .selectable_label(active, RichText::new(candidate.name))
+                    .on_hover_text(format!(
+                        "Draws {}, score {}",
+                        candidate.snapshot, candidate.score
+                    ));
+                if response.clicked() && !active {
+                    writer.write(LoadVisualization(candidate.kind));
+                }
+            }
+        });
+    Ok(())
+}
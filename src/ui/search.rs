@@ -0,0 +1,174 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, Color32, RichText},
+};
+
+use crate::{
+    search::{SEARCH_HIGHLIGHT, SearchMode, SearchQuery, SearchResults, SearchTask},
+    session::{Session, play_frames},
+    story::{Playhead, Story},
+};
+
+#[derive(Default)]
+pub struct SearchForm {
+    mode: SearchMode,
+    text: String,
+    onset: bool,
+    /// Only search the in/out range of the session.
+    in_range: bool,
+    error: Option<String>,
+}
+
+/// Starts searches over the whole story and lists what they found. Clicking a match, or
+/// stepping with the arrows, pauses on its frame.
+#[allow(clippy::too_many_arguments)]
+pub fn ui_search_panel(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    story: Res<Story>,
+    mut playhead: ResMut<Playhead>,
+    task: Option<Res<SearchTask>>,
+    results: Option<ResMut<SearchResults>>,
+    session: Option<Res<Session>>,
+    mut form: Local<SearchForm>,
+) -> Result {
+    let ctx = contexts.ctx_mut()?;
+    let highlight = Srgba::from(SEARCH_HIGHLIGHT).to_u8_array_no_alpha();
+    let highlight = Color32::from_rgb(highlight[0], highlight[1], highlight[2]);
+    egui::Window::new("Search")
+        .anchor(egui::Align2::LEFT_CENTER, egui::Vec2::new(10., 80.))
+        .resizable(false)
+        .default_open(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for mode in SearchMode::ALL {
+                    ui.radio_value(&mut form.mode, mode, mode.label());
+                }
+            });
+            let hint = match form.mode {
+                SearchMode::Value => "7 or #",
+                SearchMode::Regex => "a+b",
+                SearchMode::Predicate => "cell == 'X' and changed(x, y)",
+            };
+            let mut start = false;
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut form.text)
+                        .hint_text(hint)
+                        .code_editor(),
+                );
+                start = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                start |= ui.button("Search").clicked();
+            });
+            ui.checkbox(&mut form.onset, "Only where it starts matching");
+            let ranged = session.as_ref().is_some_and(|s| s.range.is_some());
+            ui.add_enabled(
+                ranged,
+                egui::Checkbox::new(&mut form.in_range, "Only within the in/out range"),
+            );
+            if start {
+                match SearchQuery::parse(form.mode, &form.text) {
+                    Ok(query) => {
+                        if let Some(task) = &task {
+                            task.progress.cancel();
+                        }
+                        let text = form.text.trim().to_string();
+                        let frames = if ranged && form.in_range {
+                            play_frames(&story, session.as_deref())
+                        } else {
+                            0..=story.len().saturating_sub(1)
+                        };
+                        commands.insert_resource(SearchTask::spawn(
+                            &story, text, query, form.onset, frames,
+                        ));
+                        form.error = None;
+                    }
+                    Err(err) => form.error = Some(err),
+                }
+            }
+            if let Some(err) = &form.error {
+                ui.label(RichText::new(err).small().color(Color32::LIGHT_RED));
+            }
+
+            if let Some(task) = &task {
+                ui.horizontal(|ui| {
+                    let fraction = task.progress.fraction().unwrap_or(0.);
+                    ui.add(
+                        egui::ProgressBar::new(fraction)
+                            .show_percentage()
+                            .desired_width(200.),
+                    );
+                    if ui.button("Cancel").clicked() {
+                        task.progress.cancel();
+                        commands.remove_resource::<SearchTask>();
+                    }
+                });
+                // The task reports from another thread, so keep redrawing while it runs.
+                ui.ctx().request_repaint();
+            }
+
+            let Some(mut results) = results else {
+                return;
+            };
+            ui.separator();
+            let count = results.matches.len();
+            let mut jump = None;
+            ui.horizontal(|ui| {
+                let summary = if results.truncated {
+                    format!("First {count} matches for {}", results.query)
+                } else {
+                    format!("{count} matches for {}", results.query)
+                };
+                ui.label(summary);
+                if ui
+                    .small_button("◀")
+                    .on_hover_text("Previous match")
+                    .clicked()
+                {
+                    jump = results.step_from(playhead.index, true);
+                }
+                if ui.small_button("▶").on_hover_text("Next match").clicked() {
+                    jump = results.step_from(playhead.index, false);
+                }
+                if ui.small_button("Clear").clicked() {
+                    *results = SearchResults::default();
+                }
+            });
+            let row_height = ui.text_style_height(&egui::TextStyle::Body);
+            egui::ScrollArea::vertical().max_height(300.).show_rows(
+                ui,
+                row_height,
+                results.matches.len(),
+                |ui, rows| {
+                    for i in rows {
+                        let found = results.matches[i];
+                        let text = match found.cell {
+                            Some((x, y)) => format!("tick {}  ({x}, {y})", found.tick),
+                            None => format!("tick {}", found.tick),
+                        };
+                        let text = if found.index == playhead.index {
+                            RichText::new(text).color(highlight)
+                        } else {
+                            RichText::new(text)
+                        };
+                        if ui
+                            .selectable_label(results.selected == Some(i), text)
+                            .clicked()
+                        {
+                            jump = Some(i);
+                        }
+                    }
+                },
+            );
+            if let Some(i) = jump
+                && let Some(found) = results.matches.get(i)
+            {
+                let index = found.index;
+                results.selected = Some(i);
+                playhead.paused = true;
+                playhead.seek(index, story.len());
+            }
+        });
+    Ok(())
+}
@@ -1,11 +1,22 @@
 use crate::{
     ExecutableConfiguration, FileTypeSelection,
+    error::{ErrorStage, VisualizationError, report_error},
+    loaders::{
+        graph::{GraphConfiguration, GraphFormat, load_graph_story},
+        process::run_executable_story,
+        protocol::load_protocol_story,
+        text::{TextConfiguration, load_text_story},
+        volume::load_raw_volume_story,
+    },
+    loading::LoadingTask,
     ui::{
-        components::{separator, ui_flex_spacer},
+        components::{separator, start_button, ui_flex_spacer},
+        selection::selector::ui_visualization_kind_selector,
         style::*,
     },
-    visualization::DroppedFile,
+    visualization::{DroppedFile, VisualizationKind, executable_engine, start_engine},
 };
+use bevy::prelude::Commands;
 use bevy_egui::egui::{self, Color32};
 use egui_taffy::{
     Tui, TuiBuilderLogic,
@@ -16,6 +27,7 @@ pub fn ui_executable_options(
     tui: &mut Tui,
     dropped: &DroppedFile,
     cfg: &mut ExecutableConfiguration,
+    commands: &mut Commands,
 ) {
     tui.style(compose_style([column(), full_size(), gap_y(16.)]))
         .bg_add(
@@ -75,7 +87,218 @@ pub fn ui_executable_options(
                             .size(22.),
                         );
                     });
+                tui.style(compose_style([flex(), align_self_center()]))
+                    .ui(|ui| {
+                        if start_button(ui).clicked() {
+                            let engine = match executable_engine(&dropped.0, cfg) {
+                                Ok(engine) => engine,
+                                Err(err) => {
+                                    report_error(
+                                        commands,
+                                        VisualizationError::from_load(ErrorStage::Spawn, &err),
+                                    );
+                                    return;
+                                }
+                            };
+                            start_engine(commands, engine, &dropped.0);
+                            let (path, cfg) = (dropped.0.clone(), cfg.clone());
+                            commands.insert_resource(LoadingTask::spawn(
+                                Some(VisualizationKind::Grid),
+                                move |progress| run_executable_story(&path, &cfg, progress),
+                            ));
+                        }
+                    });
             },
         );
     // });
 }
+
+pub fn ui_text_options(
+    tui: &mut Tui,
+    dropped: &DroppedFile,
+    cfg: &mut TextConfiguration,
+    commands: &mut Commands,
+) {
+    tui.style(compose_style([column(), full_size(), gap_y(16.)]))
+        .bg_add(
+            TuiBackground::new()
+                .with_background_color(Color32::from_gray(20))
+                .with_corner_radius(5.),
+            |tui| {
+                tui.style(compose_style([row(), gap_y(8.)])).add(|tui| {
+                    tui.ui(|ui| {
+                        ui.label(
+                            egui::RichText::new("Frame separator (regex) :")
+                                .size(32.)
+                                .underline(),
+                        );
+                    });
+                    tui.ui(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut cfg.separator)
+                                .font(egui::FontId::monospace(22.))
+                                .desired_width(600.),
+                        );
+                    });
+                });
+                if let Err(err) = cfg.separator_regex() {
+                    tui.ui(|ui| {
+                        ui.colored_label(Color32::LIGHT_RED, egui::RichText::new(err.to_string()));
+                    });
+                }
+                ui_visualization_kind_selector(tui, &mut cfg.kind);
+
+                tui.ui(separator);
+                ui_flex_spacer(tui);
+                tui.style(compose_style([flex(), align_self_center()]))
+                    .ui(|ui| {
+                        ui.code(
+                            egui::RichText::new(format!(
+                                "Reading text file at : {}",
+                                dropped.0.to_str().unwrap_or("")
+                            ))
+                            .size(22.),
+                        );
+                    });
+                tui.style(compose_style([flex(), align_self_center()]))
+                    .ui(|ui| {
+                        if start_button(ui).clicked() {
+                            let (path, cfg) = (dropped.0.clone(), cfg.clone());
+                            commands
+                                .insert_resource(LoadingTask::spawn(cfg.kind, move |progress| {
+                                    load_text_story(&path, &cfg, progress)
+                                }));
+                        }
+                    });
+            },
+        );
+}
+
+pub fn ui_volume_options(tui: &mut Tui, dropped: &DroppedFile, commands: &mut Commands) {
+    tui.style(compose_style([column(), full_size(), gap_y(16.)]))
+        .bg_add(
+            TuiBackground::new()
+                .with_background_color(Color32::from_gray(20))
+                .with_corner_radius(5.),
+            |tui| {
+                tui.ui(|ui| {
+                    ui.label(
+                        egui::RichText::new(
+                            "Expects an SVOL header (width, height, depth, frames as u32) \
+                             followed by little-endian f32 values.",
+                        )
+                        .size(22.),
+                    );
+                });
+                tui.ui(separator);
+                ui_flex_spacer(tui);
+                tui.style(compose_style([flex(), align_self_center()]))
+                    .ui(|ui| {
+                        ui.code(
+                            egui::RichText::new(format!(
+                                "Reading volume file at : {}",
+                                dropped.0.to_str().unwrap_or("")
+                            ))
+                            .size(22.),
+                        );
+                    });
+                tui.style(compose_style([flex(), align_self_center()]))
+                    .ui(|ui| {
+                        if start_button(ui).clicked() {
+                            let path = dropped.0.clone();
+                            commands.insert_resource(LoadingTask::spawn(None, move |progress| {
+                                load_raw_volume_story(&path, progress)
+                            }));
+                        }
+                    });
+            },
+        );
+}
+
+pub fn ui_graph_options(
+    tui: &mut Tui,
+    dropped: &DroppedFile,
+    cfg: &mut GraphConfiguration,
+    commands: &mut Commands,
+) {
+    tui.style(compose_style([column(), full_size(), gap_y(16.)]))
+        .bg_add(
+            TuiBackground::new()
+                .with_background_color(Color32::from_gray(20))
+                .with_corner_radius(5.),
+            |tui| {
+                tui.style(compose_style([row()])).add(|tui| {
+                    tui.label(egui::RichText::new("Format :").size(28.));
+                    tui.ui(|ui| {
+                        egui::ComboBox::from_id_salt("GRAPH_FORMAT_SELECTOR")
+                            .selected_text(egui::RichText::new(cfg.format.label()).size(28.))
+                            .show_ui(ui, |ui| {
+                                for format in GraphFormat::ALL {
+                                    ui.selectable_value(
+                                        &mut cfg.format,
+                                        format,
+                                        egui::RichText::new(format.label()).size(28.),
+                                    );
+                                }
+                            });
+                    });
+                });
+                tui.ui(|ui| {
+                    ui.label(
+                        egui::RichText::new(match cfg.format {
+                            GraphFormat::Dot => {
+                                "Node and edge `state` and `weight` attributes are shown."
+                            }
+                            GraphFormat::EdgeList => {
+                                "Columns : source, target, then optional weight, tick and state."
+                            }
+                        })
+                        .size(22.),
+                    );
+                });
+                tui.ui(separator);
+                ui_flex_spacer(tui);
+                tui.style(compose_style([flex(), align_self_center()]))
+                    .ui(|ui| {
+                        if start_button(ui).clicked() {
+                            let (path, cfg) = (dropped.0.clone(), cfg.clone());
+                            commands.insert_resource(LoadingTask::spawn(None, move |progress| {
+                                load_graph_story(&path, &cfg, progress)
+                            }));
+                        }
+                    });
+            },
+        );
+}
+
+pub fn ui_events_options(tui: &mut Tui, dropped: &DroppedFile, commands: &mut Commands) {
+    tui.style(compose_style([column(), full_size(), gap_y(16.)]))
+        .bg_add(
+            TuiBackground::new()
+                .with_background_color(Color32::from_gray(20))
+                .with_corner_radius(5.),
+            |tui| {
+                tui.ui(|ui| {
+                    ui.label(
+                        egui::RichText::new(
+                            "One event per line : `tick N`, `node ID [state] [weight]`, \
+                             `edge FROM TO [state] [weight]`, or `array V...`, \
+                             `compare I J`, `swap I J`, `set I V`.",
+                        )
+                        .size(22.),
+                    );
+                });
+                tui.ui(separator);
+                ui_flex_spacer(tui);
+                tui.style(compose_style([flex(), align_self_center()]))
+                    .ui(|ui| {
+                        if start_button(ui).clicked() {
+                            let path = dropped.0.clone();
+                            commands.insert_resource(LoadingTask::spawn(None, move |progress| {
+                                load_protocol_story(&path, progress)
+                            }));
+                        }
+                    });
+            },
+        );
+}
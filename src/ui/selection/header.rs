@@ -1,8 +1,6 @@
 use crate::{
-    FileTypeSelection,
-    file_id::FileTypeSuggestion,
     ui::{components::padded_button, style::*},
-    visualization::{DroppedFile, VisualizerState},
+    visualization::{VisualizerState, clear_dropped_file},
 };
 use bevy::{ecs::system::Commands, state::commands::CommandsStatesExt};
 use bevy_egui::egui::{self, Color32};
@@ -21,9 +19,7 @@ pub fn ui_selection_header(tui: &mut Tui, commands: &mut Commands) {
                     .fill(egui::Color32::DARK_RED);
 
                 if padded_button(ui, back, egui::Vec2::new(25., 12.)).clicked() {
-                    commands.remove_resource::<FileTypeSuggestion>();
-                    commands.remove_resource::<DroppedFile>();
-                    commands.remove_resource::<FileTypeSelection>();
+                    clear_dropped_file(commands);
                     commands.set_state(VisualizerState::Input);
                 }
             });
@@ -0,0 +1,325 @@
+use crate::{
+    loaders::binary::{
+        BYTES_PER_ROW, BinaryFile, HexConfiguration, MAX_GRID_BYTES, PatternSearch,
+        byte_grid_story, parse_offset, parse_pattern,
+    },
+    loading::LoadingTask,
+    ui::{
+        components::{separator, start_button, ui_flex_spacer},
+        selection::selector::ui_visualization_kind_selector,
+        style::*,
+    },
+    visualization::DroppedFile,
+};
+use bevy::prelude::Commands;
+use bevy_egui::egui::{self, Color32, RichText};
+use egui_taffy::{
+    Tui, TuiBuilderLogic,
+    bg::simple::{TuiBackground, TuiBuilderLogicWithBackground},
+};
+
+/// Rows drawn at once; only these are read from the map.
+const VISIBLE_ROWS: u64 = 32;
+/// Rows moved by one notch of the scroll wheel.
+const SCROLL_ROWS: u64 = 3;
+
+const FIELD_COLORS: [Color32; 4] = [
+    Color32::from_rgb(120, 190, 255),
+    Color32::from_rgb(255, 190, 100),
+    Color32::from_rgb(150, 230, 140),
+    Color32::from_rgb(230, 140, 230),
+];
+const MATCH_COLOR: Color32 = Color32::from_rgb(120, 90, 0);
+const CURRENT_MATCH_COLOR: Color32 = Color32::from_rgb(200, 140, 0);
+
+pub fn ui_hex_options(
+    tui: &mut Tui,
+    dropped: &DroppedFile,
+    file: Option<&BinaryFile>,
+    search: Option<&PatternSearch>,
+    cfg: &mut HexConfiguration,
+    commands: &mut Commands,
+) {
+    tui.style(compose_style([column(), full_size(), gap_y(16.)]))
+        .bg_add(
+            TuiBackground::new()
+                .with_background_color(Color32::from_gray(20))
+                .with_corner_radius(5.),
+            |tui| {
+                let Some(file) = file else {
+                    tui.label(RichText::new("Mapping file...").size(22.));
+                    return;
+                };
+                if let Some(err) = &file.error {
+                    tui.ui(|ui| {
+                        ui.colored_label(Color32::LIGHT_RED, RichText::new(err).size(22.));
+                    });
+                    return;
+                }
+                tui.label(
+                    RichText::new(format!("{} bytes ({:#x})", file.len(), file.len())).size(22.),
+                );
+
+                tui.style(compose_style([row()])).add(|tui| {
+                    tui.label(RichText::new("Go to :").size(28.));
+                    tui.ui(|ui| {
+                        let edit = ui.add(
+                            egui::TextEdit::singleline(&mut cfg.goto)
+                                .font(egui::FontId::monospace(22.))
+                                .hint_text("0x1f00")
+                                .desired_width(200.),
+                        );
+                        let submitted =
+                            edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        if (ui.button(RichText::new("Go").size(22.)).clicked() || submitted)
+                            && let Some(offset) = parse_offset(&cfg.goto)
+                        {
+                            cfg.show_offset(offset);
+                        }
+                    });
+                });
+                ui_pattern_search(tui, file, search, cfg, commands);
+
+                tui.ui(|ui| ui_hex_rows(ui, file, cfg));
+
+                if !file.fields.is_empty() {
+                    tui.label(RichText::new("Header :").size(28.).underline());
+                    tui.ui(|ui| {
+                        for (i, field) in file.fields.iter().enumerate() {
+                            let text =
+                                format!("{:#06x}  {} : {}", field.start, field.name, field.value);
+                            let color = FIELD_COLORS[i % FIELD_COLORS.len()];
+                            if ui
+                                .link(RichText::new(text).monospace().size(18.).color(color))
+                                .clicked()
+                            {
+                                cfg.show_offset(field.start);
+                            }
+                        }
+                    });
+                }
+
+                tui.ui(separator);
+                ui_byte_grid_options(tui, file, cfg);
+
+                ui_flex_spacer(tui);
+                tui.style(compose_style([flex(), align_self_center()]))
+                    .ui(|ui| {
+                        if start_button(ui).clicked() {
+                            let (path, bytes, cfg) = (dropped.0.clone(), file.share(), cfg.clone());
+                            commands.insert_resource(LoadingTask::spawn(
+                                cfg.kind,
+                                move |progress| {
+                                    progress.set_stage("Building the byte grid");
+                                    byte_grid_story(&path, bytes.bytes(), &cfg, progress)
+                                },
+                            ));
+                        }
+                    });
+            },
+        );
+}
+
+/// Patterns are searched in the background, the matches land in `cfg` once found.
+fn ui_pattern_search(
+    tui: &mut Tui,
+    file: &BinaryFile,
+    search: Option<&PatternSearch>,
+    cfg: &mut HexConfiguration,
+    commands: &mut Commands,
+) {
+    tui.style(compose_style([row()])).add(|tui| {
+        tui.label(RichText::new("Find :").size(28.));
+        tui.ui(|ui| {
+            let edit = ui.add(
+                egui::TextEdit::singleline(&mut cfg.pattern)
+                    .font(egui::FontId::monospace(22.))
+                    .hint_text("7f 45 4c 46 or \"text\"")
+                    .desired_width(300.),
+            );
+            let submitted = edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button(RichText::new("Find").size(22.)).clicked() || submitted {
+                match parse_pattern(&cfg.pattern) {
+                    Ok(pattern) => {
+                        if let Some(search) = search {
+                            search.progress.cancel();
+                        }
+                        cfg.pattern_error = None;
+                        cfg.matches.clear();
+                        commands.insert_resource(PatternSearch::spawn(file.share(), pattern));
+                    }
+                    Err(err) => {
+                        cfg.pattern_error = Some(err);
+                        cfg.matches.clear();
+                    }
+                }
+            }
+        });
+        if let Some(search) = search {
+            tui.ui(|ui| {
+                ui.add(
+                    egui::ProgressBar::new(search.progress.fraction().unwrap_or(0.))
+                        .show_percentage()
+                        .desired_width(200.),
+                );
+                if ui.button(RichText::new("Cancel").size(22.)).clicked() {
+                    search.progress.cancel();
+                    commands.remove_resource::<PatternSearch>();
+                }
+                // The task reports from another thread, so keep redrawing while it runs.
+                ui.ctx().request_repaint();
+            });
+        } else if let Some(err) = &cfg.pattern_error {
+            tui.ui(|ui| {
+                ui.colored_label(Color32::LIGHT_RED, RichText::new(err).size(22.));
+            });
+        } else if !cfg.matches.is_empty() {
+            tui.ui(|ui| {
+                let count = cfg.matches.len();
+                let previous = (cfg.current_match + count - 1) % count;
+                let next = (cfg.current_match + 1) % count;
+                for (label, target) in [("◀", previous), ("▶", next)] {
+                    if ui.button(RichText::new(label).size(22.)).clicked() {
+                        cfg.current_match = target;
+                        cfg.show_offset(cfg.matches[target]);
+                    }
+                }
+                let more = if cfg.matches_truncated { "+" } else { "" };
+                ui.label(
+                    RichText::new(format!("{} / {count}{more}", cfg.current_match + 1)).size(22.),
+                );
+            });
+        }
+    });
+}
+
+/// Draws the visible rows as offset, hex bytes and ASCII, with a slider to page through the
+/// file. Header fields are coloured and explained on hover.
+fn ui_hex_rows(ui: &mut egui::Ui, file: &BinaryFile, cfg: &mut HexConfiguration) {
+    let last_row = file.rows().saturating_sub(VISIBLE_ROWS);
+    cfg.row = cfg.row.min(last_row);
+    let bytes = file.bytes();
+
+    ui.horizontal(|ui| {
+        let rows = ui.vertical(|ui| {
+            for row in cfg.row..(cfg.row + VISIBLE_ROWS).min(file.rows()) {
+                let start = row * BYTES_PER_ROW;
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.;
+                    ui.label(
+                        RichText::new(format!("{start:010x}   "))
+                            .monospace()
+                            .size(18.)
+                            .color(Color32::GRAY),
+                    );
+                    let mut ascii = String::with_capacity(BYTES_PER_ROW as usize);
+                    for offset in start..start + BYTES_PER_ROW {
+                        let gap = if offset % 8 == 7 { "  " } else { " " };
+                        let Some(&byte) = bytes.get(offset as usize) else {
+                            ui.label(RichText::new(format!("  {gap}")).monospace().size(18.));
+                            continue;
+                        };
+                        ascii.push(if byte.is_ascii_graphic() || byte == b' ' {
+                            byte as char
+                        } else {
+                            '.'
+                        });
+                        let mut text = RichText::new(format!("{byte:02x}"))
+                            .monospace()
+                            .size(18.)
+                            .color(if byte == 0 {
+                                Color32::DARK_GRAY
+                            } else {
+                                Color32::LIGHT_GRAY
+                            });
+                        if let Some(background) = match_color(cfg, offset) {
+                            text = text.background_color(background);
+                        }
+                        let field = file
+                            .fields
+                            .iter()
+                            .position(|f| (f.start..f.start + f.len).contains(&offset));
+                        match field {
+                            Some(i) => {
+                                let field = &file.fields[i];
+                                ui.label(text.color(FIELD_COLORS[i % FIELD_COLORS.len()]))
+                                    .on_hover_text(format!("{} : {}", field.name, field.value));
+                            }
+                            None => {
+                                ui.label(text);
+                            }
+                        }
+                        ui.label(RichText::new(gap).monospace().size(18.));
+                    }
+                    ui.label(RichText::new(format!(" {ascii}")).monospace().size(18.));
+                });
+            }
+        });
+        if rows.response.contains_pointer() {
+            let scroll = ui.input(|i| i.raw_scroll_delta.y);
+            if scroll > 0. {
+                cfg.row = cfg.row.saturating_sub(SCROLL_ROWS);
+            } else if scroll < 0. {
+                cfg.row = (cfg.row + SCROLL_ROWS).min(last_row);
+            }
+        }
+        if last_row > 0 {
+            // The slider runs top to bottom like the rows, so it is inverted.
+            let mut from_bottom = last_row - cfg.row;
+            ui.spacing_mut().slider_width = rows.response.rect.height();
+            ui.add(
+                egui::Slider::new(&mut from_bottom, 0..=last_row)
+                    .vertical()
+                    .show_value(false),
+            );
+            cfg.row = last_row - from_bottom;
+        }
+    });
+}
+
+/// Background of a byte inside a search match, brighter for the current one.
+fn match_color(cfg: &HexConfiguration, offset: u64) -> Option<Color32> {
+    let i = cfg.matches.partition_point(|&start| start <= offset);
+    let start = *cfg.matches.get(i.checked_sub(1)?)?;
+    if offset >= start + cfg.match_len {
+        return None;
+    }
+    Some(if i - 1 == cfg.current_match {
+        CURRENT_MATCH_COLOR
+    } else {
+        MATCH_COLOR
+    })
+}
+
+fn ui_byte_grid_options(tui: &mut Tui, file: &BinaryFile, cfg: &mut HexConfiguration) {
+    tui.label(RichText::new("Byte grid :").size(28.).underline());
+    tui.style(compose_style([row()])).add(|tui| {
+        tui.ui(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut cfg.grid_start)
+                    .range(0..=file.len().saturating_sub(1))
+                    .hexadecimal(8, false, false)
+                    .prefix("start 0x"),
+            );
+            ui.add(
+                egui::DragValue::new(&mut cfg.grid_len)
+                    .range(1..=MAX_GRID_BYTES)
+                    .speed(16.)
+                    .suffix(" bytes"),
+            );
+            ui.add(
+                egui::DragValue::new(&mut cfg.grid_width)
+                    .range(1..=4096)
+                    .prefix("width "),
+            );
+            if ui.button(RichText::new("From view").size(22.)).clicked() {
+                cfg.grid_start = cfg.row * BYTES_PER_ROW;
+            }
+        });
+    });
+    tui.label(
+        RichText::new("Each byte becomes one cell valued 0 to 255, in rows of the given width.")
+            .size(22.),
+    );
+    ui_visualization_kind_selector(tui, &mut cfg.kind);
+}
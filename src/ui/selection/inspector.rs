@@ -0,0 +1,256 @@
+use std::collections::HashSet;
+
+use crate::{
+    loaders::structured::{ReadableConfiguration, StructuredDocument, bind_story, resolve_pointer},
+    loading::LoadingTask,
+    ui::{
+        components::{separator, start_button},
+        selection::selector::ui_visualization_kind_selector,
+        style::*,
+    },
+    visualization::DroppedFile,
+};
+use bevy::prelude::Commands;
+use bevy_egui::egui::{self, Color32, collapsing_header::CollapsingState};
+use egui_taffy::{
+    Tui, TuiBuilderLogic,
+    bg::simple::{TuiBackground, TuiBuilderLogicWithBackground},
+};
+use serde_json::Value;
+
+pub fn ui_readable_options(
+    tui: &mut Tui,
+    dropped: &DroppedFile,
+    document: Option<&StructuredDocument>,
+    cfg: &mut ReadableConfiguration,
+    commands: &mut Commands,
+) {
+    tui.style(compose_style([column(), full_size(), gap_y(16.)]))
+        .bg_add(
+            TuiBackground::new()
+                .with_background_color(Color32::from_gray(20))
+                .with_corner_radius(5.),
+            |tui| {
+                let Some(document) = document else {
+                    tui.label(egui::RichText::new("Parsing...").size(22.));
+                    return;
+                };
+                let root = match &document.root {
+                    Ok(root) => root,
+                    Err(err) => {
+                        tui.ui(|ui| {
+                            ui.colored_label(
+                                Color32::LIGHT_RED,
+                                egui::RichText::new(format!(
+                                    "{:?} parse error : {err}",
+                                    document.format
+                                ))
+                                .size(22.),
+                            );
+                        });
+                        return;
+                    }
+                };
+
+                tui.style(compose_style([row()])).add(|tui| {
+                    tui.label(egui::RichText::new("Search :").size(28.));
+                    tui.ui(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut cfg.search)
+                                .font(egui::FontId::monospace(22.))
+                                .desired_width(400.),
+                        );
+                    });
+                });
+                tui.style(compose_style([flex(), flex_shrink()])).ui(|ui| {
+                    egui::ScrollArea::vertical()
+                        .max_height(ui.available_height().max(200.))
+                        .auto_shrink([false, true])
+                        .show(ui, |ui| {
+                            let needle = cfg.search.to_lowercase();
+                            if cfg
+                                .search_matches
+                                .as_ref()
+                                .is_none_or(|(searched, _)| *searched != needle)
+                            {
+                                let mut pointers = HashSet::new();
+                                if !needle.is_empty() {
+                                    collect_matches("(root)", "", root, &needle, &mut pointers);
+                                }
+                                cfg.search_matches = Some((needle, pointers));
+                            }
+                            let matches = cfg
+                                .search_matches
+                                .as_ref()
+                                .filter(|(searched, _)| !searched.is_empty())
+                                .map(|(_, pointers)| pointers);
+                            ui_value_tree(ui, "(root)", "", root, matches);
+                        });
+                });
+
+                tui.ui(separator);
+                tui.style(compose_style([row()])).add(|tui| {
+                    tui.label(egui::RichText::new("Bind frames at :").size(28.));
+                    tui.ui(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut cfg.binding)
+                                .font(egui::FontId::monospace(22.))
+                                .hint_text("/frames/*/grid")
+                                .desired_width(400.),
+                        );
+                    });
+                    if cfg
+                        .binding_matches
+                        .as_ref()
+                        .is_none_or(|(binding, _)| *binding != cfg.binding)
+                    {
+                        let matches = resolve_pointer(root, &cfg.binding).len();
+                        cfg.binding_matches = Some((cfg.binding.clone(), matches));
+                    }
+                    let matches = cfg.binding_matches.as_ref().map_or(0, |(_, n)| *n);
+                    tui.label(egui::RichText::new(format!("{matches} match(es)")).size(22.));
+                });
+                ui_visualization_kind_selector(tui, &mut cfg.kind);
+                tui.style(compose_style([flex(), align_self_center()]))
+                    .ui(|ui| {
+                        if start_button(ui).clicked() {
+                            let (path, root) = (dropped.0.clone(), root.clone());
+                            let binding = cfg.binding.clone();
+                            commands.insert_resource(LoadingTask::spawn(
+                                cfg.kind,
+                                move |progress| {
+                                    progress.set_stage("Binding frames");
+                                    bind_story(&path, &root, &binding, progress)
+                                },
+                            ));
+                        }
+                    });
+            },
+        );
+}
+
+/// Draws `value` and its children as a collapsible tree. While a search is active, only the
+/// branches in `matches` are drawn, expanded.
+fn ui_value_tree(
+    ui: &mut egui::Ui,
+    key: &str,
+    pointer: &str,
+    value: &Value,
+    matches: Option<&HashSet<String>>,
+) {
+    if matches.is_some_and(|matches| !matches.contains(pointer)) {
+        return;
+    }
+    let children: Vec<(String, &Value)> = match value {
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v))
+            .collect(),
+        Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        _ => {
+            ui.horizontal(|ui| ui_value_row(ui, key, pointer, value));
+            return;
+        }
+    };
+
+    let id = ui.make_persistent_id(("inspector", pointer));
+    let mut state = CollapsingState::load_with_default_open(ui.ctx(), id, pointer.is_empty());
+    if matches.is_some() {
+        state.set_open(true);
+    }
+    state
+        .show_header(ui, |ui| ui_value_row(ui, key, pointer, value))
+        .body(|ui| {
+            for (child_key, child) in children {
+                ui_value_tree(
+                    ui,
+                    &child_key,
+                    &child_pointer(pointer, &child_key),
+                    child,
+                    matches,
+                );
+            }
+        });
+}
+
+fn ui_value_row(ui: &mut egui::Ui, key: &str, pointer: &str, value: &Value) {
+    let (badge, color) = badge(value);
+    ui.label(
+        egui::RichText::new(badge)
+            .monospace()
+            .small()
+            .color(Color32::BLACK)
+            .background_color(color),
+    );
+    ui.label(egui::RichText::new(key).strong());
+    ui.label(
+        egui::RichText::new(preview(value))
+            .monospace()
+            .color(Color32::LIGHT_GRAY),
+    );
+    if ui
+        .small_button("Copy path")
+        .on_hover_text(if pointer.is_empty() { "/" } else { pointer })
+        .clicked()
+    {
+        ui.ctx().copy_text(pointer.to_string());
+    }
+}
+
+fn badge(value: &Value) -> (&'static str, Color32) {
+    match value {
+        Value::Null => ("null", Color32::GRAY),
+        Value::Bool(_) => ("bool", Color32::from_rgb(230, 160, 60)),
+        Value::Number(_) => ("num", Color32::from_rgb(120, 200, 120)),
+        Value::String(_) => ("str", Color32::from_rgb(220, 200, 120)),
+        Value::Array(_) => ("arr", Color32::from_rgb(120, 170, 230)),
+        Value::Object(_) => ("obj", Color32::from_rgb(190, 140, 230)),
+    }
+}
+
+fn preview(value: &Value) -> String {
+    const MAX_PREVIEW: usize = 60;
+    match value {
+        Value::Array(items) => format!("[{}]", items.len()),
+        Value::Object(map) => format!("{{{}}}", map.len()),
+        Value::String(s) if s.chars().count() > MAX_PREVIEW => {
+            format!("{:?}…", s.chars().take(MAX_PREVIEW).collect::<String>())
+        }
+        other => other.to_string(),
+    }
+}
+
+fn child_pointer(pointer: &str, key: &str) -> String {
+    format!("{pointer}/{}", key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Adds to `pointers` every node whose key, or anything below it, contains `needle` (expected
+/// lowercase). Returns whether the node at `pointer` matched.
+fn collect_matches(
+    key: &str,
+    pointer: &str,
+    value: &Value,
+    needle: &str,
+    pointers: &mut HashSet<String>,
+) -> bool {
+    let mut matched = key.to_lowercase().contains(needle);
+    match value {
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                let child = child_pointer(pointer, &i.to_string());
+                matched |= collect_matches("", &child, item, needle, pointers);
+            }
+        }
+        Value::Object(map) => {
+            for (k, v) in map {
+                matched |= collect_matches(k, &child_pointer(pointer, k), v, needle, pointers);
+            }
+        }
+        scalar => matched |= scalar.to_string().to_lowercase().contains(needle),
+    }
+    if matched {
+        pointers.insert(pointer.to_string());
+    }
+    matched
+}
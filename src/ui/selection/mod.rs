@@ -1,4 +1,14 @@
-use crate::{FileTypeSelection, ui::style::*, visualization::DroppedFile};
+use crate::{
+    FileTypeSelection,
+    loaders::{
+        binary::{BinaryFile, PatternSearch},
+        numpy::NumpyFile,
+        structured::StructuredDocument,
+        table::TablePreview,
+    },
+    ui::style::*,
+    visualization::DroppedFile,
+};
 use bevy::prelude::*;
 use bevy_egui::{
     EguiContexts,
@@ -12,14 +22,28 @@ use egui_taffy::{
 };
 mod ft;
 mod header;
-mod selector;
+mod hex;
+mod inspector;
+mod numpy;
+pub mod selector;
+mod table;
 use ft::*;
 use header::*;
+use hex::*;
+use inspector::*;
+use numpy::*;
 use selector::*;
+use table::*;
+#[allow(clippy::too_many_arguments)]
 pub fn ui_selection_menu(
     mut commands: Commands,
     dropped: Res<DroppedFile>,
     mut selection: ResMut<FileTypeSelection>,
+    document: Option<Res<StructuredDocument>>,
+    preview: Option<Res<TablePreview>>,
+    numpy: Option<Res<NumpyFile>>,
+    binary: Option<Res<BinaryFile>>,
+    pattern_search: Option<Res<PatternSearch>>,
     mut ctx: EguiContexts,
 ) -> Result {
     let ctx = ctx.ctx_mut()?;
@@ -67,25 +91,58 @@ pub fn ui_selection_menu(
                                 FileTypeSelection::Executable(_, cfg) => {
                                     // tui.add(|tui| {
                                     // tui.ui(|ui| {
-                                    ui_executable_options(tui, &dropped, cfg);
+                                    ui_executable_options(tui, &dropped, cfg, &mut commands);
                                     // });
                                     // });
                                 }
-                                FileTypeSelection::Text(_) => {
-                                    tui.add(|tui| {
-                                        tui.ui(|ui| {
-                                            ui.code(
-                                                egui::RichText::new("Reading text file at :")
-                                                    .size(22.),
-                                            );
-                                            ui.code(
-                                                egui::RichText::new(
-                                                    dropped.0.to_str().unwrap_or(""),
-                                                )
-                                                .size(22.),
-                                            );
-                                        });
-                                    });
+                                FileTypeSelection::Text(_, cfg) => {
+                                    ui_text_options(tui, &dropped, cfg, &mut commands);
+                                }
+                                FileTypeSelection::Readable(_, cfg) => {
+                                    ui_readable_options(
+                                        tui,
+                                        &dropped,
+                                        document.as_deref(),
+                                        cfg,
+                                        &mut commands,
+                                    );
+                                }
+                                FileTypeSelection::Table(_, cfg) => {
+                                    ui_table_options(
+                                        tui,
+                                        &dropped,
+                                        preview.as_deref(),
+                                        cfg,
+                                        &mut commands,
+                                    );
+                                }
+                                FileTypeSelection::Volume(_) => {
+                                    ui_volume_options(tui, &dropped, &mut commands);
+                                }
+                                FileTypeSelection::Graph(_, cfg) => {
+                                    ui_graph_options(tui, &dropped, cfg, &mut commands);
+                                }
+                                FileTypeSelection::Events(_) => {
+                                    ui_events_options(tui, &dropped, &mut commands);
+                                }
+                                FileTypeSelection::NumPy(_, cfg) => {
+                                    ui_numpy_options(
+                                        tui,
+                                        &dropped,
+                                        numpy.as_deref(),
+                                        cfg,
+                                        &mut commands,
+                                    );
+                                }
+                                FileTypeSelection::Unknown(_, cfg) => {
+                                    ui_hex_options(
+                                        tui,
+                                        &dropped,
+                                        binary.as_deref(),
+                                        pattern_search.as_deref(),
+                                        cfg,
+                                        &mut commands,
+                                    );
                                 }
                                 _ => {}
                             }
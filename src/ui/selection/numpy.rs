@@ -0,0 +1,140 @@
+use crate::{
+    loaders::numpy::{NpyArray, NumpyConfiguration, NumpyFile, numpy_story},
+    loading::LoadingTask,
+    ui::{
+        components::{separator, start_button, ui_flex_spacer},
+        selection::selector::ui_visualization_kind_selector,
+        style::*,
+    },
+    visualization::DroppedFile,
+};
+use bevy::prelude::Commands;
+use bevy_egui::egui::{self, Color32};
+use egui_taffy::{
+    Tui, TuiBuilderLogic,
+    bg::simple::{TuiBackground, TuiBuilderLogicWithBackground},
+};
+
+pub fn ui_numpy_options(
+    tui: &mut Tui,
+    dropped: &DroppedFile,
+    file: Option<&NumpyFile>,
+    cfg: &mut NumpyConfiguration,
+    commands: &mut Commands,
+) {
+    tui.style(compose_style([column(), full_size(), gap_y(16.)]))
+        .bg_add(
+            TuiBackground::new()
+                .with_background_color(Color32::from_gray(20))
+                .with_corner_radius(5.),
+            |tui| {
+                let arrays = match file.map(|file| &file.arrays) {
+                    None => {
+                        tui.label(egui::RichText::new("Reading arrays...").size(22.));
+                        return;
+                    }
+                    Some(Err(err)) => {
+                        tui.ui(|ui| {
+                            ui.colored_label(
+                                Color32::LIGHT_RED,
+                                egui::RichText::new(err).size(22.),
+                            );
+                        });
+                        return;
+                    }
+                    Some(Ok(arrays)) => arrays,
+                };
+                tui.label(egui::RichText::new("Arrays :").size(28.).underline());
+                tui.ui(|ui| ui_array_list(ui, arrays, cfg));
+
+                let Some(array) = arrays.get(cfg.array) else {
+                    return;
+                };
+                if array.shape.len() == 3 {
+                    ui_time_axis_selector(tui, array, cfg);
+                }
+                if array.shape.len() > 3 {
+                    tui.ui(|ui| {
+                        ui.colored_label(
+                            Color32::LIGHT_RED,
+                            egui::RichText::new("Only 1D, 2D and 3D arrays can be visualized.")
+                                .size(22.),
+                        );
+                    });
+                }
+                if cfg.time_axis.is_some() || array.shape.len() < 3 {
+                    ui_visualization_kind_selector(tui, &mut cfg.kind);
+                }
+
+                tui.ui(separator);
+                ui_flex_spacer(tui);
+                tui.style(compose_style([flex(), align_self_center()]))
+                    .ui(|ui| {
+                        if start_button(ui).clicked() {
+                            let (path, array, cfg) =
+                                (dropped.0.clone(), array.clone(), cfg.clone());
+                            commands.insert_resource(LoadingTask::spawn(
+                                cfg.kind,
+                                move |progress| {
+                                    progress.set_stage("Building frames");
+                                    numpy_story(&path, &array, &cfg, progress)
+                                },
+                            ));
+                        }
+                    });
+            },
+        );
+}
+
+fn ui_array_list(ui: &mut egui::Ui, arrays: &[NpyArray], cfg: &mut NumpyConfiguration) {
+    egui::ScrollArea::vertical()
+        .max_height(220.)
+        .show(ui, |ui| {
+            for (i, array) in arrays.iter().enumerate() {
+                let text = format!(
+                    "{}   {}   {}{}",
+                    array.name,
+                    array.dtype.label(),
+                    array.shape_label(),
+                    if array.fortran_order {
+                        "   (fortran order)"
+                    } else {
+                        ""
+                    },
+                );
+                if ui
+                    .selectable_value(
+                        &mut cfg.array,
+                        i,
+                        egui::RichText::new(text).monospace().size(22.),
+                    )
+                    .changed()
+                {
+                    cfg.time_axis = None;
+                }
+            }
+        });
+}
+
+fn ui_time_axis_selector(tui: &mut Tui, array: &NpyArray, cfg: &mut NumpyConfiguration) {
+    let label = |axis: Option<usize>| match axis {
+        None => "None (show as a volume)".to_string(),
+        Some(axis) => format!("Axis {axis} ({} frames)", array.shape[axis]),
+    };
+    tui.style(compose_style([row()])).add(|tui| {
+        tui.label(egui::RichText::new("Time axis :").size(28.));
+        tui.ui(|ui| {
+            egui::ComboBox::from_id_salt("NUMPY_TIME_AXIS_SELECTOR")
+                .selected_text(egui::RichText::new(label(cfg.time_axis)).size(28.))
+                .show_ui(ui, |ui| {
+                    for axis in [None, Some(0), Some(1), Some(2)] {
+                        ui.selectable_value(
+                            &mut cfg.time_axis,
+                            axis,
+                            egui::RichText::new(label(axis)).size(28.),
+                        );
+                    }
+                });
+        });
+    });
+}
@@ -1,4 +1,4 @@
-use crate::{FileTypeSelection, ui::style::*};
+use crate::{FileTypeSelection, ui::style::*, visualization::VisualizationKind};
 use bevy_egui::egui;
 use egui_taffy::{Tui, TuiBuilderLogic};
 
@@ -14,3 +14,26 @@ pub fn ui_file_type_selector(tui: &mut Tui, selection: &mut FileTypeSelection) {
         });
     });
 }
+
+/// `None` leaves the choice to the renderer registry once the story is loaded.
+pub fn ui_visualization_kind_selector(tui: &mut Tui, kind: &mut Option<VisualizationKind>) {
+    tui.style(compose_style([row()])).add(|tui| {
+        tui.label(egui::RichText::new("Visualize as :").size(28.));
+        tui.ui(|ui| {
+            egui::ComboBox::from_id_salt("VISUALIZATION_KIND_SELECTOR")
+                .selected_text(
+                    egui::RichText::new(kind.map_or("Automatic", |k| k.label())).size(28.),
+                )
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(kind, None, egui::RichText::new("Automatic").size(28.));
+                    for option in VisualizationKind::ALL {
+                        ui.selectable_value(
+                            kind,
+                            Some(option),
+                            egui::RichText::new(option.label()).size(28.),
+                        );
+                    }
+                });
+        });
+    });
+}
@@ -0,0 +1,143 @@
+use crate::{
+    loaders::table::{TableConfiguration, TableLayout, TablePreview, load_table_story},
+    loading::LoadingTask,
+    ui::{
+        components::{separator, start_button},
+        selection::selector::ui_visualization_kind_selector,
+        style::*,
+    },
+    visualization::DroppedFile,
+};
+use bevy::prelude::Commands;
+use bevy_egui::egui::{self, Color32};
+use egui_taffy::{
+    Tui, TuiBuilderLogic,
+    bg::simple::{TuiBackground, TuiBuilderLogicWithBackground},
+};
+
+pub fn ui_table_options(
+    tui: &mut Tui,
+    dropped: &DroppedFile,
+    preview: Option<&TablePreview>,
+    cfg: &mut TableConfiguration,
+    commands: &mut Commands,
+) {
+    tui.style(compose_style([column(), full_size(), gap_y(16.)]))
+        .bg_add(
+            TuiBackground::new()
+                .with_background_color(Color32::from_gray(20))
+                .with_corner_radius(5.),
+            |tui| {
+                let Some(preview) = preview else {
+                    tui.label(egui::RichText::new("Detecting columns...").size(22.));
+                    return;
+                };
+                tui.label(
+                    egui::RichText::new(format!(
+                        "Delimiter : {}   Header : {}",
+                        match preview.delimiter {
+                            Some('\t') => "tab".to_string(),
+                            Some(d) => format!("'{d}'"),
+                            None => "whitespace".to_string(),
+                        },
+                        if preview.has_header { "yes" } else { "no" },
+                    ))
+                    .size(22.),
+                );
+
+                tui.style(compose_style([row()])).add(|tui| {
+                    tui.label(egui::RichText::new("Layout :").size(28.));
+                    tui.ui(|ui| {
+                        egui::ComboBox::from_id_salt("TABLE_LAYOUT_SELECTOR")
+                            .selected_text(egui::RichText::new(cfg.layout.label()).size(28.))
+                            .show_ui(ui, |ui| {
+                                for layout in TableLayout::ALL {
+                                    ui.selectable_value(
+                                        &mut cfg.layout,
+                                        layout,
+                                        egui::RichText::new(layout.label()).size(28.),
+                                    );
+                                }
+                            });
+                    });
+                });
+
+                if cfg.layout != TableLayout::Matrix {
+                    tui.ui(|ui| ui_column_mapping(ui, preview, cfg));
+                }
+                tui.style(compose_style([flex(), flex_shrink()]))
+                    .ui(|ui| ui_preview_rows(ui, preview));
+
+                ui_visualization_kind_selector(tui, &mut cfg.kind);
+                tui.ui(separator);
+
+                tui.style(compose_style([flex(), align_self_center()]))
+                    .ui(|ui| {
+                        if start_button(ui).clicked() {
+                            let path = dropped.0.clone();
+                            let (cfg, preview) = (cfg.clone(), preview.clone());
+                            commands
+                                .insert_resource(LoadingTask::spawn(cfg.kind, move |progress| {
+                                    load_table_story(&path, &cfg, &preview, progress)
+                                }));
+                        }
+                    });
+            },
+        );
+}
+
+fn ui_column_mapping(ui: &mut egui::Ui, preview: &TablePreview, cfg: &mut TableConfiguration) {
+    let mapping = &mut cfg.mapping;
+    let mut roles = vec![("Tick", &mut mapping.tick)];
+    if cfg.layout == TableLayout::Long {
+        roles.extend([
+            ("X", &mut mapping.x),
+            ("Y", &mut mapping.y),
+            ("Value", &mut mapping.value),
+            ("Category", &mut mapping.category),
+        ]);
+    }
+    egui::Grid::new("TABLE_COLUMN_MAPPING")
+        .num_columns(2)
+        .spacing([24., 8.])
+        .show(ui, |ui| {
+            for (role, column) in roles {
+                ui.label(egui::RichText::new(format!("{role} column :")).size(22.));
+                let name = |c: &Option<usize>| {
+                    c.and_then(|i| preview.headers.get(i))
+                        .map_or("(none)".to_string(), Clone::clone)
+                };
+                egui::ComboBox::from_id_salt(("TABLE_COLUMN", role))
+                    .selected_text(egui::RichText::new(name(column)).size(22.))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(column, None, "(none)");
+                        for (i, header) in preview.headers.iter().enumerate() {
+                            ui.selectable_value(column, Some(i), header);
+                        }
+                    });
+                ui.end_row();
+            }
+        });
+}
+
+fn ui_preview_rows(ui: &mut egui::Ui, preview: &TablePreview) {
+    egui::ScrollArea::both()
+        .max_height(220.)
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            egui::Grid::new("TABLE_PREVIEW")
+                .striped(true)
+                .show(ui, |ui| {
+                    for header in &preview.headers {
+                        ui.label(egui::RichText::new(header).strong().monospace());
+                    }
+                    ui.end_row();
+                    for row in &preview.rows {
+                        for cell in row {
+                            ui.label(egui::RichText::new(cell).monospace());
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+}
@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, Color32, RichText, Stroke},
+};
+
+use crate::{
+    config::HistoryConfig,
+    session::{Bookmark, Session, play_frames},
+    story::{LoopMode, Playhead, Story},
+    ui::markers::STORY_MARKER_COLOR,
+};
+
+const RANGE_COLOR: Color32 = Color32::from_rgba_premultiplied(60, 90, 140, 90);
+
+/// Play/pause, stepping and a scrubber over the frames of `story`, with its markers and the
+/// session bookmarks, drawn inline in the bottom panel. Seeking only moves the playhead, the
+/// renderers redraw on their next run. The loop mode and the in/out range are edited on the
+/// session, which is only touched when they change so that it is not saved every frame.
+pub fn ui_transport(
+    ui: &mut egui::Ui,
+    story: &Story,
+    playhead: &mut Playhead,
+    mut session: Option<Mut<Session>>,
+) {
+    let len = story.len();
+    let frames = play_frames(story, session.as_deref());
+    let last = len.saturating_sub(1);
+    let at_start = playhead.index == 0;
+    let at_end = playhead.index >= last;
+
+    if ui
+        .add_enabled(!at_start, egui::Button::new(RichText::new("⏮")))
+        .on_hover_text("Jump to start (Home)")
+        .clicked()
+    {
+        playhead.seek(0, len);
+    }
+    if ui
+        .add_enabled(!at_start, egui::Button::new(RichText::new("◀")))
+        .on_hover_text("Previous tick (Left)")
+        .clicked()
+    {
+        playhead.step(-1, len);
+    }
+    for (reverse, play_icon, play_hint) in
+        [(true, "⏴", "Play backwards (J)"), (false, "▶", "Play (L)")]
+    {
+        let playing = !playhead.paused && playhead.reverse == reverse;
+        let (icon, hint) = if playing {
+            ("⏸", "Pause (K)")
+        } else {
+            (play_icon, play_hint)
+        };
+        if ui.button(RichText::new(icon)).on_hover_text(hint).clicked() {
+            if playing {
+                playhead.paused = true;
+            } else {
+                playhead.play(reverse, frames.clone());
+            }
+        }
+    }
+    if ui
+        .add_enabled(!at_end, egui::Button::new(RichText::new("▶|")))
+        .on_hover_text("Next tick (Right)")
+        .clicked()
+    {
+        playhead.step(1, len);
+    }
+    if ui
+        .add_enabled(!at_end, egui::Button::new(RichText::new("⏭")))
+        .on_hover_text("Jump to end (End)")
+        .clicked()
+    {
+        playhead.seek(last, len);
+    }
+
+    let mut index = playhead.index;
+    let response = ui.add_enabled(
+        len > 1,
+        egui::Slider::new(&mut index, 0..=last).show_value(false),
+    );
+    if response.changed() && index != playhead.index {
+        playhead.seek(index, len);
+    }
+    // The handle centre travels between the rail ends, inset by its radius.
+    let rail = response
+        .rect
+        .shrink2(egui::Vec2::new(response.rect.height() / 2.5, 0.));
+    let mark = |tick: u64, color: Color32| {
+        let t = story.index_of_tick(tick) as f32 / last.max(1) as f32;
+        ui.painter().vline(
+            rail.left() + rail.width() * t,
+            response.rect.y_range(),
+            Stroke::new(2., color),
+        );
+    };
+    if let Some((start, out)) = session.as_ref().and_then(|s| s.range) {
+        let x = |tick: u64| {
+            rail.left() + rail.width() * story.index_of_tick(tick) as f32 / last.max(1) as f32
+        };
+        ui.painter().rect_filled(
+            egui::Rect::from_x_y_ranges(x(start)..=x(out), response.rect.y_range()),
+            2.,
+            RANGE_COLOR,
+        );
+    }
+    for marker in &story.markers {
+        mark(marker.tick, STORY_MARKER_COLOR);
+    }
+    for bookmark in session.iter().flat_map(|s| &s.bookmarks) {
+        let [r, g, b] = bookmark.color;
+        mark(bookmark.tick, Color32::from_rgb(r, g, b));
+    }
+    let tick = |i: usize| story.tick(i).unwrap_or_default();
+    ui.label(
+        RichText::new(format!(
+            "tick {} / {}  (frame {} of {len})",
+            tick(playhead.index),
+            tick(last),
+            playhead.index + 1,
+        ))
+        .monospace(),
+    );
+
+    let Some(session) = &mut session else {
+        return;
+    };
+    let mut edited = Session::clone(session);
+    egui::ComboBox::from_id_salt("LOOP_MODE")
+        .selected_text(edited.loop_mode.label())
+        .width(90.)
+        .show_ui(ui, |ui| {
+            for mode in LoopMode::ALL {
+                ui.selectable_value(&mut edited.loop_mode, mode, mode.label());
+            }
+        });
+    let current = tick(playhead.index);
+    if ui
+        .button("[")
+        .on_hover_text("Set the in point (I)")
+        .clicked()
+    {
+        edited.set_in(current, story);
+    }
+    if ui
+        .button("]")
+        .on_hover_text("Set the out point (O)")
+        .clicked()
+    {
+        edited.set_out(current, story);
+    }
+    if let Some((start, out)) = edited.range {
+        ui.label(RichText::new(format!("{start}–{out}")).monospace());
+        if ui
+            .small_button("✕")
+            .on_hover_text("Clear the range")
+            .clicked()
+        {
+            edited.range = None;
+        }
+    }
+    if edited != **session {
+        **session = edited;
+    }
+}
+
+/// J and L play backwards and forwards, K pauses or resumes, Left and Right step one tick, Home
+/// and End jump to either side of the story, I and O set the in and out points, B bookmarks the
+/// current tick and F3 shows the history overlay. Ignored while egui has keyboard focus, e.g. in
+/// a text field.
+pub fn transport_shortcut_system(
+    mut contexts: EguiContexts,
+    keys: Res<ButtonInput<KeyCode>>,
+    story: Res<Story>,
+    mut playhead: ResMut<Playhead>,
+    mut history: ResMut<HistoryConfig>,
+    session: Option<ResMut<Session>>,
+) -> Result {
+    if contexts.ctx_mut()?.wants_keyboard_input() {
+        return Ok(());
+    }
+    let len = story.len();
+    let frames = play_frames(&story, session.as_deref());
+    if keys.just_pressed(KeyCode::KeyJ) {
+        playhead.play(true, frames.clone());
+    }
+    if keys.just_pressed(KeyCode::KeyK) {
+        playhead.toggle(frames.clone());
+    }
+    if keys.just_pressed(KeyCode::KeyL) {
+        playhead.play(false, frames);
+    }
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        playhead.step(-1, len);
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        playhead.step(1, len);
+    }
+    if keys.just_pressed(KeyCode::Home) {
+        playhead.seek(0, len);
+    }
+    if keys.just_pressed(KeyCode::End) {
+        playhead.seek(len.saturating_sub(1), len);
+    }
+    if let Some(mut session) = session
+        && let Some(tick) = story.tick(playhead.index)
+    {
+        if keys.just_pressed(KeyCode::KeyI) {
+            session.set_in(tick, &story);
+        }
+        if keys.just_pressed(KeyCode::KeyO) {
+            session.set_out(tick, &story);
+        }
+        if keys.just_pressed(KeyCode::KeyB) {
+            let count = session.bookmarks.len();
+            session.add_bookmark(Bookmark::new(tick, count));
+        }
+    }
+    if keys.just_pressed(KeyCode::F3) {
+        history.overlay = !history.overlay;
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    EguiContexts,
+    egui::{self, RichText},
+};
+
+use crate::renderers::{
+    heatmap::Colormap,
+    volume::{Axis, VolumeInfo, VolumeSettings},
+};
+
+/// Slice scrubbing, voxel threshold and colormap for the volume view.
+pub fn ui_volume_panel(
+    mut contexts: EguiContexts,
+    mut settings: ResMut<VolumeSettings>,
+    info: Option<Res<VolumeInfo>>,
+) -> Result {
+    let Some(info) = info else {
+        return Ok(());
+    };
+    let ctx = contexts.ctx_mut()?;
+    egui::Window::new("Volume")
+        .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10., 90.))
+        .resizable(false)
+        .show(ctx, |ui| {
+            // Edit a copy so that drawing the panel does not flag the settings as changed.
+            let mut colormap = settings.colormap;
+            let mut show_slices = settings.show_slices;
+            let mut slices = settings.slices;
+            let mut show_voxels = settings.show_voxels;
+            let mut threshold = settings.threshold;
+
+            egui::ComboBox::from_id_salt("VOLUME_COLORMAP")
+                .selected_text(colormap.label())
+                .show_ui(ui, |ui| {
+                    for map in Colormap::ALL {
+                        ui.selectable_value(&mut colormap, map, map.label());
+                    }
+                });
+
+            ui.separator();
+            ui.label(RichText::new("Slices").strong());
+            for (i, axis) in Axis::ALL.into_iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut show_slices[i], format!("{axis:?}"));
+                    ui.add_enabled(
+                        show_slices[i],
+                        egui::Slider::new(&mut slices[i], 0..=info.dims[i].saturating_sub(1)),
+                    );
+                });
+            }
+
+            ui.separator();
+            ui.add_enabled(
+                info.voxels_available,
+                egui::Checkbox::new(&mut show_voxels, "Voxels above threshold"),
+            );
+            ui.add_enabled(
+                info.voxels_available && show_voxels,
+                egui::Slider::new(&mut threshold, info.range.0..=info.range.1),
+            );
+            if !info.voxels_available {
+                ui.label(RichText::new("Too many voxels, use the slices").small());
+            }
+
+            if colormap != settings.colormap
+                || show_slices != settings.show_slices
+                || slices != settings.slices
+                || show_voxels != settings.show_voxels
+                || threshold != settings.threshold
+            {
+                settings.colormap = colormap;
+                settings.show_slices = show_slices;
+                settings.slices = slices;
+                settings.show_voxels = show_voxels;
+                settings.threshold = threshold;
+            }
+        });
+    Ok(())
+}
@@ -1,4 +1,8 @@
-use bevy::{camera::Viewport, prelude::*, window::PrimaryWindow};
+use bevy::{
+    camera::Viewport,
+    prelude::*,
+    window::{PrimaryWindow, WindowResized, WindowScaleFactorChanged},
+};
 #[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum ViewportId {
     Ui,
@@ -8,21 +12,72 @@ pub enum ViewportId {
 
 use std::collections::HashMap;
 
+/// Room taken from the edges of the window, in physical pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Insets {
+    pub top: f32,
+    pub bottom: f32,
+    pub left: f32,
+    pub right: f32,
+}
+
+impl Insets {
+    fn add(self, other: Insets) -> Insets {
+        Insets {
+            top: self.top + other.top,
+            bottom: self.bottom + other.bottom,
+            left: self.left + other.left,
+            right: self.right + other.right,
+        }
+    }
+}
+
+/// The single source of truth for camera viewports. The insets of [`ViewportId::Ui`] are the
+/// panels, taken from every camera; the insets of any other id only from the cameras tagged with
+/// it.
 #[derive(Resource, Debug, Default)]
 pub struct Viewports {
-    viewports: HashMap<ViewportId, Viewport>,
+    viewports: HashMap<ViewportId, Insets>,
 }
 impl Viewports {
     pub fn get_mut(&mut self) -> MutableViewports<'_> {
         MutableViewports(self)
     }
+
+    /// Where a camera tagged `id` draws in a window of `window` physical pixels, `None` when it
+    /// covers the whole window. That is also the answer when there is no room left, e.g. once
+    /// minimised to zero size, since a viewport must never reach past the window.
+    pub fn layout(&self, id: ViewportId, window: UVec2) -> Option<Viewport> {
+        if id == ViewportId::Ui {
+            return None;
+        }
+        let insets = self.insets(ViewportId::Ui).add(self.insets(id));
+        let size = window.as_vec2();
+        let left = insets.left.clamp(0.0, size.x);
+        let top = insets.top.clamp(0.0, size.y);
+        let right = insets.right.clamp(0.0, size.x - left);
+        let bottom = insets.bottom.clamp(0.0, size.y - top);
+        let physical_size = UVec2::new(
+            (size.x - left - right) as u32,
+            (size.y - top - bottom) as u32,
+        );
+        (physical_size.x > 0 && physical_size.y > 0).then(|| Viewport {
+            physical_position: UVec2::new(left as u32, top as u32),
+            physical_size,
+            ..default()
+        })
+    }
+
+    pub fn insets(&self, id: ViewportId) -> Insets {
+        self.viewports.get(&id).copied().unwrap_or_default()
+    }
 }
 /// Temporary façade that exposes mutation. Should only be consumed in one place - problematic
 /// side effects are too easy to implement.
 pub struct MutableViewports<'a>(&'a mut Viewports);
 
 impl<'a> std::ops::Deref for MutableViewports<'a> {
-    type Target = HashMap<ViewportId, Viewport>;
+    type Target = HashMap<ViewportId, Insets>;
 
     fn deref(&self) -> &Self::Target {
         &self.0.viewports
@@ -43,39 +98,62 @@ pub struct ViewportChanged {
     pub left: f32,
     pub right: f32,
 }
-fn viewport_event_system(
+/// Records the insets sent with [`ViewportChanged`], the only place [`Viewports`] is mutated.
+pub fn viewport_event_system(
     mut events: MessageReader<ViewportChanged>,
     mut viewports: ResMut<Viewports>,
-    //TODO: Handle multiple windows ?
-    window: Single<&mut Window, With<PrimaryWindow>>,
-    // windows: Res<Windows>,
 ) {
-    // let window = windows.get_primary().unwrap();
-    let win_width = window.physical_width() as f32;
-    let win_height = window.physical_height() as f32;
-
     for ev in events.read() {
-        let left = ev.left.clamp(0.0, win_width);
-        let right = ev.right.clamp(0.0, win_width);
-        let top = ev.top.clamp(0.0, win_height);
-        let bottom = ev.bottom.clamp(0.0, win_height);
-
-        let pos = UVec2::new(left as u32, top as u32);
-        let size = UVec2::new(
-            (win_width - left - right).max(0.0) as u32,
-            (win_height - top - bottom).max(0.0) as u32,
-        );
+        let insets = Insets {
+            top: ev.top.max(0.0),
+            bottom: ev.bottom.max(0.0),
+            left: ev.left.max(0.0),
+            right: ev.right.max(0.0),
+        };
+        if viewports.insets(ev.id) != insets {
+            viewports.get_mut().insert(ev.id, insets);
+        }
+    }
+}
 
-        viewports.get_mut().insert(
-            ev.id,
-            Viewport {
-                physical_position: pos,
-                physical_size: size,
-                ..Default::default()
-            },
-        );
+/// Gives each camera tagged with a [`ViewportId`] its viewport from [`Viewports`]. Cameras are
+/// only touched when their viewport actually moves.
+pub fn apply_viewports_system(
+    viewports: Res<Viewports>,
+    //TODO: Handle multiple windows ?
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(&ViewportId, &mut Camera)>,
+) {
+    let size = window.physical_size();
+    for (id, mut camera) in &mut cameras {
+        let viewport = viewports.layout(*id, size);
+        let unchanged = match (&camera.viewport, &viewport) {
+            (Some(old), Some(new)) => {
+                old.physical_position == new.physical_position
+                    && old.physical_size == new.physical_size
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        if !unchanged {
+            camera.viewport = viewport;
+        }
     }
 }
+
+/// Lays the cameras out again when the panels, the window size or its scale factor change, or
+/// when a camera joins a viewport.
+pub fn viewports_need_layout(
+    viewports: Res<Viewports>,
+    mut resized: MessageReader<WindowResized>,
+    mut rescaled: MessageReader<WindowScaleFactorChanged>,
+    added: Query<(), Added<ViewportId>>,
+) -> bool {
+    let resized = resized.read().count() > 0;
+    let rescaled = rescaled.read().count() > 0;
+    viewports.is_changed() || resized || rescaled || !added.is_empty()
+}
+
 #[derive(Resource, Debug, Default)]
 pub struct UiSize {
     pub top: f32,
@@ -1,15 +1,58 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use bevy::prelude::*;
 use storyframe::{Renderer, core::configuration::Configuration, engine::VisualizationEngine};
 
-use crate::file_id::{FileTypeSuggestion, suggestion};
+use crate::{
+    ExecutableConfiguration, FileTypeSelection,
+    error::{ErrorStage, VisualizationError, report_error},
+    file_id::{FileTypeSuggestion, suggestion},
+    loaders::{
+        LoadError,
+        binary::{BinaryFile, PatternSearch},
+        numpy::NumpyFile,
+        process::program_command,
+        structured::StructuredDocument,
+        table::TablePreview,
+    },
+    loading::{FailedLoad, LoadingTask},
+    renderers::registry::{RendererCandidates, SelectRenderer},
+    search::{SearchResults, SearchTask},
+    session::Session,
+    story::{Playhead, Story},
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum VisualizationKind {
-    // Heatmap,
+    Heatmap,
+    #[default]
     Grid,
-    // Volume,
+    Volume,
+    Graph,
+    Array,
+}
+
+impl VisualizationKind {
+    pub const ALL: [VisualizationKind; 5] = [
+        VisualizationKind::Grid,
+        VisualizationKind::Heatmap,
+        VisualizationKind::Volume,
+        VisualizationKind::Graph,
+        VisualizationKind::Array,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            VisualizationKind::Heatmap => "Heatmap",
+            VisualizationKind::Grid => "Grid",
+            VisualizationKind::Volume => "Volume",
+            VisualizationKind::Graph => "Graph",
+            VisualizationKind::Array => "Array bars",
+        }
+    }
 }
 
 #[derive(States, Default, Debug, Clone, Eq, PartialEq, Hash)]
@@ -18,33 +61,111 @@ pub enum VisualizerState {
     Input,
     Loading,
     Grid,
+    Heatmap,
+    Volume,
+    Graph,
+    Array,
+    Error(VisualizationError),
 }
 
+impl From<VisualizationKind> for VisualizerState {
+    fn from(kind: VisualizationKind) -> Self {
+        match kind {
+            VisualizationKind::Heatmap => VisualizerState::Heatmap,
+            VisualizationKind::Grid => VisualizerState::Grid,
+            VisualizationKind::Volume => VisualizerState::Volume,
+            VisualizationKind::Graph => VisualizerState::Graph,
+            VisualizationKind::Array => VisualizerState::Array,
+        }
+    }
+}
+
+/// A renderer has its scene up, as opposed to the drop screen, loading or the error screen.
+pub fn in_scene_state(state: Res<State<VisualizerState>>) -> bool {
+    !matches!(
+        state.get(),
+        VisualizerState::Input | VisualizerState::Loading | VisualizerState::Error(_)
+    )
+}
+
+#[derive(Message)]
+pub struct LoadVisualization(pub VisualizationKind);
+
+/// Asks the camera to frame an axis-aligned box, sent by renderers once their scene is spawned.
+#[derive(Message)]
+pub struct FrameBounds {
+    pub center: Vec3,
+    pub half_extents: Vec3,
+}
+
+/// Sent to leave the current visualization and go back to dropping a file.
 #[derive(Message)]
-pub struct LoadVisualization(VisualizationKind);
+pub struct CloseVisualization;
 
 #[derive(Deref, Resource)]
 pub struct VisualizationSettings<T>(T);
 
+/// The storyframe engine of a dropped program. Its configuration reaches the renderers as
+/// [`VisualizationSettings`], while the program's output becomes the [`Story`] they draw.
 #[derive(Deref, Resource)]
 pub struct Engine(VisualizationEngine);
 
+/// Builds the engine for the program at `path`.
+pub fn executable_engine(
+    path: &Path,
+    cfg: &ExecutableConfiguration,
+) -> Result<VisualizationEngine, LoadError> {
+    VisualizationEngine::new(program_command(path, cfg))
+        .map_err(|e| LoadError::Invalid(format!("{}: {e}", path.display())))
+}
+
+/// The configuration of the engine's current part, as the renderers read it.
+fn engine_settings(
+    engine: &VisualizationEngine,
+    source: Option<&Path>,
+) -> Result<VisualizationSettings<Configuration>, VisualizationError> {
+    match engine.current_part() {
+        Ok(info) => Ok(VisualizationSettings(info.configuration.clone())),
+        Err(err) => {
+            let error = VisualizationError::new(ErrorStage::Spawn, format!("{err:?}"));
+            Err(match source {
+                Some(source) => error.with_source(source),
+                None => error,
+            })
+        }
+    }
+}
+
+/// Inserts the engine of the program at `source` and its settings. The renderer is picked once
+/// the program's story is loaded, which sends [`LoadVisualization`].
+pub fn start_engine(commands: &mut Commands, engine: VisualizationEngine, source: &Path) {
+    match engine_settings(&engine, Some(source)) {
+        Ok(settings) => commands.insert_resource(settings),
+        Err(error) => report_error(commands, error),
+    }
+    commands.insert_resource(Engine(engine));
+}
+
 #[derive(Component)]
 pub struct TaggedEntity;
 
+/// Refreshes the engine settings whenever a renderer is loaded, so a retry picks up the current
+/// part.
 pub fn configure_visualization_system(
     mut events: MessageReader<LoadVisualization>,
     mut commands: Commands,
     engine: Res<Engine>,
+    dropped: Option<Res<DroppedFile>>,
 ) {
     for message in events.read() {
         trace!(
             "Message received : {:?}. Inserting configuration.",
             message.0
         );
-        if let Ok(info) = engine.current_part() {
-            commands.insert_resource(VisualizationSettings(info.configuration.clone()));
-        };
+        match engine_settings(&engine, dropped.as_ref().map(|d| d.0.as_path())) {
+            Ok(settings) => commands.insert_resource(settings),
+            Err(error) => report_error(&mut commands, error),
+        }
     }
 }
 
@@ -74,12 +195,17 @@ pub fn file_drop(mut commands: Commands, mut evr_dnd: MessageReader<FileDragAndD
             FileDragAndDrop::DroppedFile { window, path_buf } => {
                 commands.remove_resource::<HoveredFile>();
                 commands.insert_resource(DroppedFile(path_buf.clone()));
+                info!("Dropped file with path: {path_buf:?}, in window id: {window:?}");
+                if let Err(err) = fs::metadata(path_buf) {
+                    let err = LoadError::Io(path_buf.clone(), err);
+                    report_error(
+                        &mut commands,
+                        VisualizationError::from_load(ErrorStage::Detect, &err),
+                    );
+                    continue;
+                }
                 commands.insert_resource(suggestion(path_buf));
                 commands.set_state(VisualizerState::Loading);
-                println!(
-                    "Dropped file with path: {:?}, in window id: {:?}",
-                    path_buf, window
-                );
             }
         }
     }
@@ -88,17 +214,39 @@ pub fn file_drop(mut commands: Commands, mut evr_dnd: MessageReader<FileDragAndD
 pub fn load_visualization_system(
     mut events: MessageReader<LoadVisualization>,
     mut next_state: ResMut<NextState<VisualizerState>>,
+    playhead: Option<ResMut<Playhead>>,
 ) {
-    //TODO: Impl From/Into kind <=> state
     for message in events.read() {
-        let state = match message.0 {
-            // VisualizationKind::Heatmap => HeatmapVis::spawn(commands, settings),
-            VisualizationKind::Grid => VisualizerState::Grid,
-            // VisualizationKind::Volume => VolumeVis::spawn(commands, settings),
-        };
+        let state = VisualizerState::from(message.0);
         info!("Dispatching state : {state:?}");
         next_state.set(state);
     }
+    // The incoming renderer has drawn nothing yet, even if the playhead did not move.
+    if let Some(mut playhead) = playhead {
+        playhead.rendered = None;
+    }
+}
+
+/// Forgets the dropped file and everything parsed from it.
+pub fn clear_dropped_file(commands: &mut Commands) {
+    commands.remove_resource::<FileTypeSuggestion>();
+    commands.remove_resource::<DroppedFile>();
+    commands.remove_resource::<FileTypeSelection>();
+    commands.remove_resource::<StructuredDocument>();
+    commands.remove_resource::<TablePreview>();
+    commands.remove_resource::<NumpyFile>();
+    commands.remove_resource::<BinaryFile>();
+    commands.remove_resource::<PatternSearch>();
+    commands.remove_resource::<LoadingTask>();
+    commands.remove_resource::<FailedLoad>();
+}
+
+/// Hands a freshly loaded story to a renderer: `kind` when the user picked one, otherwise the
+/// best match in the renderer registry.
+pub fn start_story(commands: &mut Commands, story: Story, kind: Option<VisualizationKind>) {
+    commands.insert_resource(story);
+    commands.insert_resource(Playhead::default());
+    commands.write_message(SelectRenderer(kind));
 }
 
 pub fn unload_visualization_system(
@@ -111,23 +259,54 @@ pub fn unload_visualization_system(
     info!("Exited visualization state");
 }
 
+/// Forgets the engine or story being shown, keeping the file it came from.
+pub fn clear_visualization(commands: &mut Commands) {
+    commands.remove_resource::<Engine>();
+    commands.remove_resource::<VisualizationSettings<Configuration>>();
+    commands.remove_resource::<Story>();
+    commands.remove_resource::<Playhead>();
+    commands.remove_resource::<RendererCandidates>();
+    commands.remove_resource::<Session>();
+    commands.remove_resource::<SearchTask>();
+    commands.remove_resource::<SearchResults>();
+}
+
+/// Tears down the renderer, the engine or story it drew, and the file it came from, so that the
+/// next file starts from a clean slate.
+pub fn close_visualization_system(
+    mut commands: Commands,
+    query: Query<Entity, With<TaggedEntity>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+    clear_visualization(&mut commands);
+    clear_dropped_file(&mut commands);
+    commands.set_state(VisualizerState::Input);
+    info!("Closed visualization");
+}
+
 pub struct SimpleGridContext;
 
 storyframe::impl_render_context!(SimpleGridContext => SimpleGridContextTag);
 
-#[derive(Clone)]
+#[derive(Component, Clone)]
 pub struct SimpleGrid;
 
 impl SimpleGrid {
-    fn spawn(commands: &mut Commands, settings: &VisualizationSettings<Configuration>) {}
+    pub fn spawn(commands: &mut Commands, _settings: &VisualizationSettings<Configuration>) {
+        commands.spawn((SimpleGrid, TaggedEntity));
+        info!("Spawned {}", SimpleGrid.renderer_name());
+    }
 }
 
 impl Renderer for SimpleGrid {
     type StateSnapshot = storyframe::domains::text::state::TextSnapshot;
     type Context<'a> = SimpleGridContext;
 
-    fn render_state(&mut self, snapshot: &Self::StateSnapshot, context: &mut Self::Context<'_>) {
-        todo!()
+    fn render_state(&mut self, _snapshot: &Self::StateSnapshot, _context: &mut Self::Context<'_>) {
+        // Grid cells are drawn from the story's frames by the grid systems.
+        warn_once!("{} does not draw snapshots yet", self.renderer_name());
     }
 
     fn renderer_name(&self) -> storyframe::core::id::RendererId {